@@ -1,26 +1,430 @@
-use clap::Parser;
+use crate::app::commands::Commands;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::error::budget_error::BudgetError;
+use crate::error::error_handler::ErrorHandler;
+use crate::programs::calendar_slice_model::CalendarSliceModel;
+use crate::schema::fx_rate::DEFAULT_CURRENCY;
+use crate::schema::recurrance::{Every, Roll};
+use crate::storage::backend::StoreKind;
+use crate::test::end_to_end::calendar_slice_model::csm_test::CsmTest;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rust_decimal::Decimal;
+use std::str::FromStr;
 
-#[derive(Parser)]
+#[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
-    #[arg(short, long, default_value_t = String::from("data"))]
+    #[arg(short, long, global = true, default_value_t = String::from("data"))]
     pub path: String,
-    #[arg(short, long)]
-    pub end_yyyy_mm: String,
-    #[arg(short, long)]
-    pub start_yyyy_mm: String,
+    #[arg(long, global = true)]
+    pub log_file: Option<String>, // default: {path}/error_log.jsonl
 
-    #[arg(short, long, default_value_t = String::from("f"))]
-    pub x_test: String, // avoid cli name collision
+    #[command(subcommand)]
+    pub command: Command,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the calendar slice model from start to end
+    Simulate(SimulateArgs),
+    /// Run a single month against the initial store
+    Month(MonthArgs),
+    /// Append an event to events/one_off.json or events/reccurring.json
+    AddEvent(AddEventArgs),
+    /// List events, or every occurrence between start and end
+    ListEvents(ListEventsArgs),
+    /// Import an OFX/QFX or bank csv statement into events/one_off.json
+    Import(ImportArgs),
+    /// Compare forecast events against events/actuals.json
+    Reconcile(ReconcileArgs),
+    /// Check that the store and events load and are consistent
+    Validate(ValidateArgs),
+    /// Print the payment, payoff date and interest of each loan in events/loans.json
+    Loans,
+    /// Print totals from the store written by the last run
+    Report(ReportArgs),
+    /// Run the end-to-end test data
+    SelfTest(SelfTestArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    #[arg(short, long, value_parser = YM::try_parse)]
+    pub start: YM,
+    #[arg(short, long, value_parser = YM::try_parse)]
+    pub end: YM,
+
+    #[arg(short = 'n', long, default_value_t = 0)]
+    pub monte_carlo_runs: usize, // 0 => single deterministic run
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    #[arg(long)]
+    pub dry_run: bool, // run without writing reports or saving the store
+    // monte carlo runs write no alerts.csv, so there is nothing to fail on
+    #[arg(long, conflicts_with = "monte_carlo_runs")]
+    pub fail_on_overdraft: bool, // exit non-zero when alerts.csv records an overdraft or over_credit_limit
+    #[arg(long, value_parser = YM::try_parse)]
+    pub actuals_through: Option<YM>, // run events/actuals.json for months up to this one
+    #[arg(long)]
+    pub strict: bool, // fail on unknown account, expense or income names instead of creating them
+    #[arg(long = "scenario")]
+    pub scenarios: Vec<String>, // overlay files, each run beside the baseline into scenario_diff.csv
+    #[arg(long, default_value = DEFAULT_CURRENCY)]
+    pub base_currency: String, // summaries, budgets and statements are converted to it
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct MonthArgs {
+    #[arg(value_parser = YM::try_parse)]
+    pub month: YM,
+    #[arg(long)]
+    pub strict: bool,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    #[arg(long, default_value = DEFAULT_CURRENCY)]
+    pub base_currency: String, // totals are converted to it
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct StoreArgs {
+    #[arg(long, default_value = "csv", value_parser = StoreKind::parse)]
+    pub store: StoreKind, // "csv" or "sqlite:path.db"
+}
+
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    // events with no payment in start..end are reported, Default: 1970-01..2199-12
+    #[arg(short, long, requires = "end", value_parser = YM::try_parse)]
+    pub start: Option<YM>,
+    #[arg(short, long, requires = "start", value_parser = YM::try_parse)]
+    pub end: Option<YM>,
+    #[arg(long)]
+    pub strict: bool, // report unknown names as problems rather than notes
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ReconcileArgs {
+    #[arg(short, long, value_parser = YM::try_parse)]
+    pub start: YM,
+    #[arg(short, long, value_parser = YM::try_parse)]
+    pub end: YM,
+    #[arg(long, default_value_t = 3)]
+    pub window: i64, // days an actual may land from its forecast date
+    #[arg(long)]
+    pub dry_run: bool, // print the totals without writing reports
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    pub file: String,
+    #[arg(long)]
+    pub account: String, // must already be in accounts.csv
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>, // default: from the file extension
+    #[arg(long)]
+    pub actuals: bool, // append to events/actuals.json instead
+    #[arg(long)]
+    pub dry_run: bool, // print what would be imported
+
+    #[command(flatten)]
+    pub mapping: CsvMappingArgs,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Ofx, // also QFX
+    Csv,
+}
+
+// column headers of a bank csv export
+#[derive(Args, Debug)]
+pub struct CsvMappingArgs {
+    #[arg(long, default_value = "Date")]
+    pub date_column: String,
+    #[arg(long, default_value = "Amount")]
+    pub amount_column: String, // negative => debit
+    #[arg(long, requires = "credit_column")]
+    pub debit_column: Option<String>, // with --credit-column, instead of --amount-column
+    #[arg(long, requires = "debit_column")]
+    pub credit_column: Option<String>,
+    #[arg(long, default_value = "Description")]
+    pub memo_column: String,
+    #[arg(long, default_value = "%Y-%m-%d")]
+    pub date_format: String, // chrono format, e.g. %m/%d/%Y
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    Payment,
+    #[value(name = "payment_received")]
+    PaymentReceived,
+    Transfer,
+}
+
+impl EventType {
+    // event_type as written to the events json
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::Payment => "payment",
+            EventType::PaymentReceived => "payment_received",
+            EventType::Transfer => "transfer",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct AddEventArgs {
+    #[arg(value_enum)]
+    pub event_type: EventType,
+    pub name: String,
+
+    #[arg(long)]
+    pub account: Option<String>, // payments and payments received
+    #[arg(long)]
+    pub from: Option<String>, // transfers only
+    #[arg(long)]
+    pub to: Option<String>,
+
+    #[arg(long, value_parser = parse_decimal)]
+    pub amount: Decimal,
+    #[arg(long, value_parser = parse_decimal, requires = "amount_high")]
+    pub amount_low: Option<Decimal>,
+    #[arg(long, value_parser = parse_decimal, requires = "amount_low")]
+    pub amount_high: Option<Decimal>,
+    #[arg(long)]
+    pub currency: Option<String>, // e.g. EUR, defaults to the account's
+
+    #[arg(long, value_parser = parse_date)]
+    pub date: NaiveDate, // completed_at for one-off events, start for recurring events
+    #[arg(long, value_parser = Every::try_parse)]
+    pub every: Option<Every>, // e.g. weeks:2, makes the event recurring
+    #[arg(long, value_parser = parse_date, requires = "every")]
+    pub until: Option<NaiveDate>,
+    #[arg(long, value_parser = Roll::try_parse, requires = "every")]
+    pub roll: Option<Roll>, // none, forward or back
+    #[arg(long, requires = "every")]
+    pub count: Option<usize>, // e.g. 36 payments of a car loan
+    #[arg(long)]
+    pub until_balance_zero: bool, // transfers only, stop once --to is paid off
+
+    #[arg(long, value_delimiter = ',')]
+    pub tags: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ListEventsArgs {
+    #[arg(short, long, value_parser = YM::try_parse, requires = "end")]
+    pub start: Option<YM>,
+    #[arg(short, long, value_parser = YM::try_parse, requires = "start")]
+    pub end: Option<YM>,
+}
+
+#[derive(Args, Debug)]
+pub struct SelfTestArgs {
+    #[arg(short, long, value_parser = YM::try_parse)]
+    pub start: YM,
+    #[arg(short, long, value_parser = YM::try_parse)]
+    pub end: YM,
+}
+
+pub type CliResult = Result<(), BudgetError>;
+
 impl Cli {
-    pub fn new(path: String, start_yyyy_mm: String, end_yyyy_mm: String, x_test: String) -> Cli {
-        Cli {
-            path,
-            end_yyyy_mm,
-            start_yyyy_mm,
-            x_test,
+    pub fn run(self) -> CliResult {
+        ErrorHandler::set_log_path(
+            self.log_file
+                .clone()
+                .unwrap_or_else(|| format!("{}/error_log.jsonl", self.path)),
+        );
+        let commands = Commands::new(self.path.clone());
+        match self.command {
+            Command::Simulate(args) => CalendarSliceModel::run_cli(self.path, args),
+            Command::Month(args) => {
+                let mut model = CalendarSliceModel::new(args.month, args.month, self.path, true);
+                model.set_store_kind(args.store.store);
+                model.set_strict(args.strict);
+                model.run_month()
+            }
+            Command::AddEvent(args) => commands.add_event(args),
+            Command::ListEvents(args) => commands.list_events(args),
+            Command::Reconcile(args) => {
+                let mut model =
+                    CalendarSliceModel::new(args.start, args.end, self.path, !args.dry_run);
+                model.set_store_kind(args.store.store);
+                model.reconcile(args.window)
+            }
+            Command::Import(args) => commands.import(args),
+            Command::Validate(args) => commands.validate(args),
+            Command::Loans => commands.loans(),
+            Command::Report(args) => commands.report(args),
+            Command::SelfTest(args) => {
+                println!("Running test...");
+                if CsmTest::run(args.start, args.end) == 1 {
+                    return Err(From::from("Self test failed."));
+                };
+                println!("Test Complete");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_decimal(amount: &str) -> Result<Decimal, String> {
+    Decimal::from_str(amount).map_err(|err| format!("Invalid amount {amount:?}: {err}"))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|err| format!("Invalid date {date:?}, expected YYYY-MM-DD: {err}"))
+}
+
+#[cfg(test)]
+mod cli_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse_from__reads_simulate_args() {
+        let cli = Cli::try_parse_from([
+            "monthly_budget",
+            "-p",
+            "my_data",
+            "simulate",
+            "-s",
+            "2023-03",
+            "-e",
+            "2023-06",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert_eq!("my_data", cli.path);
+        match cli.command {
+            Command::Simulate(args) => {
+                assert_eq!(YM::new(2023, MK::Mar), args.start);
+                assert_eq!(YM::new(2023, MK::Jun), args.end);
+                assert!(args.dry_run);
+                assert_eq!(StoreKind::Csv, args.store.store);
+            }
+            _ => panic!("expected simulate"),
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse_from__rejects_fail_on_overdraft_with_monte_carlo_runs() {
+        let simulate = |args: &[&str]| {
+            Cli::try_parse_from(
+                [
+                    "monthly_budget",
+                    "simulate",
+                    "-s",
+                    "2023-01",
+                    "-e",
+                    "2023-03",
+                ]
+                .iter()
+                .chain(args.iter()),
+            )
+        };
+        assert!(simulate(&["--fail-on-overdraft"]).is_ok());
+        assert!(simulate(&["-n", "100"]).is_ok());
+        assert!(simulate(&["-n", "100", "--fail-on-overdraft"]).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse_from__rejects_invalid_year_month() {
+        assert!(Cli::try_parse_from(["monthly_budget", "month", "2023-13"]).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse_from__reads_add_event_args() {
+        let cli = Cli::try_parse_from([
+            "monthly_budget",
+            "add-event",
+            "payment_received",
+            "Space Man",
+            "--account",
+            "Credit Union",
+            "--amount",
+            "5000.00",
+            "--date",
+            "2023-02-14",
+            "--every",
+            "weeks:2",
+            "--tags",
+            "work,side",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::AddEvent(args) => {
+                assert_eq!(EventType::PaymentReceived, args.event_type);
+                assert_eq!(Decimal::new(500000, 2), args.amount);
+                assert_eq!(Some(Every::Weeks(2)), args.every);
+                assert_eq!(vec!["work".to_string(), "side".to_string()], args.tags);
+            }
+            _ => panic!("expected add-event"),
         }
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse_from__requires_every_for_until() {
+        assert!(Cli::try_parse_from([
+            "monthly_budget",
+            "add-event",
+            "payment",
+            "Rent",
+            "--account",
+            "Credit Union",
+            "--amount",
+            "100",
+            "--date",
+            "2023-02-14",
+            "--until",
+            "2024-02-14",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse_from__requires_both_amount_bounds() {
+        assert!(Cli::try_parse_from([
+            "monthly_budget",
+            "add-event",
+            "payment",
+            "Rent",
+            "--account",
+            "Credit Union",
+            "--amount",
+            "100",
+            "--amount-low",
+            "80",
+            "--date",
+            "2023-02-14",
+        ])
+        .is_err());
+    }
 }
@@ -0,0 +1,865 @@
+use crate::app::cli::{
+    AddEventArgs, EventType, ImportArgs, ImportFormat, ListEventsArgs, ReportArgs, ValidateArgs,
+};
+use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::holiday::HolidayCalendar;
+use crate::calendar::month_key::MonthKey as MK;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::loan::Loan;
+use crate::composite::payment_event::{fetch_json_event_results, PaymentEvent, RecurrenceState};
+use crate::composite::recurring_payment_event::RecurringPaymentEvent;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::suggestion::did_you_mean;
+use crate::import::bank_csv;
+use crate::import::imported_transaction::ImportedTransaction;
+use crate::import::ofx;
+use crate::programs::calendar_slice_model::CalendarSliceModel;
+use crate::schema::account::Account;
+use crate::schema::goal::{Goal, GoalStore};
+use crate::schema::opening_balance::{OpeningBalance, OpeningBalanceStore};
+use crate::storage::backend::StoreKind;
+use crate::storage::store::Store;
+use crate::traits::csv_store::CsvStore;
+use crate::traits::file_io::FileIO;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+// subcommands that read or edit the data directory without running a model
+pub struct Commands {
+    path: String,
+}
+
+pub type CommandResult = Result<(), BudgetError>;
+
+impl FileIO<Commands> for Commands {
+    fn path(&self) -> String {
+        self.path.clone()
+    }
+}
+
+impl Commands {
+    pub fn new(path: String) -> Commands {
+        Commands { path }
+    }
+
+    fn one_off_events_path(&self) -> String {
+        format!("{}/{}", self.path_events(), "one_off.json")
+    }
+
+    fn recurring_events_path(&self) -> String {
+        format!("{}/{}", self.path_events(), "reccurring.json")
+    }
+
+    fn loans_path(&self) -> String {
+        format!("{}/{}", self.path_events(), "loans.json")
+    }
+
+    pub fn add_event(&self, args: AddEventArgs) -> CommandResult {
+        match args.event_type {
+            EventType::Transfer if args.from.is_none() || args.to.is_none() => {
+                return Err(From::from("A transfer needs both --from and --to."));
+            }
+            EventType::Payment | EventType::PaymentReceived if args.until_balance_zero => {
+                return Err(From::from("--until-balance-zero is for transfers only."));
+            }
+            EventType::Payment | EventType::PaymentReceived if args.account.is_none() => {
+                return Err(From::from(format!(
+                    "A {} needs --account.",
+                    args.event_type.as_str()
+                )));
+            }
+            _ => (),
+        }
+        if let (Some(low), Some(high)) = (args.amount_low, args.amount_high) {
+            if low > high {
+                return Err(From::from(format!(
+                    "--amount-low {low} is greater than --amount-high {high}."
+                )));
+            }
+        }
+
+        match args.every {
+            Some(recurrence) => {
+                let path = self.recurring_events_path();
+                let mut events = match Path::new(&path).exists() {
+                    true => RecurringPaymentEvent::fetch_events(path.clone())?,
+                    false => vec![],
+                };
+                events.push(RecurringPaymentEvent {
+                    id: None,
+                    event_type: args.event_type.as_str().to_string(),
+                    name: args.name.clone(),
+                    account_name: args.account.unwrap_or_default(),
+                    from_account_name: args.from,
+                    to_account_name: args.to,
+                    amount: args.amount,
+                    amount_low: args.amount_low,
+                    amount_high: args.amount_high,
+                    currency: args.currency,
+                    tags: args.tags,
+                    start: args.date,
+                    end: args.until,
+                    count: args.count,
+                    until_balance_zero: args.until_balance_zero,
+                    recurrence,
+                    roll: args.roll.unwrap_or_default(),
+                });
+                RecurringPaymentEvent::write_events(path.clone(), &events)?;
+                println!("Added recurring {:?} to {path}", args.name);
+            }
+            None => {
+                let path = self.one_off_events_path();
+                let mut events = match Path::new(&path).exists() {
+                    true => PaymentEvent::fetch_events(path.clone())?,
+                    false => vec![],
+                };
+                events.push(PaymentEvent {
+                    id: None,
+                    event_type: args.event_type.as_str().to_string(),
+                    name: args.name.clone(),
+                    account_name: args.account.unwrap_or_default(),
+                    from_account_name: args.from,
+                    to_account_name: args.to,
+                    amount: args.amount,
+                    amount_low: args.amount_low,
+                    amount_high: args.amount_high,
+                    currency: args.currency,
+                    tags: args.tags,
+                    completed_at: args.date.and_hms_opt(12, 0, 0).unwrap(),
+                    recurrence_state: RecurrenceState::None,
+                    until_balance_zero: args.until_balance_zero,
+                });
+                PaymentEvent::write_events(path.clone(), &events)?;
+                println!("Added {:?} to {path}", args.name);
+            }
+        }
+        Ok(())
+    }
+
+    // appends one off events for transactions not already in one_off.json or actuals.json
+    pub fn import(&self, args: ImportArgs) -> CommandResult {
+        let mut store = Store::new();
+        store.init(Some(self.path_in()))?;
+        let account = Account::by_name(&args.account, &store.accounts).ok_or_else(|| {
+            BudgetError::UnknownAccount {
+                name: args.account.clone(),
+                did_you_mean: did_you_mean(
+                    &args.account,
+                    store.accounts.values().map(|account| account.name.as_str()),
+                ),
+            }
+        })?;
+
+        let format = args.format.unwrap_or_else(|| {
+            let file = args.file.to_lowercase();
+            match file.ends_with(".ofx") || file.ends_with(".qfx") {
+                true => ImportFormat::Ofx,
+                false => ImportFormat::Csv,
+            }
+        });
+        let transactions = match format {
+            ImportFormat::Ofx => ofx::read_ofx(&args.file)?,
+            ImportFormat::Csv => bank_csv::read_bank_csv(&args.file, &args.mapping)?,
+        };
+
+        let path = match args.actuals {
+            true => format!("{}/{}", self.path_events(), "actuals.json"),
+            false => self.one_off_events_path(),
+        };
+        let mut events = match Path::new(&path).exists() {
+            true => PaymentEvent::fetch_events(path.clone())?,
+            false => vec![],
+        };
+        let (transactions, skipped) =
+            ImportedTransaction::without_duplicates(transactions, &events, &account.name);
+        for transaction in transactions.iter() {
+            let event = transaction.to_payment_event(&account.name);
+            println!("  {}", Commands::describe_event(&event));
+            events.push(event);
+        }
+
+        if !args.dry_run {
+            PaymentEvent::write_events(path.clone(), &events)?;
+        }
+        println!(
+            "Imported {} transaction(s) into {path}, skipped {skipped} duplicate(s).{}",
+            transactions.len(),
+            match args.dry_run {
+                true => " (dry run, nothing written)",
+                false => "",
+            }
+        );
+        Ok(())
+    }
+
+    pub fn list_events(&self, args: ListEventsArgs) -> CommandResult {
+        match (args.start, args.end) {
+            (Some(start), Some(end)) => {
+                // every occurrence in the slice, including statement payments
+                let mut store = Store::new();
+                store.init(Some(self.path_in()))?;
+                let model = CalendarSliceModel::new(start, end, self.path(), false);
+                let cal_slice = model.cal_slice(start, end)?;
+                let bins = model.bin_payment_events(&cal_slice, &store.accounts)?;
+                for (year_month, bin) in bins.iter() {
+                    println!("{}", year_month.display());
+                    let mut events: Vec<&PaymentEvent> = bin.values().collect();
+                    events.sort_by_key(|event| event.completed_at);
+                    for event in events {
+                        println!("  {}", Commands::describe_event(event));
+                    }
+                }
+            }
+            _ => {
+                println!("one off");
+                for event in PaymentEvent::fetch_events(self.one_off_events_path())?.iter() {
+                    println!("  {}", Commands::describe_event(event));
+                }
+                println!("recurring");
+                let recurring_events =
+                    RecurringPaymentEvent::fetch_events(self.recurring_events_path())?;
+                for event in recurring_events.iter() {
+                    let mut stops: Vec<String> = vec![];
+                    if let Some(end) = event.end {
+                        stops.push(format!("until {end}"));
+                    }
+                    if let Some(count) = event.count {
+                        stops.push(format!("{count} times"));
+                    }
+                    if event.until_balance_zero {
+                        stops.push("until paid off".to_string());
+                    }
+                    println!(
+                        "  {} every {:?} {}",
+                        Commands::describe_event(&event.to_payment_event(&event.start)),
+                        event.recurrence,
+                        stops.join(", ")
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn describe_event(event: &PaymentEvent) -> String {
+        let accounts = match (&event.from_account_name, &event.to_account_name) {
+            (Some(from), Some(to)) => format!("{from} -> {to}"),
+            _ => event.account_name.clone(),
+        };
+        format!(
+            "{} {:<16} {:<24} {:<32} {:>12}",
+            event.completed_at.date(),
+            event.event_type,
+            event.name,
+            accounts,
+            event.amount
+        )
+    }
+
+    // reports every problem in init/ and events/ rather than stopping at the first
+    // strict turns the accounts, expenses and incomes events would create into problems
+    pub fn validate(&self, args: ValidateArgs) -> CommandResult {
+        let problems = self.validation_problems(&args)?;
+        if problems.is_empty() {
+            println!("OK");
+            return Ok(());
+        }
+        for problem in problems.iter() {
+            println!("  {problem}");
+        }
+        Err(From::from(format!("{} problem(s) found.", problems.len())))
+    }
+
+    fn validation_problems(&self, args: &ValidateArgs) -> BudgetResult<Vec<String>> {
+        let mut problems: Vec<String> = vec![];
+
+        let mut store = Store::new();
+        match &args.store.store {
+            StoreKind::Csv => problems.extend(
+                store
+                    .read_tables(&self.path_in(), true)
+                    .iter()
+                    .map(|err| err.to_string()),
+            ),
+            store_kind => store_kind
+                .backend(self.path_in(), self.path_out())
+                .load(&mut store)?,
+        }
+        problems.extend(store.dangling_references());
+
+        let mut opening_balances = OpeningBalanceStore::new();
+        let opening_balance_errors = OpeningBalance::read_rows_if_exists(
+            &mut opening_balances,
+            &format!("{}/{}", self.path_in(), "opening_balances.csv"),
+            true,
+        );
+        problems.extend(opening_balance_errors.iter().map(|err| err.to_string()));
+        if let Err(err) = OpeningBalance::year_months(&opening_balances) {
+            problems.push(err.to_string());
+        }
+
+        let mut goals = GoalStore::new();
+        let goal_errors = Goal::read_rows_if_exists(
+            &mut goals,
+            &format!("{}/{}", self.path_in(), "goals.csv"),
+            true,
+        );
+        problems.extend(goal_errors.iter().map(|err| err.to_string()));
+        for goal in goals.values() {
+            if let Err(err) = goal.link() {
+                problems.push(err.to_string());
+            }
+        }
+
+        let one_off_events = Commands::read_events(
+            &self.one_off_events_path(),
+            |e: &PaymentEvent| e.id,
+            &mut problems,
+        );
+        let recurring_events = Commands::read_events(
+            &self.recurring_events_path(),
+            |e: &RecurringPaymentEvent| e.id,
+            &mut problems,
+        );
+        let actuals_path = format!("{}/{}", self.path_events(), "actuals.json");
+        let actual_events = match Path::new(&actuals_path).exists() {
+            true => Commands::read_events(&actuals_path, |e: &PaymentEvent| e.id, &mut problems),
+            false => vec![],
+        };
+        let loans = match Path::new(&self.loans_path()).exists() {
+            true => Commands::read_events(&self.loans_path(), |l: &Loan| l.id, &mut problems),
+            false => vec![],
+        };
+
+        // without --start/--end only dates a typo in the year would produce are flagged
+        let mut cal_slice = match (args.start, args.end) {
+            (Some(start), Some(end)) => CalendarSlice::new(start, end)?,
+            _ => CalendarSlice::new(YM::new(1970, MK::Jan), YM::new(2199, MK::Dec))?,
+        };
+        cal_slice.set_holidays(HolidayCalendar::load(&self.path_in())?);
+        let slice_start = cal_slice.start.start_of_month();
+        let slice_end = cal_slice.end.start_of_next_month();
+        let outside_slice = format!(
+            "falls outside {} - {}",
+            cal_slice.start.display(),
+            cal_slice.end.display()
+        );
+
+        for event in one_off_events.iter().chain(actual_events.iter()) {
+            problems.extend(Commands::event_problems(event));
+            let date = event.completed_at.date();
+            if date < slice_start || slice_end <= date {
+                problems.push(format!("{:?}: {date} {outside_slice}", event.name));
+            }
+        }
+        let recurring_payment_events: Vec<PaymentEvent> = recurring_events
+            .iter()
+            .map(|event| event.to_payment_event(&event.start))
+            .collect();
+        for (event, payment_event) in recurring_events.iter().zip(recurring_payment_events.iter()) {
+            problems.extend(Commands::event_problems(payment_event));
+            if let Some(end) = event.end {
+                if event.start > end {
+                    problems.push(format!(
+                        "{:?}: start {} is after end {end}",
+                        event.name, event.start
+                    ));
+                }
+            }
+            if event.count == Some(0) {
+                problems.push(format!("{:?}: count 0 never pays", event.name));
+            }
+            let in_slice = event
+                .payment_dates(&cal_slice)
+                .iter()
+                .any(|date| slice_start <= *date && *date < slice_end);
+            if !in_slice {
+                problems.push(format!("{:?}: every payment {outside_slice}", event.name));
+            }
+        }
+
+        for loan in loans.iter() {
+            problems.extend(Commands::loan_problems(loan));
+        }
+
+        // loans create their own liability account and interest expense, not the account paying
+        let mut unknown_names = PaymentEvent::unknown_names(
+            one_off_events
+                .iter()
+                .chain(recurring_payment_events.iter())
+                .chain(actual_events.iter()),
+            &store,
+        );
+        for loan in loans.iter() {
+            if Account::by_name(&loan.payment_account_name, &store.accounts).is_none() {
+                unknown_names.push(BudgetError::UnknownAccount {
+                    name: loan.payment_account_name.clone(),
+                    did_you_mean: did_you_mean(
+                        &loan.payment_account_name,
+                        store.accounts.values().map(|account| account.name.as_str()),
+                    ),
+                });
+            }
+        }
+        // the simulation stops on these in either mode, loans add their own account first
+        problems.extend(
+            OpeningBalance::unknown_names(&opening_balances, &store)
+                .into_iter()
+                .filter(|err| match err {
+                    BudgetError::UnknownAccount { name, .. } => {
+                        !loans.iter().any(|loan| &loan.account_name() == name)
+                    }
+                    _ => true,
+                })
+                .map(|err| err.to_string()),
+        );
+        for err in unknown_names.into_iter() {
+            match args.strict {
+                true => problems.push(err.to_string()),
+                // not an error, the composites add the record on first use
+                false => println!("  note: {err} It will be created on first use."),
+            }
+        }
+
+        println!(
+            "Loaded {} account(s), {} one off, {} recurring and {} actual event(s), {} loan(s)",
+            store.accounts.len(),
+            one_off_events.len(),
+            recurring_events.len(),
+            actual_events.len(),
+            loans.len()
+        );
+        Ok(problems)
+    }
+
+    // every event that parses, a problem for each that does not or repeats an id
+    fn read_events<T: DeserializeOwned>(
+        path: &str,
+        id: impl Fn(&T) -> Option<usize>,
+        problems: &mut Vec<String>,
+    ) -> Vec<T> {
+        let results = match fetch_json_event_results::<T>(path) {
+            Err(err) => {
+                problems.push(err.to_string());
+                return vec![];
+            }
+            Ok(results) => results,
+        };
+
+        let mut ids: BTreeSet<usize> = BTreeSet::new();
+        let mut events: Vec<T> = vec![];
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Err(err) => problems.push(err.to_string()),
+                Ok(event) => {
+                    if let Some(event_id) = id(&event) {
+                        if !ids.insert(event_id) {
+                            problems.push(format!("{path} event {index}: duplicate id {event_id}"));
+                        }
+                    }
+                    events.push(event);
+                }
+            }
+        }
+        events
+    }
+
+    fn loan_problems(loan: &Loan) -> Vec<String> {
+        let name = &loan.name;
+        let mut problems: Vec<String> = vec![];
+        if loan.principal <= Decimal::new(0, 0) {
+            problems.push(format!(
+                "{name:?}: principal {} must be greater than 0",
+                loan.principal
+            ));
+        }
+        if loan.rate < Decimal::new(0, 0) {
+            problems.push(format!("{name:?}: rate {} is negative", loan.rate));
+        }
+        if loan.term_months == 0 {
+            problems.push(format!("{name:?}: term_months must be at least 1"));
+        }
+        if loan.payment_account_name.is_empty() {
+            problems.push(format!("{name:?}: missing payment_account_name"));
+        }
+        let extras = loan
+            .extra_principal
+            .into_iter()
+            .chain(loan.extra_payments.iter().map(|extra| extra.amount));
+        for amount in extras.filter(|amount| *amount <= Decimal::new(0, 0)) {
+            problems.push(format!(
+                "{name:?}: extra principal {amount} must be greater than 0"
+            ));
+        }
+        problems
+    }
+
+    fn event_problems(event: &PaymentEvent) -> Vec<String> {
+        let name = &event.name;
+        let mut problems: Vec<String> = vec![];
+        let account_names: Vec<&str> = match event.event_type.as_str() {
+            "payment" | "payment_received" => vec![event.account_name.as_str()],
+            "transfer" => match (&event.from_account_name, &event.to_account_name) {
+                (Some(from), Some(to)) => vec![from.as_str(), to.as_str()],
+                _ => {
+                    problems.push(format!(
+                        "{name:?}: transfer needs from_account_name and to_account_name"
+                    ));
+                    vec![]
+                }
+            },
+            event_type => {
+                problems.push(format!("{name:?}: unknown event_type {event_type:?}"));
+                vec![]
+            }
+        };
+        for account in account_names {
+            if account.is_empty() {
+                problems.push(format!("{name:?}: missing account_name"));
+            }
+        }
+        if event.amount <= Decimal::new(0, 0) {
+            problems.push(format!(
+                "{name:?}: amount {} must be greater than 0",
+                event.amount
+            ));
+        }
+        for (field, amount) in [
+            ("amount_low", event.amount_low),
+            ("amount_high", event.amount_high),
+        ] {
+            if let Some(amount) = amount.filter(|amount| *amount < Decimal::new(0, 0)) {
+                problems.push(format!("{name:?}: {field} {amount} is negative"));
+            }
+        }
+        match (event.amount_low, event.amount_high) {
+            (Some(low), Some(high)) if low > high => problems.push(format!(
+                "{name:?}: amount_low {low} is greater than amount_high {high}"
+            )),
+            (Some(_), None) | (None, Some(_)) => problems.push(format!(
+                "{name:?}: amount_low and amount_high must be set together"
+            )),
+            _ => (),
+        }
+        if event.until_balance_zero && event.event_type != "transfer" {
+            problems.push(format!(
+                "{name:?}: until_balance_zero is for transfers only"
+            ));
+        }
+        problems
+    }
+
+    // over the life of each loan, extra principal against the schedule as signed
+    pub fn loans(&self) -> CommandResult {
+        for loan in Loan::fetch_loans(self.loans_path())?.iter() {
+            let summary = loan.summary();
+            let date = |date: Option<NaiveDate>| date.map_or("-".to_string(), |d| d.to_string());
+            println!(
+                "{:<24} {:>12} {:>8}% over {} months, {} a month",
+                loan.name,
+                loan.principal,
+                (loan.rate * Decimal::new(100, 0)).normalize(),
+                loan.term_months,
+                summary.monthly_payment
+            );
+            println!(
+                "  paid off {} with {} interest",
+                date(summary.payoff_date),
+                summary.total_interest
+            );
+            if summary.months_saved > 0 || !summary.interest_saved.is_zero() {
+                println!(
+                    "  scheduled {} with {} interest, {} month(s) and {} interest saved",
+                    date(summary.scheduled_payoff_date),
+                    summary.scheduled_total_interest,
+                    summary.months_saved,
+                    summary.interest_saved
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn report(&self, args: ReportArgs) -> CommandResult {
+        // the final store the last run saved to reports/
+        let base_currency = args.base_currency.as_str();
+        let mut store = Store::new();
+        args.store
+            .store
+            .backend(self.path_in(), self.path_out())
+            .load_saved(&mut store)?;
+
+        println!("accounts");
+        let account_ids: Vec<usize> = store.accounts.keys().cloned().collect();
+        for account_id in account_ids.iter() {
+            let account = Account::by_id(*account_id, &mut store.accounts).unwrap();
+            let balance = account.current_balance(&mut store.account_balances);
+            println!("  {:<32} {balance:>12}", account.name);
+        }
+
+        println!("expenses");
+        for summary in
+            CalendarSliceModel::construct_payment_summary(&mut store, base_currency)?.values()
+        {
+            println!("  {:<32} {:>12}", summary.name, summary.total);
+        }
+
+        println!("incomes");
+        for summary in
+            CalendarSliceModel::construct_payment_received_summary(&mut store, base_currency)?
+                .values()
+        {
+            println!("  {:<32} {:>12}", summary.name, summary.total);
+        }
+
+        let tag_summary = CalendarSliceModel::construct_tag_summary(&mut store, base_currency)?;
+        if !tag_summary.is_empty() {
+            println!("tags");
+            for summary in tag_summary.values() {
+                println!(
+                    "  {:<32} {:>12} {:>12}",
+                    summary.name, summary.expense_total, summary.income_total
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod commands_spec {
+    use super::*;
+    use crate::app::cli::{CsvMappingArgs, StoreArgs};
+    use crate::schema::recurrance::Every;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::fs;
+
+    fn commands(dir: &str) -> Commands {
+        let path = format!("{}/{dir}", std::env::temp_dir().display());
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(format!("{path}/events")).unwrap();
+        Commands::new(path)
+    }
+
+    fn commands_with_test_init(dir: &str) -> Commands {
+        let commands = commands(dir);
+        let init = format!("{}/init", commands.path());
+        fs::create_dir_all(&init).unwrap();
+        for entry in fs::read_dir("src/test/data/init").unwrap() {
+            let entry = entry.unwrap();
+            fs::copy(
+                entry.path(),
+                format!("{init}/{}", entry.file_name().to_str().unwrap()),
+            )
+            .unwrap();
+        }
+        commands
+    }
+
+    fn validate_args(strict: bool) -> ValidateArgs {
+        ValidateArgs {
+            start: None,
+            end: None,
+            strict,
+            store: StoreArgs {
+                store: StoreKind::Csv,
+            },
+        }
+    }
+
+    fn add_event_args(every: Option<Every>) -> AddEventArgs {
+        AddEventArgs {
+            event_type: EventType::Payment,
+            name: "Rent".to_string(),
+            account: Some("Credit Union".to_string()),
+            from: None,
+            to: None,
+            amount: Decimal::new(150000, 2),
+            amount_low: None,
+            amount_high: None,
+            currency: None,
+            date: NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            every,
+            until: None,
+            roll: None,
+            count: None,
+            until_balance_zero: false,
+            tags: vec!["housing".to_string()],
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn add_event__appends_one_off_event() {
+        let commands = commands("monthly_budget_add_one_off");
+        commands.add_event(add_event_args(None)).unwrap();
+        commands.add_event(add_event_args(None)).unwrap();
+
+        let events = PaymentEvent::fetch_events(commands.one_off_events_path()).unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!("payment", events[0].event_type);
+        assert_eq!(Decimal::new(1500, 0), events[0].amount);
+        assert_eq!(vec!["housing".to_string()], events[0].tags);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn add_event__appends_recurring_event_when_every_is_given() {
+        let commands = commands("monthly_budget_add_recurring");
+        commands
+            .add_event(add_event_args(Some(Every::Months(1))))
+            .unwrap();
+
+        let events = RecurringPaymentEvent::fetch_events(commands.recurring_events_path()).unwrap();
+        assert_eq!(1, events.len());
+        assert_eq!(Every::Months(1), events[0].recurrence);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            events[0].start
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn add_event__returns_err_for_transfer_without_accounts() {
+        let commands = commands("monthly_budget_add_transfer");
+        let mut args = add_event_args(None);
+        args.event_type = EventType::Transfer;
+        assert!(commands.add_event(args).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn import__skips_transactions_already_imported() {
+        let commands = commands_with_test_init("monthly_budget_import");
+        let file = format!("{}/statement.ofx", commands.path());
+        fs::write(
+            &file,
+            "<OFX><STMTTRN><DTPOSTED>20230301<TRNAMT>-4.50<NAME>COFFEE SHOP</STMTTRN>\
+             <STMTTRN><DTPOSTED>20230302<TRNAMT>2500.00<NAME>PAYROLL</STMTTRN></OFX>",
+        )
+        .unwrap();
+        let args = || ImportArgs {
+            file: file.clone(),
+            account: "piggybank".to_string(),
+            format: None,
+            actuals: false,
+            dry_run: false,
+            mapping: CsvMappingArgs {
+                date_column: "Date".to_string(),
+                amount_column: "Amount".to_string(),
+                debit_column: None,
+                credit_column: None,
+                memo_column: "Description".to_string(),
+                date_format: "%Y-%m-%d".to_string(),
+            },
+        };
+
+        commands.import(args()).unwrap();
+        commands.import(args()).unwrap();
+
+        let events = PaymentEvent::fetch_events(commands.one_off_events_path()).unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!("payment", events[0].event_type);
+        assert_eq!(Decimal::new(450, 2), events[0].amount);
+        assert_eq!("payment_received", events[1].event_type);
+        assert_eq!("piggybank", events[1].account_name);
+
+        let mut unknown = args();
+        unknown.account = "Big Bank".to_string();
+        assert!(matches!(
+            commands.import(unknown),
+            Err(BudgetError::UnknownAccount { .. })
+        ));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn validate__passes_end_to_end_data() {
+        let commands = Commands::new("src/test/end_to_end/calendar_slice_model/data".to_string());
+        assert!(commands.validate(validate_args(false)).is_ok());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn validate__reports_unknown_names_in_strict_mode() {
+        let commands = commands_with_test_init("monthly_budget_validate_strict");
+        let mut args = add_event_args(None);
+        args.name = "mortgage".to_string();
+        args.account = Some("piggybank".to_string());
+        commands.add_event(args).unwrap();
+        RecurringPaymentEvent::write_events(commands.recurring_events_path(), &[]).unwrap();
+
+        assert!(commands.validate(validate_args(true)).is_ok());
+
+        let mut typo = add_event_args(None);
+        typo.name = "mortgage".to_string();
+        typo.account = Some("piggybnak".to_string());
+        commands.add_event(typo).unwrap();
+        assert!(commands.validate(validate_args(false)).is_ok());
+        assert!(commands.validate(validate_args(true)).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn validation_problems__reports_every_problem_in_init_and_events() {
+        let commands = commands_with_test_init("monthly_budget_validate_problems");
+        let init = format!("{}/init", commands.path());
+        let payments = fs::read_to_string(format!("{init}/payments.csv")).unwrap();
+        fs::write(
+            format!("{init}/payments.csv"),
+            format!("{payments}99,2023-03-01T12:00:00,1,1,42\n"),
+        )
+        .unwrap();
+        fs::write(
+            format!("{init}/goals.csv"),
+            "id,name,target_amount,target_date,account_name,tag\n1,Rainy Day,100.0,2023-12-31,piggybank,savings\n",
+        )
+        .unwrap();
+        fs::write(
+            commands.one_off_events_path(),
+            r#"[
+  { "id": 1, "event_type": "payment", "name": "mortgage", "account_name": "piggybank",
+    "amount": 0.0, "completed_at": "2023-03-01T12:00:00", "recurrence_state": { "rs": "None" } },
+  { "id": 1, "event_type": "refund", "name": "food", "account_name": "piggybank",
+    "amount": 10.0, "completed_at": "2203-03-01T12:00:00", "recurrence_state": { "rs": "None" } },
+  { "event_type": "payment", "name": "food", "account_name": "piggybank", "amount": "ten",
+    "completed_at": "2023-03-01T12:00:00", "recurrence_state": { "rs": "None" } }
+]"#,
+        )
+        .unwrap();
+        fs::write(
+            commands.recurring_events_path(),
+            r#"[
+  { "event_type": "payment", "name": "dog food", "account_name": "piggybank", "amount": 5.0,
+    "amount_low": 3.0, "start": "2023-05-01", "end": "2023-04-01", "recurrence": { "t": "Weeks", "c": 1 } }
+]"#,
+        )
+        .unwrap();
+
+        let problems = commands.validation_problems(&validate_args(false)).unwrap();
+        let expected = [
+            "payments.csv id 99: expense_id 42 does not exist",
+            r#"goal 1 "Rainy Day": set one of account_name or tag"#,
+            r#""mortgage": amount 0 must be greater than 0"#,
+            "event 1: duplicate id 1",
+            "event 2 line 6 column",
+            r#""food": unknown event_type "refund""#,
+            r#""food": 2203-03-01 falls outside 1970-01 - 2199-12"#,
+            r#""dog food": start 2023-05-01 is after end 2023-04-01"#,
+            r#""dog food": every payment falls outside"#,
+            r#""dog food": amount_low and amount_high must be set together"#,
+        ];
+        for problem in expected {
+            assert!(
+                problems.iter().any(|p| p.contains(problem)),
+                "{problem:?} not in {problems:#?}"
+            );
+        }
+        assert_eq!(expected.len(), problems.len(), "{problems:#?}");
+    }
+}
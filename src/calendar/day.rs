@@ -1,8 +1,16 @@
+use crate::composite::alert::{Alert, AlertStore};
+use crate::composite::interest_accrual::{
+    InterestAccrual, InterestAccrualStore, UnpostedInterestStore,
+};
 use crate::composite::payment_composite::{PaymentComposite, PaymentCompositeStore};
-use crate::composite::payment_event::{PaymentEvent, PaymentEventComposite};
+use crate::composite::payment_event::{PaymentEvent, PaymentEventComposite, RecurrenceState};
 use crate::composite::payment_received_composite::{
     PaymentReceivedComposite, PaymentReceivedCompositeStore,
 };
+use crate::composite::transfer_composite::{TransferComposite, TransferCompositeStore};
+use crate::error::budget_error::BudgetError;
+use crate::error::error_handler::ErrorHandler;
+use crate::schema::account::Account;
 use crate::storage::store::Store;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
@@ -10,14 +18,16 @@ use chrono::NaiveDate;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
-use std::error::Error;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Day {
     pub id: Option<usize>,
     pub payments: PaymentCompositeStore,
     pub payments_received: PaymentReceivedCompositeStore,
+    pub transfers: TransferCompositeStore,
+    pub interest_accruals: InterestAccrualStore,
+    pub alerts: AlertStore, // in execution order
     pub date: NaiveDate,
 }
 
@@ -47,6 +57,9 @@ impl Day {
             id: None,
             payments: PaymentCompositeStore::new(),
             payments_received: PaymentReceivedCompositeStore::new(),
+            transfers: TransferCompositeStore::new(),
+            interest_accruals: InterestAccrualStore::new(),
+            alerts: AlertStore::new(),
             date: NaiveDate::from_ymd_opt(year, month, day).unwrap(),
         }
     }
@@ -56,7 +69,14 @@ impl Day {
             PaymentEventComposite::PR(pymnt_rec_composite) => {
                 self.add_payment_received(pymnt_rec_composite)
             }
-            PaymentEventComposite::None => (),
+            PaymentEventComposite::T(transfer_composite) => self.add_transfer(transfer_composite),
+            // validate reports these up front
+            PaymentEventComposite::None => ErrorHandler::log(From::from(format!(
+                "WARNING: {:?} on {} was skipped, unknown event_type {:?} or missing account.",
+                payment_event.name,
+                payment_event.completed_at.date(),
+                payment_event.event_type
+            ))),
         }
     }
 
@@ -68,6 +88,10 @@ impl Day {
         PaymentReceivedComposite::save_to_store(payment_rec_comp, &mut self.payments_received);
     }
 
+    pub fn add_transfer(&mut self, transfer_comp: TransferComposite) {
+        TransferComposite::save_to_store(transfer_comp, &mut self.transfers);
+    }
+
     pub fn payment_event_ids_chrono(&self) -> Vec<(usize, NaiveDateTime, &str)> {
         // TODO: refactor to use this method at the start of execute_payments_in_order
         // temporarily avoided due to mut/immut references to .self
@@ -84,11 +108,19 @@ impl Day {
             ))
         }
 
+        for (id, transfer) in self.transfers.iter() {
+            payment_times.push((*id, transfer.transfer_completed_at, "transfer"));
+        }
+
+        for (id, accrual) in self.interest_accruals.iter() {
+            payment_times.push((*id, accrual.accrued_at, "interest"));
+        }
+
         payment_times.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
         payment_times
     }
 
-    pub fn execute_payments_in_order(&mut self, store: &mut Store) -> Result<(), Box<dyn Error>> {
+    pub fn execute_payments_in_order(&mut self, store: &mut Store) -> Result<(), BudgetError> {
         let mut payment_times: Vec<(usize, NaiveDateTime, &str)> = vec![];
         for (id, pymnt) in self.payments.iter() {
             payment_times.push((*id, pymnt.payment_completed_at, "payment"));
@@ -102,23 +134,65 @@ impl Day {
             ))
         }
 
+        for (id, transfer) in self.transfers.iter() {
+            payment_times.push((*id, transfer.transfer_completed_at, "transfer"));
+        }
+
         payment_times.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
 
         for pymnt_event in payment_times.iter() {
             match pymnt_event.2 {
                 "payment" => {
                     if let Entry::Occupied(mut record) = self.payments.entry(pymnt_event.0) {
-                        record
-                            .get_mut()
-                            .create_payment(store, Some(pymnt_event.1))?;
+                        let payment = record.get_mut();
+                        payment.create_payment(store, Some(pymnt_event.1))?;
+                        if let Some(alert) = Alert::check(
+                            &store.accounts,
+                            payment.account_id.unwrap(),
+                            "payment",
+                            &payment.expense_name,
+                            payment.payment_completed_at,
+                            (payment.prev_balance, payment.ending_balance),
+                        ) {
+                            Alert::save_to_store(alert, &mut self.alerts);
+                        }
                     }
                 }
                 "payment_received" => {
                     if let Entry::Occupied(mut record) = self.payments_received.entry(pymnt_event.0)
                     {
-                        record
-                            .get_mut()
-                            .create_payment_received(store, Some(pymnt_event.1))?;
+                        let payment_received = record.get_mut();
+                        payment_received.create_payment_received(store, Some(pymnt_event.1))?;
+                        // a negative amount is a debit
+                        if let Some(alert) = Alert::check(
+                            &store.accounts,
+                            payment_received.account_id.unwrap(),
+                            "payment_received",
+                            &payment_received.income_name,
+                            payment_received.payment_received_completed_at,
+                            (
+                                payment_received.prev_balance,
+                                payment_received.ending_balance,
+                            ),
+                        ) {
+                            Alert::save_to_store(alert, &mut self.alerts);
+                        }
+                    }
+                }
+                "transfer" => {
+                    if let Entry::Occupied(mut record) = self.transfers.entry(pymnt_event.0) {
+                        let transfer = record.get_mut();
+                        transfer.create_transfer(store, Some(pymnt_event.1))?;
+                        if let Some(alert) = Alert::check(
+                            &store.accounts,
+                            transfer.from_account_id.unwrap(),
+                            "transfer",
+                            &transfer.name,
+                            transfer.transfer_completed_at,
+                            (transfer.from_prev_balance, transfer.from_ending_balance),
+                        ) {
+                            Alert::save_to_store(alert, &mut self.alerts);
+                        }
                     }
                 }
                 _ => (),
@@ -126,13 +200,82 @@ impl Day {
         }
         Ok(())
     }
+
+    // drops until_balance_zero transfers of a recurrence that already paid its account off
+    pub fn skip_paid_off_transfers(&mut self, paid_off: &BTreeSet<String>) {
+        self.transfers.retain(|_id, transfer| {
+            !(transfer.until_balance_zero && paid_off.contains(&transfer.name))
+        });
+    }
+
+    // after execute_payments_in_order, names of until_balance_zero transfers that ended their recurrence
+    // those that found nothing owed moved nothing and are dropped
+    pub fn end_paid_off_transfers(&mut self) -> Vec<String> {
+        let paid_off = self
+            .transfers
+            .values()
+            .filter(|transfer| {
+                transfer.until_balance_zero
+                    && matches!(transfer.recurrence_state, RecurrenceState::Last)
+            })
+            .map(|transfer| transfer.name.clone())
+            .collect();
+        self.transfers.retain(|_id, transfer| {
+            !(transfer.until_balance_zero && transfer.to_account_balance_id.is_none())
+        });
+        paid_off
+    }
+
+    // accrue interest at the end of the day to each account whose compounding period ends today
+    // at full precision, daily interest compounds on what is accrued but not yet posted
+    // what was accrued is posted on the last day of the month, rounded once
+    pub fn accrue_interest(
+        &mut self,
+        store: &mut Store,
+        last_day_of_month: bool,
+        unposted: &mut UnpostedInterestStore,
+    ) -> Result<(), BudgetError> {
+        let accrued_at = self.date.and_hms_opt(23, 59, 59).unwrap();
+        let account_ids: Vec<usize> = store.accounts.keys().cloned().collect();
+        for account_id in account_ids.iter() {
+            let account = Account::by_id(*account_id, &mut store.accounts).unwrap();
+            let periods_per_year = match account.interest_periods_due(last_day_of_month) {
+                Some(periods_per_year) => periods_per_year,
+                None => continue,
+            };
+            let accrued = unposted.entry(*account_id).or_default();
+            let balance = account.current_balance(&mut store.account_balances) + *accrued;
+            *accrued += InterestAccrual::period_interest(&account, balance, periods_per_year);
+            if !last_day_of_month {
+                continue;
+            }
+
+            let accrued = unposted.remove(account_id).unwrap_or_default();
+            let mut accrual = InterestAccrual::new(&account, periods_per_year, accrued_at);
+            accrual.create_accrual(store, accrued)?;
+            if accrual.account_balance_id.is_some() {
+                // interest charged on an overdrawn account or a maxed out card digs it deeper
+                if let Some(alert) = Alert::check(
+                    &store.accounts,
+                    accrual.account_id,
+                    "interest",
+                    &accrual.account_name,
+                    accrual.accrued_at,
+                    (accrual.prev_balance, accrual.ending_balance),
+                ) {
+                    Alert::save_to_store(alert, &mut self.alerts);
+                }
+                InterestAccrual::save_to_store(accrual, &mut self.interest_accruals);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod day_spec {
 
     use super::*;
-    use crate::composite::payment_event::RecurrenceState;
     use crate::schema::account::Account;
     use crate::schema::account_balance::AccountBalance;
     use crate::schema::account_balance::AccountBalanceStore;
@@ -147,7 +290,6 @@ mod day_spec {
 
         let mut day = Day::new(2023, 6, 6);
         let payment_event = PaymentEvent {
-            id: None,
             event_type: "payment".to_string(),
             name: "My Payment".to_string(),
             account_name: "Big Bank".to_string(),
@@ -156,7 +298,7 @@ mod day_spec {
                 .unwrap()
                 .and_hms_opt(12, 00, 00)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         };
 
         assert_eq!(0, day.payments.len());
@@ -172,7 +314,6 @@ mod day_spec {
 
         let mut day = Day::new(2023, 6, 6);
         let payment_event = PaymentEvent {
-            id: None,
             event_type: "payment_received".to_string(),
             name: "My Payment Received".to_string(),
             account_name: "Big Bank".to_string(),
@@ -181,7 +322,7 @@ mod day_spec {
                 .unwrap()
                 .and_hms_opt(12, 00, 00)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         };
 
         assert_eq!(0, day.payments_received.len());
@@ -189,6 +330,213 @@ mod day_spec {
         assert_eq!(1, day.payments_received.len());
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn add_payment_event__adds_transfer_when_event_0_is_transfer() {
+        let mut day = Day::new(2023, 6, 6);
+        let payment_event = PaymentEvent {
+            event_type: "transfer".to_string(),
+            name: "Savings Sweep".to_string(),
+            from_account_name: Some("Big Bank".to_string()),
+            to_account_name: Some("Piggy Bank".to_string()),
+            amount: Decimal::new(12345, 2),
+            completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
+                .unwrap()
+                .and_hms_opt(12, 00, 00)
+                .unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(0, day.transfers.len());
+        day.add_payment_event(&payment_event);
+        assert_eq!(1, day.transfers.len());
+        assert_eq!(0, day.payments.len());
+        assert_eq!(0, day.payments_received.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn execute_payments_in_order__enacts_transfers_between_payments_in_chrono_order() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut day = Day::new(2023, 6, 6);
+        day.add_payment_event(&PaymentEvent {
+            event_type: "payment_received".to_string(),
+            name: "Paycheck".to_string(),
+            account_name: "Checking".to_string(),
+            amount: Decimal::new(1000, 0),
+            completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
+                .unwrap()
+                .and_hms_opt(9, 00, 00)
+                .unwrap(),
+            ..Default::default()
+        });
+        day.add_payment_event(&PaymentEvent {
+            event_type: "transfer".to_string(),
+            name: "Savings Sweep".to_string(),
+            from_account_name: Some("Checking".to_string()),
+            to_account_name: Some("Savings".to_string()),
+            amount: Decimal::new(300, 0),
+            completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
+                .unwrap()
+                .and_hms_opt(10, 00, 00)
+                .unwrap(),
+            ..Default::default()
+        });
+
+        day.execute_payments_in_order(&mut store).unwrap();
+
+        let checking = Account::by_name("Checking", &store.accounts).unwrap();
+        let savings = Account::by_name("Savings", &store.accounts).unwrap();
+        assert_eq!(
+            Decimal::new(700, 0),
+            checking.current_balance(&mut store.account_balances)
+        );
+        assert_eq!(
+            Decimal::new(300, 0),
+            savings.current_balance(&mut store.account_balances)
+        );
+        assert_eq!(4, store.payments.len()); // no new Payment
+        assert_eq!(3, store.expenses.len()); // no new Expense
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn execute_payments_in_order__builds_on_balances_reported_at_the_same_moment() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let noon = NaiveDate::from_ymd_opt(2023, 6, 6)
+            .unwrap()
+            .and_hms_opt(12, 00, 00)
+            .unwrap();
+        let mut day = Day::new(2023, 6, 6);
+        day.add_payment_event(&PaymentEvent {
+            event_type: "payment_received".to_string(),
+            name: "Paycheck".to_string(),
+            account_name: "Checking".to_string(),
+            amount: Decimal::new(1000, 0),
+            completed_at: noon,
+            ..Default::default()
+        });
+        for (name, amount) in [("Rent", 300), ("Groceries", 200)] {
+            day.add_payment_event(&PaymentEvent {
+                event_type: "payment".to_string(),
+                name: name.to_string(),
+                account_name: "Checking".to_string(),
+                amount: Decimal::new(amount, 0),
+                completed_at: noon,
+                ..Default::default()
+            });
+        }
+
+        day.execute_payments_in_order(&mut store).unwrap();
+
+        let checking = Account::by_name("Checking", &store.accounts).unwrap();
+        assert_eq!(
+            Decimal::new(500, 0),
+            checking.current_balance(&mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn accrue_interest__posts_monthly_interest_only_on_last_day_of_month() {
+        use crate::schema::account::Compounding;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(1, |account| {
+            account.interest_rate = Some(Decimal::new(12, 2));
+            account.compounding = Compounding::Monthly;
+        });
+
+        let mut day = Day::new(2023, 6, 29);
+        day.accrue_interest(&mut store, false, &mut UnpostedInterestStore::new())
+            .unwrap();
+        assert_eq!(0, day.interest_accruals.len());
+
+        let mut last_day = Day::new(2023, 6, 30);
+        last_day
+            .accrue_interest(&mut store, true, &mut UnpostedInterestStore::new())
+            .unwrap();
+        assert_eq!(1, last_day.interest_accruals.len());
+        assert_eq!(
+            Decimal::new(202, 0), // 200 * 0.12 / 12 = 2
+            Account::by_id(1, &mut store.accounts)
+                .unwrap()
+                .current_balance(&mut store.account_balances)
+        );
+        assert_eq!("interest", last_day.payment_event_ids_chrono()[0].2);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn accrue_interest__posts_daily_interest_once_at_month_end() {
+        use crate::schema::account::Compounding;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(1, |account| {
+            account.interest_rate = Some(Decimal::new(1, 2));
+            account.compounding = Compounding::Daily;
+        });
+
+        // 200 * 0.01 / 365 is half a cent a day, rounded daily it would post 0.01 every day
+        let mut unposted = UnpostedInterestStore::new();
+        for day_of_month in 1..=30 {
+            let mut day = Day::new(2023, 6, day_of_month);
+            day.accrue_interest(&mut store, day_of_month == 30, &mut unposted)
+                .unwrap();
+            let posted = usize::from(day_of_month == 30);
+            assert_eq!(posted, day.interest_accruals.len());
+        }
+        assert!(unposted.is_empty());
+        assert_eq!(
+            Decimal::new(20016, 2),
+            Account::by_id(1, &mut store.accounts)
+                .unwrap()
+                .current_balance(&mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn accrue_interest__alerts_when_interest_takes_an_asset_account_further_below_zero() {
+        use crate::composite::alert::AlertKind;
+        use crate::schema::account::Compounding;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(1, |account| {
+            account.interest_rate = Some(Decimal::new(12, 2));
+            account.compounding = Compounding::Monthly;
+        });
+        AccountBalance::save_to_store(
+            AccountBalance {
+                id: None,
+                account_id: 1,
+                reported_at: NaiveDate::from_ymd_opt(2023, 6, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+                amount: Decimal::new(-100, 0),
+            },
+            &mut store.account_balances,
+        );
+
+        let mut last_day = Day::new(2023, 6, 30);
+        last_day
+            .accrue_interest(&mut store, true, &mut UnpostedInterestStore::new())
+            .unwrap();
+        assert_eq!(1, last_day.alerts.len());
+        let alert = &last_day.alerts[&1];
+        assert_eq!(AlertKind::Overdraft, alert.kind);
+        assert_eq!("interest", alert.event_type);
+        assert_eq!(Decimal::new(-101, 0), alert.ending_balance); // -100 * 0.12 / 12 = -1
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn execute_payments_in_order__enacts_all_payment_events_on_day_in_chrono_order() {
@@ -197,52 +545,48 @@ mod day_spec {
 
         let mut day = Day::new(2023, 6, 6);
         day.add_payment_event(&PaymentEvent {
-            id: None,
             event_type: "payment".to_string(),
             name: "My Payment".to_string(),
             account_name: "New Bank".to_string(),
-            amount: Decimal::new(001, 2),
+            amount: Decimal::new(1, 2),
             completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
                 .unwrap()
-                .and_hms_opt(12, 00, 01)
+                .and_hms_opt(12, 00, 1)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         });
         day.add_payment_event(&PaymentEvent {
-            id: None,
             event_type: "payment_received".to_string(),
             name: "My Payment Received".to_string(),
             account_name: "New Bank".to_string(),
-            amount: Decimal::new(010, 2),
+            amount: Decimal::new(10, 2),
             completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
                 .unwrap()
-                .and_hms_opt(12, 00, 02)
+                .and_hms_opt(12, 00, 2)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         });
         day.add_payment_event(&PaymentEvent {
-            id: None,
             event_type: "payment".to_string(),
             name: "My Payment".to_string(),
             account_name: "New Bank".to_string(),
             amount: Decimal::new(100, 2),
             completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
                 .unwrap()
-                .and_hms_opt(12, 00, 03)
+                .and_hms_opt(12, 00, 3)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         });
         day.add_payment_event(&PaymentEvent {
-            id: None,
             event_type: "payment_received".to_string(),
             name: "My Payment Received".to_string(),
             account_name: "New Bank".to_string(),
             amount: Decimal::new(1000, 2),
             completed_at: NaiveDate::from_ymd_opt(2023, 6, 6)
                 .unwrap()
-                .and_hms_opt(12, 00, 04)
+                .and_hms_opt(12, 00, 4)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         });
 
         assert_eq!(2, day.payments.len());
@@ -250,7 +594,7 @@ mod day_spec {
 
         day.execute_payments_in_order(&mut store).unwrap();
 
-        let account = Account::by_name("New Bank", &mut store.accounts).unwrap();
+        let account = Account::by_name("New Bank", &store.accounts).unwrap();
         let account_balance_ids = account.account_balance_ids(&mut store.account_balances);
 
         let mut acc_bal_store = AccountBalanceStore::new();
@@ -260,9 +604,9 @@ mod day_spec {
             AccountBalance::save_to_store(acc_bal, &mut acc_bal_store);
         }
 
-        assert_eq!(Decimal::new(-0001, 2), acc_bal_store[&1].amount); // pay .01
-        assert_eq!(Decimal::new(009, 2), acc_bal_store[&2].amount); // receive .1
-        assert_eq!(Decimal::new(-091, 2), acc_bal_store[&3].amount); // pay 1
+        assert_eq!(Decimal::new(-1, 2), acc_bal_store[&1].amount); // pay .01
+        assert_eq!(Decimal::new(9, 2), acc_bal_store[&2].amount); // receive .1
+        assert_eq!(Decimal::new(-91, 2), acc_bal_store[&3].amount); // pay 1
         assert_eq!(Decimal::new(909, 2), acc_bal_store[&4].amount); // receive 10
     }
 }
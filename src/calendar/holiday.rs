@@ -0,0 +1,174 @@
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+// a day the banks are closed, read from init/holidays.csv
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Holiday {
+    pub id: Option<usize>,
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+pub type HolidayStore = BTreeMap<usize, Holiday>;
+
+impl CsvRecord<Holiday> for Holiday {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> Holiday {
+        self.clone()
+    }
+}
+
+impl CsvStore<Holiday> for Holiday {}
+
+// answers "is this a banking day?", weekends are never banking days
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    dates: BTreeSet<NaiveDate>,
+}
+
+impl HolidayCalendar {
+    pub fn new(holidays: &HolidayStore) -> HolidayCalendar {
+        HolidayCalendar {
+            dates: holidays.values().map(|holiday| holiday.date).collect(),
+        }
+    }
+
+    // reads holidays.csv and holidays.ics from dir_path, either may be missing
+    pub fn load(dir_path: &str) -> BudgetResult<HolidayCalendar> {
+        let mut holidays = HolidayStore::new();
+        Holiday::init_store_if_exists(&mut holidays, &format!("{dir_path}/holidays.csv"))?;
+        let ics_path = format!("{dir_path}/holidays.ics");
+        if Path::new(&ics_path).exists() {
+            for holiday in Holiday::from_ics(&ics_path)? {
+                Holiday::save_to_store(holiday, &mut holidays);
+            }
+        }
+        Ok(HolidayCalendar::new(&holidays))
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.dates.contains(&date)
+    }
+
+    pub fn is_banking_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.is_holiday(date)
+    }
+}
+
+impl Holiday {
+    // all-day VEVENTs only, as exported by most calendar apps
+    // DTSTART;VALUE=DATE:20231225 + SUMMARY:Christmas Day
+    pub fn from_ics(path: &str) -> BudgetResult<Vec<Holiday>> {
+        let content = fs::read_to_string(path).map_err(|err| BudgetError::Calendar {
+            message: format!("{path}: {err}"),
+        })?;
+
+        let mut holidays: Vec<Holiday> = vec![];
+        let mut date: Option<NaiveDate> = None;
+        let mut name = String::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim_end();
+            match line.split_once(':') {
+                Some(("BEGIN", "VEVENT")) => {
+                    date = None;
+                    name = String::new();
+                }
+                Some((key, value)) if key.starts_with("DTSTART") => {
+                    let value = value.get(..8).unwrap_or(value);
+                    date = Some(NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|err| {
+                        BudgetError::Calendar {
+                            message: format!("{path} line {}: {err}", index + 1),
+                        }
+                    })?);
+                }
+                Some(("SUMMARY", value)) => name = value.to_string(),
+                Some(("END", "VEVENT")) => match date {
+                    Some(date) => holidays.push(Holiday {
+                        id: None,
+                        date,
+                        name: name.clone(),
+                    }),
+                    None => {
+                        return Err(BudgetError::Calendar {
+                            message: format!("{path} line {}: VEVENT without DTSTART", index + 1),
+                        })
+                    }
+                },
+                _ => (),
+            }
+        }
+        Ok(holidays)
+    }
+}
+
+#[cfg(test)]
+mod holiday_spec {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn is_banking_day__skips_weekends_and_holidays() {
+        let mut holidays = HolidayStore::new();
+        Holiday::save_to_store(
+            Holiday {
+                id: None,
+                date: date(2023, 12, 25),
+                name: "Christmas Day".to_string(),
+            },
+            &mut holidays,
+        );
+        let calendar = HolidayCalendar::new(&holidays);
+
+        assert!(!calendar.is_banking_day(date(2023, 12, 25)));
+        // 2023-12-23 is a Saturday
+        assert!(!calendar.is_banking_day(date(2023, 12, 23)));
+        assert!(calendar.is_banking_day(date(2023, 12, 26)));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn from_ics__reads_all_day_events() {
+        let path = format!(
+            "{}/monthly_budget_holidays.ics",
+            std::env::temp_dir().display()
+        );
+        fs::write(
+            &path,
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20230704\r\n\
+             SUMMARY:Independence Day\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART:20231123T000000\r\n\
+             SUMMARY:Thanksgiving\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        let holidays = Holiday::from_ics(&path).unwrap();
+        assert_eq!(2, holidays.len());
+        assert_eq!(date(2023, 7, 4), holidays[0].date);
+        assert_eq!("Independence Day", holidays[0].name);
+        assert_eq!(date(2023, 11, 23), holidays[1].date);
+    }
+}
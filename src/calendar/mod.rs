@@ -1,5 +1,6 @@
 pub mod calendar_slice;
 pub mod day;
+pub mod holiday;
 pub mod month;
 pub mod month_key;
 pub mod year_month;
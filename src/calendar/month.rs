@@ -1,6 +1,8 @@
 use crate::calendar::day::DayStore;
 use crate::calendar::month_key::MonthKey as MK;
 use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::alert::{Alert, AlertStore};
+use crate::composite::interest_accrual::{InterestAccrual, InterestAccrualStore};
 use crate::composite::payment_display::{PaymentDisplay, PaymentDisplayStore};
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
@@ -22,6 +24,30 @@ impl Month {
         }
     }
 
+    pub fn alerts(&self) -> AlertStore {
+        let mut store = AlertStore::new();
+        for (_id, day) in self.days.iter() {
+            for (_id, alert) in day.alerts.iter() {
+                let mut new_alert = alert.clone_record();
+                new_alert.id = None; // clear id tied to day, will be set in chrono order for month
+                Alert::save_to_store(new_alert, &mut store);
+            }
+        }
+        store
+    }
+
+    pub fn interest_accruals(&self) -> InterestAccrualStore {
+        let mut store = InterestAccrualStore::new();
+        for (_id, day) in self.days.iter() {
+            for (_id, accrual) in day.interest_accruals.iter() {
+                let mut new_accrual = accrual.clone_record();
+                new_accrual.id = None; // clear id tied to day, will be set in chrono order for month
+                InterestAccrual::save_to_store(new_accrual, &mut store);
+            }
+        }
+        store
+    }
+
     pub fn all_payments_display(&mut self) -> PaymentDisplayStore {
         let mut all_pd: Vec<PaymentDisplay> = vec![];
         for (_id, day) in self.days.iter_mut() {
@@ -86,6 +112,25 @@ impl Month {
         store
     }
 
+    pub fn all_transfers_display(&mut self) -> PaymentDisplayStore {
+        let mut all_pd: Vec<PaymentDisplay> = vec![];
+        for (_id, day) in self.days.iter_mut() {
+            for (_id, transfer) in day.transfers.iter_mut() {
+                all_pd.push(transfer.display());
+            }
+        }
+        all_pd.sort_by(|a, b| a.completed_at.partial_cmp(&b.completed_at).unwrap());
+
+        let mut store = PaymentDisplayStore::new();
+        for pd in all_pd.iter() {
+            let mut new_pd = pd.clone_record();
+            new_pd.id = None; // clear id tied to day, will be set in chrono order for month
+            PaymentDisplay::save_to_store(new_pd, &mut store);
+        }
+
+        store
+    }
+
     pub fn id(month: MK) -> u32 {
         // u32 expected by NaiveDate
         match month {
@@ -124,11 +169,17 @@ impl Month {
         }
     }
 
-    pub fn length(month: MK) -> u32 {
+    pub fn length(year: i32, month: MK) -> u32 {
         // u32 expected by NaiveDate
         match month {
             MK::Jan => 31,
-            MK::Feb => 28,
+            MK::Feb => {
+                if Month::is_leap_year(year) {
+                    29
+                } else {
+                    28
+                }
+            }
             MK::Mar => 31,
             MK::Apr => 30,
             MK::May => 31,
@@ -143,6 +194,10 @@ impl Month {
         }
     }
 
+    pub fn is_leap_year(year: i32) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
     pub fn next_month(month: MK) -> MK {
         // u32 expected by NaiveDate
         match month {
@@ -221,4 +276,23 @@ mod tests {
         let res = Month::new(YM::new(2023, MK::Jan));
         assert_eq!("January", res.name());
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn is_leap_year__follows_gregorian_rules() {
+        assert!(Month::is_leap_year(2024));
+        assert!(Month::is_leap_year(2028));
+        assert!(Month::is_leap_year(2000));
+        assert!(!Month::is_leap_year(2023));
+        assert!(!Month::is_leap_year(2100));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn length__returns_29_for_february_in_leap_years() {
+        assert_eq!(28, Month::length(2023, MK::Feb));
+        assert_eq!(29, Month::length(2024, MK::Feb));
+        assert_eq!(28, Month::length(2100, MK::Feb));
+        assert_eq!(31, Month::length(2024, MK::Mar));
+    }
 }
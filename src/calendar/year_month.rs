@@ -15,6 +15,19 @@ impl YearMonth {
         YearMonth { year, month }
     }
 
+    pub fn length(&self) -> u32 {
+        Month::length(self.year, self.month)
+    }
+
+    pub fn start_of_month(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, Month::id(self.month), 1).unwrap()
+    }
+
+    // YYYY-MM, the format accepted by YearMonth::try_parse
+    pub fn display(&self) -> String {
+        format!("{}-{:02}", self.year, Month::id(self.month))
+    }
+
     pub fn start_of_next_month(&self) -> NaiveDate {
         let next_month = Month::next_month(self.month);
         let next_month_id = Month::id(next_month);
@@ -26,21 +39,21 @@ impl YearMonth {
         NaiveDate::from_ymd_opt(year, next_month_id, 1).unwrap()
     }
 
-    pub fn parse(ym: String) -> YearMonth {
-        let mut ym_split = ym.split('-');
-        let year: i32 = ym_split
-            .next()
-            .unwrap()
-            .parse::<i32>()
-            .expect("Invalid Year Entered");
-        let month: MK = Month::key_from_id(
-            ym_split
-                .next()
-                .unwrap()
-                .parse::<u32>()
-                .expect("Invalid Month Entered"),
-        );
-        YearMonth::new(year, month)
+    // clap value parser, rejects anything but YYYY-MM with a month in 1..=12
+    pub fn try_parse(ym: &str) -> Result<YearMonth, String> {
+        let (year, month) = ym
+            .split_once('-')
+            .ok_or_else(|| format!("{ym:?} is not YYYY-MM"))?;
+        let year: i32 = year
+            .parse()
+            .map_err(|_| format!("Invalid year {year:?} in {ym:?}"))?;
+        let month: u32 = month
+            .parse()
+            .map_err(|_| format!("Invalid month {month:?} in {ym:?}"))?;
+        match Month::key_from_id(month) {
+            MK::None => Err(format!("Month must be 01-12, got {month} in {ym:?}")),
+            month => Ok(YearMonth::new(year, month)),
+        }
     }
 }
 
@@ -64,14 +77,37 @@ mod tests {
         let year_month_2 = YearMonth::new(2023_i32, MK::Feb);
         let year_month_3 = YearMonth::new(2023_i32, MK::Mar);
 
-        assert_eq!(true, year_month_1 < year_month_2);
-        assert_eq!(true, year_month_1 <= year_month_2);
-        assert_eq!(true, year_month_2 < year_month_3);
-        assert_eq!(true, year_month_2 <= year_month_3);
-        assert_eq!(true, year_month_1 < year_month_3);
-        assert_eq!(true, year_month_1 <= year_month_3);
-        assert_eq!(true, year_month_1 <= year_month_1);
-        assert_eq!(true, year_month_2 <= year_month_2);
-        assert_eq!(true, year_month_3 <= year_month_3);
+        assert!(year_month_1 < year_month_2);
+        assert!(year_month_1 <= year_month_2);
+        assert!(year_month_2 < year_month_3);
+        assert!(year_month_2 <= year_month_3);
+        assert!(year_month_1 < year_month_3);
+        assert!(year_month_1 <= year_month_3);
+        assert!(year_month_1 <= year_month_1);
+        assert!(year_month_2 <= year_month_2);
+        assert!(year_month_3 <= year_month_3);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn display__round_trips_through_try_parse() {
+        let year_month = YearMonth::new(2023_i32, MK::Mar);
+        assert_eq!("2023-03", year_month.display());
+        assert_eq!(
+            year_month,
+            YearMonth::try_parse(&year_month.display()).unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse__rejects_malformed_year_months() {
+        assert!(YearMonth::try_parse("2023").is_err());
+        assert!(YearMonth::try_parse("2023-13").is_err());
+        assert!(YearMonth::try_parse("20x3-01").is_err());
+        assert_eq!(
+            YearMonth::new(2023_i32, MK::Dec),
+            YearMonth::try_parse("2023-12").unwrap()
+        );
     }
 }
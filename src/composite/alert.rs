@@ -0,0 +1,209 @@
+use crate::schema::account::AccountStore;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// a debit or interest charge that left an account below its floor
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Alert {
+    pub id: Option<usize>,
+    pub kind: AlertKind,
+    pub account_id: usize,
+    pub account_name: String,
+    pub event_type: String, // "payment", "payment_received", "transfer" or "interest"
+    pub event_name: String,
+    pub occurred_at: NaiveDateTime,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub floor: Decimal, // min_balance, 0 when unset, -credit_limit for liabilities
+    #[serde(with = "rust_decimal::serde::float")]
+    pub ending_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub shortfall: Decimal, // floor - ending_balance
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Overdraft,       // ending_balance below 0
+    BelowMinimum,    // ending_balance below min_balance but not below 0
+    OverCreditLimit, // liability ending_balance below -credit_limit
+}
+
+impl CsvRecord<Alert> for Alert {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> Alert {
+        self.clone()
+    }
+}
+
+impl CsvStore<Alert> for Alert {}
+
+pub type AlertStore = BTreeMap<usize, Alert>;
+
+impl Alert {
+    // below_minimum alerts are warnings, overdrafts and over_credit_limit fail --fail-on-overdraft
+    pub fn overdrafts(alerts: &AlertStore) -> usize {
+        alerts
+            .values()
+            .filter(|alert| alert.kind != AlertKind::BelowMinimum)
+            .count()
+    }
+
+    // Some when the debit moved an account's balance down and below its floor
+    // liabilities without a credit_limit are skipped
+    pub fn check(
+        accounts: &AccountStore,
+        account_id: usize,
+        event_type: &str,
+        event_name: &str,
+        occurred_at: NaiveDateTime,
+        balances: (Option<Decimal>, Option<Decimal>), // (prev_balance, ending_balance)
+    ) -> Option<Alert> {
+        let account = accounts.get(&account_id)?;
+        let (prev_balance, ending_balance) = (balances.0?, balances.1?);
+        let floor = match account.is_liability() {
+            true => -account.credit_limit?,
+            false => account.min_balance.unwrap_or_default(),
+        };
+        if ending_balance >= prev_balance || ending_balance >= floor {
+            return None;
+        }
+
+        Some(Alert {
+            id: None,
+            kind: match (account.is_liability(), ending_balance < Decimal::new(0, 0)) {
+                (true, _) => AlertKind::OverCreditLimit,
+                (false, true) => AlertKind::Overdraft,
+                (false, false) => AlertKind::BelowMinimum,
+            },
+            account_id,
+            account_name: account.name.clone(),
+            event_type: event_type.to_string(),
+            event_name: event_name.to_string(),
+            occurred_at,
+            floor,
+            ending_balance,
+            shortfall: floor - ending_balance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod alert_spec {
+    use super::*;
+    use crate::schema::account::AccountKind;
+    use crate::storage::store::Store;
+    use crate::test::spec::Spec;
+    use chrono::NaiveDate;
+
+    fn occurred_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn check(store: &Store, prev: i64, ending: i64) -> Option<Alert> {
+        Alert::check(
+            &store.accounts,
+            1,
+            "payment",
+            "mortgage",
+            occurred_at(),
+            (Some(Decimal::new(prev, 0)), Some(Decimal::new(ending, 0))),
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn check__flags_overdraft_when_debit_ends_below_zero() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let alert = check(&store, 100, -50).unwrap();
+        assert_eq!(AlertKind::Overdraft, alert.kind);
+        assert_eq!("piggybank", alert.account_name);
+        assert_eq!(Decimal::new(50, 0), alert.shortfall);
+        assert!(check(&store, 100, 50).is_none());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn check__flags_balance_below_min_balance() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(1, |account| {
+            account.min_balance = Some(Decimal::new(500, 0))
+        });
+
+        let alert = check(&store, 600, 400).unwrap();
+        assert_eq!(AlertKind::BelowMinimum, alert.kind);
+        assert_eq!(Decimal::new(100, 0), alert.shortfall);
+        // credits do not alert, even below the floor
+        assert!(check(&store, 300, 400).is_none());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn check__skips_liabilities_without_a_credit_limit() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store
+            .accounts
+            .update(1, |account| account.kind = AccountKind::Liability);
+
+        assert!(check(&store, 100, -50).is_none());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn check__flags_charge_over_credit_limit() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(1, |account| {
+            account.kind = AccountKind::Liability;
+            account.credit_limit = Some(Decimal::new(1000, 0));
+        });
+
+        let alert = check(&store, -900, -1200).unwrap();
+        assert_eq!(AlertKind::OverCreditLimit, alert.kind);
+        assert_eq!(Decimal::new(-1000, 0), alert.floor);
+        assert_eq!(Decimal::new(200, 0), alert.shortfall);
+        assert!(check(&store, -500, -900).is_none());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn overdrafts__counts_only_overdraft_alerts() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(1, |account| {
+            account.min_balance = Some(Decimal::new(500, 0))
+        });
+
+        let mut alerts = AlertStore::new();
+        Alert::save_to_store(check(&store, 600, 400).unwrap(), &mut alerts);
+        assert_eq!(0, Alert::overdrafts(&alerts));
+
+        Alert::save_to_store(check(&store, 400, -50).unwrap(), &mut alerts);
+        assert_eq!(1, Alert::overdrafts(&alerts));
+
+        store.accounts.update(1, |account| {
+            account.kind = AccountKind::Liability;
+            account.credit_limit = Some(Decimal::new(1000, 0));
+        });
+        Alert::save_to_store(check(&store, -900, -1200).unwrap(), &mut alerts);
+        assert_eq!(2, Alert::overdrafts(&alerts));
+    }
+}
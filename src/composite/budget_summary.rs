@@ -0,0 +1,107 @@
+use crate::schema::budget::Budget;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// a Budget's limit compared to what was spent in one month
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct BudgetSummary {
+    pub id: Option<usize>,
+    pub budget_id: usize,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub limit: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub rollover_in: Decimal, // unused limit carried from the previous month
+    #[serde(with = "rust_decimal::serde::float")]
+    pub actual: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub remaining: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub percent_used: Decimal,
+}
+
+pub type BudgetSummaryStore = BTreeMap<usize, BudgetSummary>;
+
+// budget_id => unused limit carried into the next month
+pub type BudgetRolloverStore = BTreeMap<usize, Decimal>;
+
+impl CsvRecord<BudgetSummary> for BudgetSummary {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> BudgetSummary {
+        self.clone()
+    }
+}
+
+impl CsvStore<BudgetSummary> for BudgetSummary {}
+
+impl BudgetSummary {
+    pub fn new(budget: &Budget, rollover_in: Decimal, actual: Decimal) -> BudgetSummary {
+        let available = budget.limit + rollover_in;
+        let percent_used = if available > Decimal::new(0, 0) {
+            (actual / available * Decimal::new(100, 0)).round_dp(2)
+        } else {
+            Decimal::new(0, 0)
+        };
+
+        BudgetSummary {
+            id: None,
+            budget_id: budget.id.unwrap(),
+            name: budget.name.clone(),
+            limit: budget.limit,
+            rollover_in,
+            actual,
+            remaining: available - actual,
+            percent_used,
+        }
+    }
+
+    // overspending is not carried forward
+    pub fn rollover_out(&self) -> Decimal {
+        self.remaining.max(Decimal::new(0, 0))
+    }
+}
+
+#[cfg(test)]
+mod budget_summary_spec {
+    use super::*;
+
+    fn budget() -> Budget {
+        Budget {
+            id: Some(1),
+            name: "food".to_string(),
+            expense_id: Some(2),
+            limit: Decimal::new(400, 0),
+            rollover: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn new__computes_remaining_and_percent_used() {
+        let summary = BudgetSummary::new(&budget(), Decimal::new(100, 0), Decimal::new(250, 0));
+        assert_eq!(Decimal::new(250, 0), summary.remaining);
+        assert_eq!(Decimal::new(50, 0), summary.percent_used);
+        assert_eq!(Decimal::new(250, 0), summary.rollover_out());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn rollover_out__does_not_carry_overspending() {
+        let summary = BudgetSummary::new(&budget(), Decimal::new(0, 0), Decimal::new(500, 0));
+        assert_eq!(Decimal::new(-100, 0), summary.remaining);
+        assert_eq!(Decimal::new(125, 0), summary.percent_used);
+        assert_eq!(Decimal::new(0, 0), summary.rollover_out());
+    }
+}
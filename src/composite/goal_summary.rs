@@ -0,0 +1,221 @@
+use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::error::error_handler::ErrorHandler;
+use crate::schema::goal::{Goal, GoalProgress, GoalStore};
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// where a goal stands after a run
+// beyond the slice the amount is extrapolated from its average change per month over the slice
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GoalSummary {
+    pub id: Option<usize>,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub target_amount: Decimal,
+    pub target_date: NaiveDate,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount_at_start: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount_at_target_date: Decimal,
+    pub projected_date: Option<NaiveDate>, // None => never reached at the current pace
+    #[serde(with = "rust_decimal::serde::float")]
+    pub shortfall: Decimal, // at target_date, 0 when on track
+    #[serde(with = "rust_decimal::serde::float")]
+    pub extra_monthly_contribution: Decimal, // from the slice start through target_date
+}
+
+pub type GoalSummaryStore = BTreeMap<usize, GoalSummary>;
+
+impl CsvRecord<GoalSummary> for GoalSummary {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> GoalSummary {
+        self.clone()
+    }
+}
+
+impl CsvStore<GoalSummary> for GoalSummary {}
+
+impl GoalSummary {
+    // a goal that cannot be tracked is logged and left out
+    pub fn summarize(
+        goals: &GoalStore,
+        store: &Store,
+        cal_slice: &CalendarSlice,
+    ) -> GoalSummaryStore {
+        let start = cal_slice
+            .start
+            .start_of_month()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let end = cal_slice
+            .end
+            .start_of_next_month()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let mut summary_store = GoalSummaryStore::new();
+        for goal in goals.values() {
+            match goal.progress(store, start, end) {
+                Ok(progress) => {
+                    let mut summary = GoalSummary::project(goal, &progress, cal_slice);
+                    summary.id = goal.id;
+                    summary_store.insert(goal.id.unwrap(), summary);
+                }
+                Err(err) => ErrorHandler::log(err),
+            }
+        }
+        summary_store
+    }
+
+    pub fn project(goal: &Goal, progress: &GoalProgress, cal_slice: &CalendarSlice) -> GoalSummary {
+        let (opening, history) = progress;
+        let slice_start = cal_slice.start.start_of_month();
+        let slice_end = cal_slice.end.start_of_next_month();
+        let closing = history.last().map_or(*opening, |(_, amount)| *amount);
+        let slice_months = Decimal::from(months_between(cal_slice.start, cal_slice.end) + 1);
+        let per_month = (closing - *opening) / slice_months;
+        let target = goal.target_amount;
+
+        let reached_at = match *opening >= target {
+            true => Some(slice_start),
+            false => history
+                .iter()
+                .find(|(_, amount)| *amount >= target)
+                .map(|(at, _)| at.date()),
+        };
+        // the last day of the month the trend gets there
+        let projected_date = reached_at.or_else(|| match per_month > Decimal::ZERO {
+            true => ((target - closing) / per_month)
+                .ceil()
+                .to_u32()
+                .and_then(|months| slice_end.checked_add_months(Months::new(months)))
+                .and_then(|date| date.checked_sub_days(Days::new(1))),
+            false => None,
+        });
+
+        let target_month = year_month(goal.target_date);
+        let amount_at_target_date = if goal.target_date < slice_start {
+            *opening
+        } else if goal.target_date < slice_end {
+            history
+                .iter()
+                .rev()
+                .find(|(at, _)| at.date() <= goal.target_date)
+                .map_or(*opening, |(_, amount)| *amount)
+        } else {
+            closing + per_month * Decimal::from(months_between(cal_slice.end, target_month))
+        };
+        let shortfall = (target - amount_at_target_date).max(Decimal::ZERO);
+        let months_to_save = months_between(cal_slice.start, target_month).max(0) + 1;
+
+        GoalSummary {
+            id: None,
+            name: goal.name.clone(),
+            target_amount: target,
+            target_date: goal.target_date,
+            amount_at_start: *opening,
+            amount_at_target_date: amount_at_target_date.round_dp(2),
+            projected_date,
+            shortfall: shortfall.round_dp(2),
+            extra_monthly_contribution: (shortfall / Decimal::from(months_to_save)).round_dp(2),
+        }
+    }
+}
+
+fn year_month(date: NaiveDate) -> YM {
+    YM::new(date.year(), Month::key_from_id(date.month()))
+}
+
+// 0 when from and to are the same month
+fn months_between(from: YM, to: YM) -> i64 {
+    let index = |ym: YM| i64::from(ym.year) * 12 + i64::from(Month::id(ym.month));
+    index(to) - index(from)
+}
+
+#[cfg(test)]
+mod goal_summary_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use chrono::NaiveDateTime;
+
+    fn at(month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, month, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn goal(target_amount: i64, month: u32, day: u32) -> Goal {
+        Goal {
+            id: Some(1),
+            name: "Emergency Fund".to_string(),
+            target_amount: Decimal::new(target_amount, 0),
+            target_date: NaiveDate::from_ymd_opt(2024, month, day).unwrap(),
+            account_name: Some("Savings".to_string()),
+            tag: None,
+        }
+    }
+
+    // Jan - Apr, 1000 to start, +500 each month
+    fn progress() -> GoalProgress {
+        (
+            Decimal::new(1000, 0),
+            vec![
+                (at(1, 15), Decimal::new(1500, 0)),
+                (at(2, 15), Decimal::new(2000, 0)),
+                (at(3, 15), Decimal::new(2500, 0)),
+                (at(4, 15), Decimal::new(3000, 0)),
+            ],
+        )
+    }
+
+    fn cal_slice() -> CalendarSlice {
+        CalendarSlice::new(YM::new(2024, MK::Jan), YM::new(2024, MK::Apr)).unwrap()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn project__finds_the_date_reached_within_the_slice() {
+        let summary = GoalSummary::project(&goal(2000, 3, 31), &progress(), &cal_slice());
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 2, 15), summary.projected_date);
+        assert_eq!(Decimal::new(2500, 0), summary.amount_at_target_date);
+        assert_eq!(Decimal::ZERO, summary.shortfall);
+        assert_eq!(Decimal::ZERO, summary.extra_monthly_contribution);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn project__extrapolates_beyond_the_slice_and_spreads_the_shortfall() {
+        // 3000 at the end of April, 500 a month => 4000 by the end of June, 5000 by August
+        let summary = GoalSummary::project(&goal(5000, 6, 30), &progress(), &cal_slice());
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 8, 31), summary.projected_date);
+        assert_eq!(Decimal::new(4000, 0), summary.amount_at_target_date);
+        assert_eq!(Decimal::new(1000, 0), summary.shortfall);
+        // Jan through Jun
+        assert_eq!(Decimal::new(16667, 2), summary.extra_monthly_contribution);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn project__never_reaches_a_goal_that_is_not_growing() {
+        let progress = (Decimal::new(1000, 0), vec![]);
+        let summary = GoalSummary::project(&goal(5000, 2, 29), &progress, &cal_slice());
+        assert_eq!(None, summary.projected_date);
+        assert_eq!(Decimal::new(4000, 0), summary.shortfall);
+        assert_eq!(Decimal::new(2000, 0), summary.extra_monthly_contribution);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::error::budget_error::BudgetError;
+use crate::schema::account::Account;
+use crate::schema::account_balance::AccountBalance;
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::NaiveDateTime;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// interest posted to an account for one compounding period
+// positive balances earn interest, negative balances (loans, cards) are charged interest
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct InterestAccrual {
+    pub id: Option<usize>,
+    pub account_id: usize,
+    pub account_name: String,
+    pub account_balance_id: Option<usize>, // None when no interest was posted
+    #[serde(with = "rust_decimal::serde::float")]
+    pub rate: Decimal, // annual
+    pub periods_per_year: u32,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub prev_balance: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub interest: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub ending_balance: Option<Decimal>,
+    pub accrued_at: NaiveDateTime,
+}
+
+impl CsvRecord<InterestAccrual> for InterestAccrual {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> InterestAccrual {
+        self.clone()
+    }
+}
+
+impl CsvStore<InterestAccrual> for InterestAccrual {}
+
+pub type InterestAccrualStore = BTreeMap<usize, InterestAccrual>;
+pub type UnpostedInterestStore = BTreeMap<usize, Decimal>; // account id => accrued since the last posting
+
+type CreateAccrualResult = Result<(), BudgetError>;
+
+impl InterestAccrual {
+    pub fn new(account: &Account, periods_per_year: u32, accrued_at: NaiveDateTime) -> Self {
+        InterestAccrual {
+            id: None,
+            account_id: account.id.unwrap(),
+            account_name: account.name.clone(),
+            account_balance_id: None,
+            rate: account.interest_rate.unwrap_or_default(),
+            periods_per_year,
+            prev_balance: None,
+            interest: None,
+            ending_balance: None,
+            accrued_at,
+        }
+    }
+
+    // one compounding period of interest on balance, at full precision
+    pub fn period_interest(account: &Account, balance: Decimal, periods_per_year: u32) -> Decimal {
+        balance * account.interest_rate.unwrap_or_default() / Decimal::from(periods_per_year)
+    }
+
+    // posts `accrued`, the interest of every period since the last posting, rounded once
+    pub fn create_accrual(&mut self, store: &mut Store, accrued: Decimal) -> CreateAccrualResult {
+        let prev_balance = Account::by_id(self.account_id, &mut store.accounts)
+            .ok_or_else(|| BudgetError::UnknownAccount {
+                name: self.account_name.clone(),
+                did_you_mean: None,
+            })?
+            .current_balance(&mut store.account_balances);
+        self.prev_balance = Some(prev_balance);
+
+        let interest = accrued.round_dp(2);
+        self.interest = Some(interest);
+        if interest.is_zero() {
+            return Ok(());
+        }
+
+        let ending_balance = prev_balance + interest;
+        self.ending_balance = Some(ending_balance);
+        self.account_balance_id = Some(AccountBalance::save_to_store(
+            AccountBalance {
+                id: None,
+                account_id: self.account_id,
+                amount: ending_balance,
+                reported_at: self.accrued_at,
+            },
+            &mut store.account_balances,
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod interest_accrual_spec {
+    use super::*;
+    use crate::test::spec::Spec;
+    use chrono::NaiveDate;
+
+    fn accrued_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 2, 28)
+            .unwrap()
+            .and_hms_opt(23, 59, 59)
+            .unwrap()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_accrual__posts_positive_interest_on_positive_balance() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut account = Account::by_id(1, &mut store.accounts).unwrap(); // piggybank, 200
+        account.interest_rate = Some(Decimal::new(6, 2));
+        let mut accrual = InterestAccrual::new(&account, 12, accrued_at());
+        let accrued = InterestAccrual::period_interest(&account, Decimal::new(200, 0), 12);
+        accrual.create_accrual(&mut store, accrued).unwrap();
+
+        assert_eq!(Decimal::new(1, 0), accrual.interest.unwrap());
+        assert_eq!(Decimal::new(201, 0), accrual.ending_balance.unwrap());
+        assert_eq!(5, accrual.account_balance_id.unwrap());
+        assert_eq!(
+            Decimal::new(201, 0),
+            account.current_balance(&mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_accrual__posts_negative_interest_on_negative_balance() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        AccountBalance::save_to_store(
+            AccountBalance {
+                id: None,
+                account_id: 1,
+                reported_at: accrued_at() - chrono::Duration::days(1),
+                amount: Decimal::new(-1200, 0),
+            },
+            &mut store.account_balances,
+        );
+        let mut account = Account::by_id(1, &mut store.accounts).unwrap();
+        account.interest_rate = Some(Decimal::new(24, 2));
+        let mut accrual = InterestAccrual::new(&account, 12, accrued_at());
+        let accrued = InterestAccrual::period_interest(&account, Decimal::new(-1200, 0), 12);
+        accrual.create_accrual(&mut store, accrued).unwrap();
+
+        assert_eq!(Decimal::new(-24, 0), accrual.interest.unwrap());
+        assert_eq!(Decimal::new(-1224, 0), accrual.ending_balance.unwrap());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_accrual__does_not_post_balance_when_interest_rounds_to_zero() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut account = Account::by_id(1, &mut store.accounts).unwrap();
+        account.interest_rate = Some(Decimal::new(1, 4));
+        let mut accrual = InterestAccrual::new(&account, 365, accrued_at());
+        let accrued = InterestAccrual::period_interest(&account, Decimal::new(200, 0), 365);
+        accrual.create_accrual(&mut store, accrued).unwrap();
+
+        assert_eq!(None, accrual.account_balance_id);
+        assert_eq!(4, store.account_balances.len());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::payment_event::{
+    fetch_json_events, PaymentEvent, PaymentEventBinStore, RecurrenceState,
+};
+use crate::error::budget_error::BudgetError;
+use crate::schema::account::{Account, AccountKind};
+use crate::schema::account_balance::AccountBalance;
+use crate::schema::expense::Expense;
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, Months, NaiveDate};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// a fixed rate loan repaid monthly, read from events/loans.json
+// each payment is an interest "payment" and a principal "transfer" to the loan's liability account
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Loan {
+    pub id: Option<usize>,
+    pub name: String,
+    #[serde(default)]
+    pub account_name: Option<String>, // liability account, None => name
+    pub payment_account_name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub principal: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub rate: Decimal, // annual, 0.065 => 6.5%
+    pub term_months: u32,
+    pub start: NaiveDate, // first payment, the loan is funded a month before
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub extra_principal: Option<Decimal>, // added to every payment
+    #[serde(default)]
+    pub extra_payments: Vec<LoanExtraPayment>, // one off principal payments
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LoanExtraPayment {
+    pub date: NaiveDate,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+}
+
+// one row of the amortization schedule, extra payments have no interest
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoanPayment {
+    pub id: Option<usize>,
+    pub loan_name: String,
+    pub number: usize, // 0 for extra payments
+    pub date: NaiveDate,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub interest: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub principal: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub balance: Decimal, // left owing after this payment
+}
+
+// scheduled => as signed, without extra principal
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoanSummary {
+    pub id: Option<usize>,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub monthly_payment: Decimal,
+    pub payoff_date: Option<NaiveDate>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_interest: Decimal,
+    pub scheduled_payoff_date: Option<NaiveDate>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub scheduled_total_interest: Decimal,
+    pub months_saved: usize,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub interest_saved: Decimal,
+}
+
+pub type LoanPaymentStore = BTreeMap<usize, LoanPayment>;
+pub type LoanSummaryStore = BTreeMap<usize, LoanSummary>;
+type LoanFetchResult = Result<Vec<Loan>, BudgetError>;
+type LoanApplyResult = Result<(), BudgetError>;
+
+impl CsvRecord<LoanPayment> for LoanPayment {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> LoanPayment {
+        self.clone()
+    }
+}
+
+impl CsvStore<LoanPayment> for LoanPayment {}
+
+impl CsvRecord<LoanSummary> for LoanSummary {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> LoanSummary {
+        self.clone()
+    }
+}
+
+impl CsvStore<LoanSummary> for LoanSummary {}
+
+impl Loan {
+    pub fn fetch_loans(path: String) -> LoanFetchResult {
+        fetch_json_events(&path)
+    }
+
+    pub fn account_name(&self) -> String {
+        self.account_name
+            .clone()
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    pub fn interest_name(&self) -> String {
+        format!("{} Interest", self.name)
+    }
+
+    pub fn funded_on(&self) -> NaiveDate {
+        self.start - Months::new(1)
+    }
+
+    // principal * r / (1 - (1 + r)^-term), rounded to the cent
+    pub fn monthly_payment(&self) -> Decimal {
+        let term = Decimal::from(self.term_months.max(1));
+        let monthly_rate = self.rate / Decimal::new(12, 0);
+        if monthly_rate.is_zero() {
+            return (self.principal / term).round_dp(2);
+        }
+        let mut growth = Decimal::new(1, 0);
+        for _month in 0..self.term_months.max(1) {
+            growth *= Decimal::new(1, 0) + monthly_rate;
+        }
+        (self.principal * monthly_rate * growth / (growth - Decimal::new(1, 0))).round_dp(2)
+    }
+
+    // the final payment covers whatever is left, so the balance always ends at 0
+    // extra payments dated before a monthly payment lower the balance its interest is charged on
+    pub fn schedule(&self, with_extras: bool) -> Vec<LoanPayment> {
+        let monthly_payment = self.monthly_payment();
+        let monthly_rate = self.rate / Decimal::new(12, 0);
+        let extra_principal = match with_extras {
+            true => self.extra_principal.unwrap_or_default(),
+            false => Decimal::new(0, 0),
+        };
+        let mut extra_payments: Vec<&LoanExtraPayment> = match with_extras {
+            true => self.extra_payments.iter().collect(),
+            false => vec![],
+        };
+        extra_payments.sort_by_key(|extra_payment| extra_payment.date);
+        let mut extra_payments = extra_payments.into_iter().peekable();
+
+        let mut balance = self.principal;
+        let mut schedule: Vec<LoanPayment> = vec![];
+        let row = |number: usize, date: NaiveDate, interest, principal, balance| LoanPayment {
+            id: None,
+            loan_name: self.name.clone(),
+            number,
+            date,
+            interest,
+            principal,
+            balance,
+        };
+        for number in 1..=self.term_months as usize {
+            let date = self.start + Months::new(number as u32 - 1);
+            while let Some(extra_payment) = extra_payments.next_if(|extra| extra.date < date) {
+                let principal = extra_payment.amount.min(balance);
+                balance -= principal;
+                schedule.push(row(
+                    0,
+                    extra_payment.date,
+                    Decimal::new(0, 0),
+                    principal,
+                    balance,
+                ));
+            }
+            if balance.is_zero() {
+                break;
+            }
+
+            let interest = (balance * monthly_rate).round_dp(2);
+            let mut principal = monthly_payment - interest + extra_principal;
+            if principal > balance || number == self.term_months as usize {
+                principal = balance;
+            }
+            balance -= principal;
+            schedule.push(row(number, date, interest, principal, balance));
+            if balance.is_zero() {
+                break;
+            }
+        }
+        schedule
+    }
+
+    pub fn summary(&self) -> LoanSummary {
+        let schedule = self.schedule(true);
+        let scheduled = self.schedule(false);
+        let total_interest: Decimal = schedule.iter().map(|payment| payment.interest).sum();
+        let scheduled_total_interest: Decimal =
+            scheduled.iter().map(|payment| payment.interest).sum();
+        let payments = schedule.iter().filter(|payment| payment.number > 0).count();
+        LoanSummary {
+            id: None,
+            name: self.name.clone(),
+            monthly_payment: self.monthly_payment(),
+            payoff_date: schedule.last().map(|payment| payment.date),
+            total_interest,
+            scheduled_payoff_date: scheduled.last().map(|payment| payment.date),
+            scheduled_total_interest,
+            months_saved: scheduled.len().saturating_sub(payments),
+            interest_saved: scheduled_total_interest - total_interest,
+        }
+    }
+
+    // owed after every payment made before date
+    pub fn balance_before(&self, date: NaiveDate) -> Decimal {
+        self.schedule(true)
+            .iter()
+            .take_while(|payment| payment.date < date)
+            .last()
+            .map_or(self.principal, |payment| payment.balance)
+    }
+
+    pub fn payment_events(&self, cal_slice: &CalendarSlice) -> Vec<(YM, PaymentEvent)> {
+        let schedule = self.schedule(true);
+        let last_number = schedule.iter().map(|payment| payment.number).max();
+        let mut payment_events = vec![];
+        for payment in schedule.iter() {
+            let ym = YM::new(
+                payment.date.year(),
+                Month::key_from_id(payment.date.month()),
+            );
+            if ym < cal_slice.start || cal_slice.end < ym {
+                continue;
+            }
+            let recurrence_state = match payment.number {
+                0 => RecurrenceState::None,
+                1 => RecurrenceState::First,
+                number if Some(number) == last_number => RecurrenceState::Last,
+                _ => RecurrenceState::Active,
+            };
+            // interest posts a second ahead of principal so the two never share a moment
+            let event =
+                |event_type: &str, name: String, amount: Decimal, second: u32| PaymentEvent {
+                    id: None,
+                    event_type: event_type.to_string(),
+                    name,
+                    account_name: match event_type {
+                        "payment" => self.payment_account_name.clone(),
+                        _ => "".to_string(),
+                    },
+                    from_account_name: match event_type {
+                        "payment" => None,
+                        _ => Some(self.payment_account_name.clone()),
+                    },
+                    to_account_name: match event_type {
+                        "payment" => None,
+                        _ => Some(self.account_name()),
+                    },
+                    amount,
+                    amount_low: None,
+                    amount_high: None,
+                    currency: None,
+                    tags: vec![],
+                    completed_at: payment.date.and_hms_opt(12, 0, second).unwrap(),
+                    recurrence_state,
+                    until_balance_zero: false,
+                };
+            if !payment.interest.is_zero() {
+                payment_events.push((
+                    ym,
+                    event("payment", self.interest_name(), payment.interest, 0),
+                ));
+            }
+            if !payment.principal.is_zero() {
+                let name = format!("{} Principal", self.name);
+                payment_events.push((ym, event("transfer", name, payment.principal, 1)));
+            }
+        }
+        payment_events
+    }
+
+    pub fn bin_loan_payment_events(
+        loans: &[Loan],
+        cal_slice: &CalendarSlice,
+        bin_store: &mut PaymentEventBinStore,
+    ) {
+        for loan in loans.iter() {
+            for (ym, payment_event) in loan.payment_events(cal_slice) {
+                let store = bin_store.entry(ym).or_default();
+                PaymentEvent::save_to_store(payment_event, store);
+            }
+        }
+    }
+
+    // creates each loan's liability account and interest expense
+    // and records what is owed at the slice start, or when the loan is funded during the slice,
+    // unless the account already has a balance by then, e.g. from opening_balances.csv
+    pub fn apply(loans: &[Loan], start: YM, store: &mut Store) -> LoanApplyResult {
+        let slice_start = start.start_of_month();
+        for loan in loans.iter() {
+            let account_id = match Account::by_name(&loan.account_name(), &store.accounts) {
+                Some(account) => account.id.unwrap(),
+                None => Account::save_to_store(
+                    Account {
+                        id: None,
+                        name: loan.account_name(),
+                        kind: AccountKind::Liability,
+                        ..Default::default()
+                    },
+                    &mut store.accounts,
+                ),
+            };
+            if Expense::by_name(&loan.interest_name(), &store.expenses).is_none() {
+                Expense::save_to_store(
+                    Expense {
+                        id: None,
+                        active: true,
+                        name: loan.interest_name(),
+                    },
+                    &mut store.expenses,
+                );
+            }
+
+            let opening = slice_start.and_hms_opt(0, 0, 0).unwrap();
+            let has_balance = store
+                .account_balances
+                .reported_ids(account_id, ..=opening)
+                .next()
+                .is_some();
+            if has_balance {
+                continue;
+            }
+            let mut balances = vec![];
+            match loan.funded_on() <= slice_start {
+                true => balances.push((slice_start, -loan.balance_before(slice_start))),
+                false => {
+                    balances.push((slice_start, Decimal::new(0, 0)));
+                    balances.push((loan.funded_on(), -loan.principal));
+                }
+            }
+            for (date, amount) in balances {
+                AccountBalance::save_to_store(
+                    AccountBalance {
+                        id: None,
+                        account_id,
+                        reported_at: date.and_hms_opt(0, 0, 0).unwrap(),
+                        amount,
+                    },
+                    &mut store.account_balances,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn summarize(loans: &[Loan]) -> LoanSummaryStore {
+        let mut summary_store = LoanSummaryStore::new();
+        for loan in loans.iter() {
+            LoanSummary::save_to_store(loan.summary(), &mut summary_store);
+        }
+        summary_store
+    }
+
+    pub fn schedules(loans: &[Loan]) -> LoanPaymentStore {
+        let mut payment_store = LoanPaymentStore::new();
+        for payment in loans.iter().flat_map(|loan| loan.schedule(true)) {
+            LoanPayment::save_to_store(payment, &mut payment_store);
+        }
+        payment_store
+    }
+}
+
+#[cfg(test)]
+mod loan_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use crate::test::spec::Spec;
+
+    fn loan() -> Loan {
+        Loan {
+            id: None,
+            name: "Car Loan".to_string(),
+            account_name: None,
+            payment_account_name: "piggybank".to_string(),
+            principal: Decimal::new(12000, 0),
+            rate: Decimal::new(6, 2),
+            term_months: 12,
+            start: NaiveDate::from_ymd_opt(2023, 3, 5).unwrap(),
+            extra_principal: None,
+            extra_payments: vec![],
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn monthly_payment__amortizes_principal_over_term() {
+        assert_eq!(Decimal::new(103280, 2), loan().monthly_payment());
+
+        let mut interest_free = loan();
+        interest_free.rate = Decimal::new(0, 0);
+        assert_eq!(Decimal::new(1000, 0), interest_free.monthly_payment());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn schedule__splits_payments_and_ends_at_zero() {
+        let schedule = loan().schedule(true);
+        assert_eq!(12, schedule.len());
+        assert_eq!(Decimal::new(6000, 2), schedule[0].interest); // 12000 * 0.06 / 12
+        assert_eq!(Decimal::new(97280, 2), schedule[0].principal);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 2, 5).unwrap(),
+            schedule[11].date
+        );
+        assert!(schedule[11].balance.is_zero());
+        let principal: Decimal = schedule.iter().map(|payment| payment.principal).sum();
+        assert_eq!(Decimal::new(12000, 0), principal);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn summary__shows_what_extra_principal_saves() {
+        let mut loan = loan();
+        loan.extra_payments = vec![LoanExtraPayment {
+            date: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+            amount: Decimal::new(3000, 0),
+        }];
+
+        let summary = loan.summary();
+        assert_eq!(
+            Some(NaiveDate::from_ymd_opt(2024, 2, 5).unwrap()),
+            summary.scheduled_payoff_date
+        );
+        assert!(summary.payoff_date < summary.scheduled_payoff_date);
+        assert!(summary.months_saved > 0);
+        assert!(summary.interest_saved > Decimal::new(0, 0));
+        assert_eq!(
+            summary.scheduled_total_interest - summary.interest_saved,
+            summary.total_interest
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_events__generates_interest_payment_and_principal_transfer_in_slice() {
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Apr), YM::new(2023, MK::May)).unwrap();
+        let payment_events = loan().payment_events(&cal_slice);
+        assert_eq!(4, payment_events.len());
+
+        let (ym, interest) = &payment_events[0];
+        assert_eq!(YM::new(2023, MK::Apr), *ym);
+        assert_eq!("payment", interest.event_type);
+        assert_eq!("Car Loan Interest", interest.name);
+        assert_eq!("piggybank", interest.account_name);
+
+        let (_ym, principal) = &payment_events[1];
+        assert_eq!("transfer", principal.event_type);
+        assert_eq!(Some("Car Loan".to_string()), principal.to_account_name);
+        assert_eq!(Decimal::new(103280, 2), interest.amount + principal.amount);
+        assert!(interest.completed_at < principal.completed_at);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__records_balance_owed_at_slice_start() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let loan = loan();
+        // two payments made before May
+        Loan::apply(
+            std::slice::from_ref(&loan),
+            YM::new(2023, MK::May),
+            &mut store,
+        )
+        .unwrap();
+
+        let account = Account::by_name("Car Loan", &store.accounts).unwrap();
+        assert_eq!(AccountKind::Liability, account.kind);
+        assert_eq!(
+            -loan.schedule(true)[1].balance,
+            account.current_balance(&mut store.account_balances)
+        );
+        assert!(Expense::by_name("Car Loan Interest", &store.expenses).is_some());
+    }
+}
@@ -1,8 +1,20 @@
 // composite data structures
 pub mod account_summary;
+pub mod alert;
+pub mod budget_summary;
+pub mod goal_summary;
+pub mod interest_accrual;
+pub mod loan;
+pub mod monte_carlo_summary;
 pub mod payment_composite;
 pub mod payment_display;
 pub mod payment_event;
 pub mod payment_received_composite;
 pub mod payment_summary;
+pub mod reconciliation;
 pub mod recurring_payment_event;
+pub mod scenario;
+pub mod statement;
+pub mod statement_payment_event;
+pub mod tag_summary;
+pub mod transfer_composite;
@@ -0,0 +1,135 @@
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// one account's outcome in a single monte carlo run of a single month
+#[derive(Clone, Copy, Debug)]
+pub struct MonteCarloSample {
+    pub month_end_balance: Decimal,
+    pub went_negative: bool,
+}
+
+// distribution of an account's month-end balance across all monte carlo runs
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct MonteCarloSummary {
+    pub id: Option<usize>,
+    pub name: String,
+    pub year_month: String,
+    pub runs: usize,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub p5: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub p50: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub p95: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub probability_negative: Decimal,
+}
+
+impl MonteCarloSummary {
+    pub fn from_samples(name: String, year_month: String, samples: &[MonteCarloSample]) -> Self {
+        let mut balances: Vec<Decimal> = samples.iter().map(|s| s.month_end_balance).collect();
+        balances.sort();
+        let negative_runs = samples.iter().filter(|s| s.went_negative).count();
+
+        MonteCarloSummary {
+            id: None,
+            name,
+            year_month,
+            runs: samples.len(),
+            p5: MonteCarloSummary::percentile(&balances, 5),
+            p50: MonteCarloSummary::percentile(&balances, 50),
+            p95: MonteCarloSummary::percentile(&balances, 95),
+            probability_negative: MonteCarloSummary::probability(negative_runs, samples.len()),
+        }
+    }
+
+    // nearest-rank percentile of an ascending slice
+    pub fn percentile(sorted: &[Decimal], p: usize) -> Decimal {
+        if sorted.is_empty() {
+            return Decimal::new(0, 0);
+        }
+        let rank = (p * sorted.len() + 99) / 100;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    pub fn probability(hits: usize, runs: usize) -> Decimal {
+        if runs == 0 {
+            return Decimal::new(0, 0);
+        }
+        Decimal::from(hits) / Decimal::from(runs)
+    }
+}
+
+pub type MonteCarloSummaryStore = BTreeMap<usize, MonteCarloSummary>;
+
+impl CsvRecord<MonteCarloSummary> for MonteCarloSummary {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> MonteCarloSummary {
+        self.clone()
+    }
+}
+
+impl CsvStore<MonteCarloSummary> for MonteCarloSummary {}
+
+#[cfg(test)]
+mod monte_carlo_summary_spec {
+    use super::*;
+
+    fn samples(balances: &[i64]) -> Vec<MonteCarloSample> {
+        balances
+            .iter()
+            .map(|b| MonteCarloSample {
+                month_end_balance: Decimal::new(*b, 0),
+                went_negative: *b < 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn percentile__returns_nearest_rank() {
+        let sorted: Vec<Decimal> = (1..=20).map(|n| Decimal::new(n, 0)).collect();
+        assert_eq!(
+            Decimal::new(1, 0),
+            MonteCarloSummary::percentile(&sorted, 5)
+        );
+        assert_eq!(
+            Decimal::new(10, 0),
+            MonteCarloSummary::percentile(&sorted, 50)
+        );
+        assert_eq!(
+            Decimal::new(19, 0),
+            MonteCarloSummary::percentile(&sorted, 95)
+        );
+        assert_eq!(
+            Decimal::new(1, 0),
+            MonteCarloSummary::percentile(&sorted, 0)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn from_samples__summarizes_unsorted_samples() {
+        let summary = MonteCarloSummary::from_samples(
+            "Big Bank".to_string(),
+            "2023-03".to_string(),
+            &samples(&[30, -10, 20, 10]),
+        );
+        assert_eq!(4, summary.runs);
+        assert_eq!(Decimal::new(-10, 0), summary.p5);
+        assert_eq!(Decimal::new(10, 0), summary.p50);
+        assert_eq!(Decimal::new(30, 0), summary.p95);
+        assert_eq!(Decimal::new(25, 2), summary.probability_negative);
+    }
+}
@@ -1,18 +1,21 @@
 use crate::composite::payment_display::PaymentDisplay;
+use crate::error::budget_error::BudgetError;
 use crate::error::error_handler::ErrorHandler;
 use crate::schema::account::Account;
 use crate::schema::account_balance::AccountBalance;
 use crate::schema::amount::Amount;
 use crate::schema::expense::Expense;
+use crate::schema::expense_tag::ExpenseTag;
+use crate::schema::fx_rate::FxRate;
 use crate::schema::payment::Payment;
 use crate::storage::store::Store;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
+use crate::traits::tag_link::TagLink;
 use chrono::{NaiveDateTime, Utc};
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::error::Error;
 
 use super::payment_event::RecurrenceState;
 
@@ -29,11 +32,15 @@ pub struct PaymentComposite {
     pub amount_id: Option<usize>,
     #[serde(with = "rust_decimal::serde::float")]
     pub amount_standard: Decimal,
+    #[serde(default)]
+    pub currency: Option<String>, // amount_standard's, None => the account's
     pub payment_id: Option<usize>,
     pub payment_completed_at: NaiveDateTime,
     pub expense_id: Option<usize>,
     pub expense_name: String,
     pub recurrence_state: RecurrenceState,
+    #[serde(skip)]
+    pub tags: Vec<String>,
 }
 
 impl CsvRecord<PaymentComposite> for PaymentComposite {
@@ -55,7 +62,7 @@ impl CsvStore<PaymentComposite> for PaymentComposite {}
 
 pub type PaymentCompositeStore = BTreeMap<usize, PaymentComposite>;
 
-type CreatePaymentResult = Result<(), Box<dyn Error>>;
+type CreatePaymentResult = Result<(), BudgetError>;
 
 impl PaymentComposite {
     pub fn display(&self) -> PaymentDisplay {
@@ -89,6 +96,7 @@ impl PaymentComposite {
                         Account {
                             id: self.account_id,
                             name: self.account_name.clone(),
+                            ..Default::default()
                         },
                         &mut store.accounts,
                     ));
@@ -105,6 +113,7 @@ impl PaymentComposite {
                     standard: self.amount_standard,
                     high: None,
                     low: None,
+                    currency: self.currency.clone(),
                 },
                 &mut store.amounts,
             ));
@@ -131,17 +140,33 @@ impl PaymentComposite {
 
         let mut update_expense_active = {
             |b: bool| {
-                if let Some(expense) = store.expenses.get_mut(&self.expense_id.unwrap()) {
-                    expense.active = b;
-                }
+                store
+                    .expenses
+                    .update(self.expense_id.unwrap(), |expense| expense.active = b);
             }
         };
         update_expense_active(true);
+        ExpenseTag::tag_record(
+            self.expense_id.unwrap(),
+            &self.tags,
+            &mut store.tags,
+            &mut store.expense_tags,
+        );
 
         self.payment_completed_at = match complete_at {
             None => Utc::now().naive_local(),
             Some(ndt) => ndt,
         };
+        // the account moves by amount_standard in its own currency, at the rate on the day
+        let account = Account::by_id(self.account_id.unwrap(), &mut store.accounts).unwrap();
+        let account_amount = FxRate::convert(
+            self.amount_standard,
+            self.currency.as_deref().unwrap_or(account.currency()),
+            account.currency(),
+            self.payment_completed_at.date(),
+            &store.fx_rates,
+        )?;
+
         // create Payment record
         self.payment_id = Some(Payment::new_id(&store.payments));
         Payment::save_to_store(
@@ -156,14 +181,14 @@ impl PaymentComposite {
         );
 
         // create new AccountBalance record
-        let prev_balance = Account::by_id(self.account_id.unwrap(), &mut store.accounts)
-            .unwrap()
-            .current_balance(&mut store.account_balances);
+        let account = Account::by_id(self.account_id.unwrap(), &mut store.accounts).unwrap();
+        let prev_balance = account.current_balance(&mut store.account_balances);
 
         self.prev_balance = Some(prev_balance);
 
-        let ending_balance = prev_balance - self.amount_standard;
+        let ending_balance = prev_balance - account_amount;
         self.ending_balance = Some(ending_balance);
+
         self.account_balance_id = Some(AccountBalance::save_to_store(
             AccountBalance {
                 id: None,
@@ -181,6 +206,7 @@ impl PaymentComposite {
 #[cfg(test)]
 mod payment_composite_spec {
     use super::*;
+    use crate::schema::tag::Tag;
     use crate::test::spec::Spec;
     use chrono::NaiveDate;
 
@@ -194,6 +220,7 @@ mod payment_composite_spec {
             ending_balance: None,
             amount_id: None,
             amount_standard: Decimal::new(12345, 2),
+            currency: None,
             payment_id: None,
             payment_completed_at: NaiveDate::from_ymd_opt(2023, 2, 17)
                 .unwrap()
@@ -202,9 +229,54 @@ mod payment_composite_spec {
             expense_id: None,
             expense_name: "dog food".to_string(),
             recurrence_state: RecurrenceState::None,
+            tags: vec![],
         }
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_payment__debits_a_foreign_currency_amount_converted_on_the_payment_date() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        FxRate::save_to_store(
+            FxRate {
+                id: None,
+                date: NaiveDate::from_ymd_opt(2023, 2, 1).unwrap(),
+                pair: "EUR/USD".to_string(),
+                rate: Decimal::new(110, 2),
+            },
+            &mut store.fx_rates,
+        );
+
+        let mut payment_comp = payment_comp();
+        payment_comp.amount_standard = Decimal::new(100, 0);
+        payment_comp.currency = Some("EUR".to_string());
+        let at = payment_comp.payment_completed_at;
+        payment_comp.create_payment(&mut store, Some(at)).unwrap();
+
+        // the amount keeps the currency it was paid in, piggybank moves by 110 USD
+        let amount = &store.amounts[&payment_comp.amount_id.unwrap()];
+        assert_eq!(Some("EUR".to_string()), amount.currency);
+        assert_eq!(Decimal::new(100, 0), amount.standard);
+        assert_eq!(Decimal::new(90, 0), payment_comp.ending_balance.unwrap());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_payment__returns_err_without_an_fx_rate() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut payment_comp = payment_comp();
+        payment_comp.currency = Some("EUR".to_string());
+        let balance_count = store.account_balances.len();
+        assert!(matches!(
+            payment_comp.create_payment(&mut store, None),
+            Err(BudgetError::MissingFxRate { .. })
+        ));
+        assert_eq!(balance_count, store.account_balances.len());
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn create_payment__retrieves_account_id_by_name_when_self_account_id_is_none_and_account_exists(
@@ -219,7 +291,7 @@ mod payment_composite_spec {
 
     #[test]
     #[allow(non_snake_case)]
-    // TODO: eventually may want to change this behavior to raise an error saying account dne
+    // simulate --strict rejects unknown names up front, see PaymentEvent::unknown_names
     fn create_payment__creates_account_when_self_account_id_is_none_and_account_name_does_not_exist(
     ) {
         let mut store = Store::new();
@@ -232,7 +304,7 @@ mod payment_composite_spec {
         assert_eq!(3, store.accounts.len());
         assert_eq!(
             3,
-            Account::by_name("New Account", &mut store.accounts)
+            Account::by_name("New Account", &store.accounts)
                 .unwrap()
                 .id
                 .unwrap()
@@ -278,7 +350,7 @@ mod payment_composite_spec {
         assert_eq!(4, store.expenses.len());
         assert_eq!(
             4,
-            Expense::by_name("New Expense", &mut store.expenses)
+            Expense::by_name("New Expense", &store.expenses)
                 .unwrap()
                 .id
                 .unwrap()
@@ -294,10 +366,9 @@ mod payment_composite_spec {
 
         let mut payment_comp = payment_comp();
         payment_comp.create_payment(&mut store, None).unwrap();
-        assert_eq!(
-            false,
+        assert!(
             payment_comp.payment_completed_at
-                == NaiveDate::from_ymd_opt(2023, 2, 17)
+                != NaiveDate::from_ymd_opt(2023, 2, 17)
                     .unwrap()
                     .and_hms_opt(13, 00, 00)
                     .unwrap()
@@ -391,4 +462,21 @@ mod payment_composite_spec {
         assert_eq!(new_acc_bal.amount, payment_comp.ending_balance.unwrap());
         assert_eq!(new_acc_bal.reported_at, payment_comp.payment_completed_at)
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_payment__tags_expense_with_tags() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut payment_comp = payment_comp();
+        payment_comp.tags = vec!["pets".to_string()];
+        payment_comp.create_payment(&mut store, None).unwrap();
+
+        let tag_id = Tag::by_content("pets", &store.tags).unwrap().id.unwrap();
+        assert_eq!(
+            vec![payment_comp.expense_id.unwrap()],
+            ExpenseTag::tagged_ids(tag_id, &store.expense_tags)
+        );
+    }
 }
@@ -3,29 +3,51 @@ use crate::calendar::month::Month;
 use crate::calendar::year_month::YearMonth as YM;
 use crate::composite::payment_composite::PaymentComposite;
 use crate::composite::payment_received_composite::PaymentReceivedComposite;
+use crate::composite::transfer_composite::TransferComposite;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::suggestion::did_you_mean;
+use crate::schema::amount::Amount;
+use crate::storage::store::Store;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use chrono::{Datelike, NaiveDateTime};
+use rand::Rng;
 use rust_decimal::prelude::*;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use std::error::Error;
+use serde_json::value::RawValue;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct PaymentEvent {
     pub id: Option<usize>,
     pub event_type: String,
     pub name: String,
+    #[serde(default)] // unused by transfers
     pub account_name: String,
+    pub from_account_name: Option<String>, // transfers only
+    pub to_account_name: Option<String>,   // transfers only
+    #[serde(with = "rust_decimal::serde::float")]
     pub amount: Decimal,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub amount_low: Option<Decimal>, // used by monte carlo runs
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub amount_high: Option<Decimal>,
+    #[serde(default)]
+    pub currency: Option<String>, // amount's currency, None => the account's, from_account's for transfers
+    #[serde(default)]
+    pub tags: Vec<String>, // applied to the Expense/Income the event records
     pub completed_at: NaiveDateTime,
     pub recurrence_state: RecurrenceState,
+    #[serde(default)]
+    pub until_balance_zero: bool, // transfers only, pay at most what to_account owes
 }
 
 pub enum PaymentEventComposite {
     P(PaymentComposite),
     PR(PaymentReceivedComposite),
+    T(TransferComposite),
     None,
 }
 
@@ -40,12 +62,13 @@ pub enum RecurrenceState {
 }
 
 // fetch
-type PaymentEventFetchResult = Result<Vec<PaymentEvent>, Box<dyn Error>>;
+type PaymentEventFetchResult = Result<Vec<PaymentEvent>, BudgetError>;
+pub type PaymentEventWriteResult = Result<(), BudgetError>;
 // store
 pub type PaymentEventStore = BTreeMap<usize, PaymentEvent>;
 // bin
 pub type PaymentEventBinStore = BTreeMap<YM, PaymentEventStore>;
-type PaymentEventBinResult = Result<(), Box<dyn Error>>;
+type PaymentEventBinResult = Result<(), BudgetError>;
 
 impl CsvRecord<PaymentEvent> for PaymentEvent {
     fn id(&self) -> Option<usize> {
@@ -62,6 +85,10 @@ impl CsvRecord<PaymentEvent> for PaymentEvent {
             event_type: self.event_type.clone(),
             name: self.name.clone(),
             account_name: self.account_name.clone(),
+            from_account_name: self.from_account_name.clone(),
+            to_account_name: self.to_account_name.clone(),
+            tags: self.tags.clone(),
+            currency: self.currency.clone(),
             ..*self
         }
     }
@@ -69,11 +96,48 @@ impl CsvRecord<PaymentEvent> for PaymentEvent {
 
 impl CsvStore<PaymentEvent> for PaymentEvent {}
 
+// deserialize each entry on its own so a bad event is reported by its index
+pub fn fetch_json_events<T: DeserializeOwned>(path: &str) -> BudgetResult<Vec<T>> {
+    fetch_json_event_results(path)?.into_iter().collect()
+}
+
+// Err when the file is unreadable or not a json array, otherwise one result per event
+// errors carry the line and column of the problem within the file
+pub fn fetch_json_event_results<T: DeserializeOwned>(
+    path: &str,
+) -> BudgetResult<Vec<BudgetResult<T>>> {
+    let data = fs::read_to_string(path).map_err(|err| BudgetError::json(path, None, err))?;
+    let values: Vec<&RawValue> =
+        serde_json::from_str(&data).map_err(|err| BudgetError::json_at(path, None, err, (1, 1)))?;
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            serde_json::from_str(value.get()).map_err(|err| {
+                // value borrows from data, so its offset gives the event's position in the file
+                let offset = value.get().as_ptr() as usize - data.as_ptr() as usize;
+                let before = &data[..offset];
+                let line = before.matches('\n').count() + 1;
+                let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+                BudgetError::json_at(path, Some(index), err, (line, column))
+            })
+        })
+        .collect())
+}
+
+pub fn write_json_events<T: Serialize>(path: &str, events: &[T]) -> BudgetResult<()> {
+    let data =
+        serde_json::to_string_pretty(events).map_err(|err| BudgetError::json(path, None, err))?;
+    fs::write(path, data).map_err(|err| BudgetError::json(path, None, err))
+}
+
 impl PaymentEvent {
     pub fn fetch_events(path: String) -> PaymentEventFetchResult {
-        let data: String = fs::read_to_string(path)?.parse()?;
-        let payment_events: Vec<PaymentEvent> = serde_json::from_str(&data)?;
-        Ok(payment_events)
+        fetch_json_events(&path)
+    }
+
+    pub fn write_events(path: String, payment_events: &[PaymentEvent]) -> PaymentEventWriteResult {
+        write_json_events(&path, payment_events)
     }
 
     pub fn fetch_and_bin_one_off_events(
@@ -96,6 +160,82 @@ impl PaymentEvent {
         Ok(())
     }
 
+    // accounts, expenses and incomes the composites would create on first use
+    // one error per name, suggesting the closest existing name
+    pub fn unknown_names<'a>(
+        payment_events: impl IntoIterator<Item = &'a PaymentEvent>,
+        store: &Store,
+    ) -> Vec<BudgetError> {
+        let account_names: Vec<&str> = store.accounts.values().map(|a| a.name.as_str()).collect();
+        let expense_names: Vec<&str> = store.expenses.values().map(|e| e.name.as_str()).collect();
+        let income_names: Vec<&str> = store.incomes.values().map(|i| i.name.as_str()).collect();
+
+        let mut seen: BTreeSet<(&str, &str)> = BTreeSet::new();
+        let mut unknown: Vec<BudgetError> = vec![];
+        for payment_event in payment_events {
+            let accounts = match payment_event.event_type.as_str() {
+                "payment" | "payment_received" => vec![Some(&payment_event.account_name)],
+                "transfer" | "statement_payment" => vec![
+                    payment_event.from_account_name.as_ref(),
+                    payment_event.to_account_name.as_ref(),
+                ],
+                _ => vec![],
+            };
+            for name in accounts.into_iter().flatten() {
+                if !name.is_empty()
+                    && !account_names.contains(&name.as_str())
+                    && seen.insert(("account", name))
+                {
+                    unknown.push(BudgetError::UnknownAccount {
+                        name: name.clone(),
+                        did_you_mean: did_you_mean(name, account_names.iter().copied()),
+                    });
+                }
+            }
+
+            let name = &payment_event.name;
+            match payment_event.event_type.as_str() {
+                "payment"
+                    if !expense_names.contains(&name.as_str())
+                        && seen.insert(("expense", name)) =>
+                {
+                    unknown.push(BudgetError::UnknownExpense {
+                        name: name.clone(),
+                        did_you_mean: did_you_mean(name, expense_names.iter().copied()),
+                    });
+                }
+                "payment_received"
+                    if !income_names.contains(&name.as_str()) && seen.insert(("income", name)) =>
+                {
+                    unknown.push(BudgetError::UnknownIncome {
+                        name: name.clone(),
+                        did_you_mean: did_you_mean(name, income_names.iter().copied()),
+                    });
+                }
+                _ => (),
+            }
+        }
+        unknown
+    }
+
+    // draw self.amount from [amount_low, amount_high]
+    // events without both bounds keep their standard amount, validate reports a lone bound
+    pub fn randomize_amount<R: Rng>(&mut self, rng: &mut R) {
+        let (low, high) = match (self.amount_low, self.amount_high) {
+            (Some(low), Some(high)) => (low, high),
+            _ => return,
+        };
+        self.amount = Amount {
+            id: None,
+            standard: self.amount,
+            low: Some(low),
+            high: Some(high),
+            currency: None,
+        }
+        .randomize_with(rng)
+        .round_dp(2);
+    }
+
     pub fn to_composite(&self) -> PaymentEventComposite {
         match self.event_type.as_str() {
             "payment" => PaymentEventComposite::P(PaymentComposite {
@@ -107,11 +247,13 @@ impl PaymentEvent {
                 ending_balance: None,
                 amount_id: None,
                 amount_standard: self.amount,
+                currency: self.currency.clone(),
                 payment_id: None,
                 payment_completed_at: self.completed_at,
                 expense_id: None,
                 expense_name: self.name.clone(),
                 recurrence_state: self.recurrence_state,
+                tags: self.tags.clone(),
             }),
             "payment_received" => PaymentEventComposite::PR(PaymentReceivedComposite {
                 id: None,
@@ -122,12 +264,41 @@ impl PaymentEvent {
                 ending_balance: None,
                 amount_id: None,
                 amount_standard: self.amount,
+                currency: self.currency.clone(),
                 payment_received_id: None,
                 payment_received_completed_at: self.completed_at,
                 income_id: None,
                 income_name: self.name.clone(),
                 recurrence_state: self.recurrence_state,
+                tags: self.tags.clone(),
             }),
+            "transfer" | "statement_payment" => {
+                match (&self.from_account_name, &self.to_account_name) {
+                    (Some(from_account_name), Some(to_account_name)) => {
+                        PaymentEventComposite::T(TransferComposite {
+                            id: None,
+                            name: self.name.clone(),
+                            from_account_id: None,
+                            from_account_name: from_account_name.clone(),
+                            from_account_balance_id: None,
+                            from_prev_balance: None,
+                            from_ending_balance: None,
+                            to_account_id: None,
+                            to_account_name: to_account_name.clone(),
+                            to_account_balance_id: None,
+                            to_prev_balance: None,
+                            to_ending_balance: None,
+                            amount_standard: self.amount,
+                            currency: self.currency.clone(),
+                            transfer_completed_at: self.completed_at,
+                            recurrence_state: self.recurrence_state,
+                            statement_payment: self.event_type == "statement_payment",
+                            until_balance_zero: self.until_balance_zero,
+                        })
+                    }
+                    _ => PaymentEventComposite::None,
+                }
+            }
             _ => PaymentEventComposite::None,
         }
     }
@@ -173,9 +344,7 @@ mod expense_spec {
             .unwrap();
         assert_eq!(bin_store.len(), 3);
 
-        let mar_store = bin_store
-            .entry(YM::new(2023_i32, MK::Mar))
-            .or_insert(PaymentEventStore::new());
+        let mar_store = bin_store.entry(YM::new(2023_i32, MK::Mar)).or_default();
         assert_eq!(mar_store.len(), 2);
 
         let mar_event = &mar_store[&1];
@@ -201,12 +370,70 @@ mod expense_spec {
             .unwrap();
         assert_eq!(bin_store.len(), 2);
 
-        let mar_store = bin_store
-            .entry(YM::new(2023_i32, MK::Mar))
-            .or_insert(PaymentEventStore::new());
+        let mar_store = bin_store.entry(YM::new(2023_i32, MK::Mar)).or_default();
         assert_eq!(mar_store.len(), 2);
     }
 
+    fn ranged_payment_event(low: Option<Decimal>, high: Option<Decimal>) -> PaymentEvent {
+        PaymentEvent {
+            event_type: "payment".to_string(),
+            name: "Utilities".to_string(),
+            account_name: "My Bank Account".to_string(),
+            amount: Decimal::new(100, 0),
+            amount_low: low,
+            amount_high: high,
+            completed_at: NaiveDate::from_ymd_opt(2023, 1, 2)
+                .unwrap()
+                .and_hms_opt(12, 00, 00)
+                .unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn randomize_amount__draws_amount_between_low_and_high() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let mut event =
+                ranged_payment_event(Some(Decimal::new(80, 0)), Some(Decimal::new(140, 0)));
+            event.randomize_amount(&mut rng);
+            assert!(event.amount >= Decimal::new(80, 0));
+            assert!(event.amount < Decimal::new(140, 0));
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn randomize_amount__keeps_standard_amount_when_no_range_given() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut event = ranged_payment_event(None, None);
+        event.randomize_amount(&mut rng);
+        assert_eq!(Decimal::new(100, 0), event.amount);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn randomize_amount__keeps_standard_amount_when_one_bound_is_missing() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut event = ranged_payment_event(Some(Decimal::new(80, 0)), None);
+        event.randomize_amount(&mut rng);
+        assert_eq!(Decimal::new(100, 0), event.amount);
+
+        let mut event = ranged_payment_event(None, Some(Decimal::new(140, 0)));
+        event.randomize_amount(&mut rng);
+        assert_eq!(Decimal::new(100, 0), event.amount);
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn to_composite__returns_payment_composite_when_0_is_payment() {
@@ -214,7 +441,6 @@ mod expense_spec {
         Spec::init(&mut store);
 
         match (PaymentEvent {
-            id: None,
             event_type: "payment".to_string(),
             name: "My Payment".to_string(),
             account_name: "My Bank Account".to_string(),
@@ -223,7 +449,7 @@ mod expense_spec {
                 .unwrap()
                 .and_hms_opt(12, 00, 00)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         })
         .to_composite()
         {
@@ -241,7 +467,6 @@ mod expense_spec {
         Spec::init(&mut store);
 
         match (PaymentEvent {
-            id: None,
             event_type: "payment_received".to_string(),
             name: "My Payment Received".to_string(),
             account_name: "My Bank Account".to_string(),
@@ -250,7 +475,7 @@ mod expense_spec {
                 .unwrap()
                 .and_hms_opt(12, 00, 00)
                 .unwrap(),
-            recurrence_state: RecurrenceState::None,
+            ..Default::default()
         })
         .to_composite()
         {
@@ -260,4 +485,79 @@ mod expense_spec {
             _ => assert_eq!(0, 1),
         };
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn to_composite__returns_transfer_composite_when_0_is_transfer() {
+        match (PaymentEvent {
+            event_type: "transfer".to_string(),
+            name: "Savings Sweep".to_string(),
+            from_account_name: Some("Checking".to_string()),
+            to_account_name: Some("Savings".to_string()),
+            amount: Decimal::new(50000, 2),
+            completed_at: NaiveDate::from_ymd_opt(2023, 1, 2)
+                .unwrap()
+                .and_hms_opt(12, 00, 00)
+                .unwrap(),
+            ..Default::default()
+        })
+        .to_composite()
+        {
+            PaymentEventComposite::T(transfer) => {
+                assert_eq!(transfer.from_account_name, "Checking".to_string());
+                assert_eq!(transfer.to_account_name, "Savings".to_string());
+            }
+            _ => assert_eq!(0, 1),
+        };
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn to_composite__returns_none_when_transfer_is_missing_an_account() {
+        match (PaymentEvent {
+            event_type: "transfer".to_string(),
+            name: "Savings Sweep".to_string(),
+            from_account_name: Some("Checking".to_string()),
+            amount: Decimal::new(50000, 2),
+            completed_at: NaiveDate::from_ymd_opt(2023, 1, 2)
+                .unwrap()
+                .and_hms_opt(12, 00, 00)
+                .unwrap(),
+            ..Default::default()
+        })
+        .to_composite()
+        {
+            PaymentEventComposite::None => (),
+            _ => assert_eq!(0, 1),
+        };
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn unknown_names__lists_each_name_once_with_a_suggestion() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let mut events = [
+            ranged_payment_event(None, None),
+            ranged_payment_event(None, None),
+        ];
+        for event in events.iter_mut() {
+            event.name = "mortgage".to_string();
+            event.account_name = "piggybnak".to_string();
+        }
+        events[1].event_type = "payment_received".to_string();
+        events[1].name = "Spaceman".to_string();
+
+        let unknown: Vec<String> = PaymentEvent::unknown_names(events.iter(), &store)
+            .iter()
+            .map(|err| err.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                r#"Unknown account "piggybnak", did you mean "piggybank"?"#.to_string(),
+                r#"Unknown income "Spaceman", did you mean "spaceman"?"#.to_string(),
+            ],
+            unknown
+        );
+    }
 }
@@ -1,18 +1,21 @@
 use crate::composite::payment_display::PaymentDisplay;
+use crate::error::budget_error::BudgetError;
 use crate::error::error_handler::ErrorHandler;
 use crate::schema::account::Account;
 use crate::schema::account_balance::AccountBalance;
 use crate::schema::amount::Amount;
+use crate::schema::fx_rate::FxRate;
 use crate::schema::income::Income;
+use crate::schema::income_tag::IncomeTag;
 use crate::schema::payment_received::PaymentReceived;
 use crate::storage::store::Store;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
+use crate::traits::tag_link::TagLink;
 use chrono::{NaiveDateTime, Utc};
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::error::Error;
 
 use super::payment_event::RecurrenceState;
 
@@ -29,11 +32,15 @@ pub struct PaymentReceivedComposite {
     pub amount_id: Option<usize>,
     #[serde(with = "rust_decimal::serde::float")]
     pub amount_standard: Decimal,
+    #[serde(default)]
+    pub currency: Option<String>, // amount_standard's, None => the account's
     pub payment_received_id: Option<usize>,
     pub payment_received_completed_at: NaiveDateTime,
     pub income_id: Option<usize>,
     pub income_name: String,
     pub recurrence_state: RecurrenceState,
+    #[serde(skip)]
+    pub tags: Vec<String>,
 }
 
 impl CsvRecord<PaymentReceivedComposite> for PaymentReceivedComposite {
@@ -55,7 +62,7 @@ impl CsvStore<PaymentReceivedComposite> for PaymentReceivedComposite {}
 
 pub type PaymentReceivedCompositeStore = BTreeMap<usize, PaymentReceivedComposite>;
 
-type CreatePaymentReceivedResult = Result<(), Box<dyn Error>>;
+type CreatePaymentReceivedResult = Result<(), BudgetError>;
 
 impl PaymentReceivedComposite {
     pub fn display(&self) -> PaymentDisplay {
@@ -89,6 +96,7 @@ impl PaymentReceivedComposite {
                         Account {
                             id: self.account_id, // T::new_id returns T, this unwrap is a formality
                             name: self.account_name.clone(),
+                            ..Default::default()
                         },
                         &mut store.accounts,
                     ));
@@ -105,6 +113,7 @@ impl PaymentReceivedComposite {
                     standard: self.amount_standard,
                     high: None,
                     low: None,
+                    currency: self.currency.clone(),
                 },
                 &mut store.amounts,
             ));
@@ -131,18 +140,34 @@ impl PaymentReceivedComposite {
 
         let mut update_income_active = {
             |b: bool| {
-                if let Some(income) = store.incomes.get_mut(&self.income_id.unwrap()) {
-                    income.active = b;
-                }
+                store
+                    .incomes
+                    .update(self.income_id.unwrap(), |income| income.active = b);
             }
         };
         update_income_active(true);
+        IncomeTag::tag_record(
+            self.income_id.unwrap(),
+            &self.tags,
+            &mut store.tags,
+            &mut store.income_tags,
+        );
 
         self.payment_received_completed_at = match complete_at {
             None => Utc::now().naive_local(),
             Some(ndt) => ndt,
         };
 
+        // the account moves by amount_standard in its own currency, at the rate on the day
+        let account = Account::by_id(self.account_id.unwrap(), &mut store.accounts).unwrap();
+        let account_amount = FxRate::convert(
+            self.amount_standard,
+            self.currency.as_deref().unwrap_or(account.currency()),
+            account.currency(),
+            self.payment_received_completed_at.date(),
+            &store.fx_rates,
+        )?;
+
         // create PaymentReceived record
         self.payment_received_id = Some(PaymentReceived::new_id(&store.payments_received));
         PaymentReceived::save_to_store(
@@ -162,7 +187,7 @@ impl PaymentReceivedComposite {
             .current_balance(&mut store.account_balances);
         self.prev_balance = Some(prev_balance);
 
-        let ending_balance = prev_balance + self.amount_standard;
+        let ending_balance = prev_balance + account_amount;
         self.ending_balance = Some(ending_balance);
 
         self.account_balance_id = Some(AccountBalance::save_to_store(
@@ -195,6 +220,7 @@ mod payment_composite_spec {
             ending_balance: None,
             amount_id: None,
             amount_standard: Decimal::new(12345, 2),
+            currency: None,
             payment_received_id: None,
             payment_received_completed_at: NaiveDate::from_ymd_opt(2023, 2, 17)
                 .unwrap()
@@ -203,6 +229,7 @@ mod payment_composite_spec {
             income_id: None,
             income_name: "cowboy".to_string(),
             recurrence_state: RecurrenceState::None,
+            tags: vec![],
         }
     }
 
@@ -222,7 +249,7 @@ mod payment_composite_spec {
 
     #[test]
     #[allow(non_snake_case)]
-    // TODO: eventually may want to change this behavior to raise an error saying account dne
+    // simulate --strict rejects unknown names up front, see PaymentEvent::unknown_names
     fn create_payment_received__creates_account_when_self_account_id_is_none_and_account_name_does_not_exist(
     ) {
         let mut store = Store::new();
@@ -237,7 +264,7 @@ mod payment_composite_spec {
         assert_eq!(3, store.accounts.len());
         assert_eq!(
             3,
-            Account::by_name("New Account", &mut store.accounts)
+            Account::by_name("New Account", &store.accounts)
                 .unwrap()
                 .id
                 .unwrap()
@@ -288,7 +315,7 @@ mod payment_composite_spec {
         assert_eq!(3, store.incomes.len());
         assert_eq!(
             3,
-            Income::by_name("New Income", &mut store.incomes)
+            Income::by_name("New Income", &store.incomes)
                 .unwrap()
                 .id
                 .unwrap()
@@ -307,10 +334,9 @@ mod payment_composite_spec {
         payment_rec_comp
             .create_payment_received(&mut store, None)
             .unwrap();
-        assert_eq!(
-            false,
+        assert!(
             payment_rec_comp.payment_received_completed_at
-                == NaiveDate::from_ymd_opt(2023, 2, 17)
+                != NaiveDate::from_ymd_opt(2023, 2, 17)
                     .unwrap()
                     .and_hms_opt(13, 00, 00)
                     .unwrap()
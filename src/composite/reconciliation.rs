@@ -0,0 +1,398 @@
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::payment_event::PaymentEvent;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// one forecast event, actual transaction, or the pair of them once matched
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReconciliationEntry {
+    pub id: Option<usize>,
+    pub year_month: String, // month of the forecast, or of the actual when unexpected
+    pub status: ReconciliationStatus,
+    pub event_type: String,
+    pub name: String, // forecast name when there is one, so variance lands on the Expense/Income
+    pub account_name: String,
+    pub forecast_date: Option<NaiveDate>,
+    pub actual_date: Option<NaiveDate>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub forecast_amount: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub actual_amount: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub variance: Decimal, // actual - forecast
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationStatus {
+    Matched,
+    Missing,    // forecast with no actual
+    Unexpected, // actual with no forecast
+}
+
+// totals per Expense/Income per month
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReconciliationSummary {
+    pub id: Option<usize>,
+    pub year_month: String,
+    pub event_type: String,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub forecast_total: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub actual_total: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub variance: Decimal,
+}
+
+pub type ReconciliationEntryStore = BTreeMap<usize, ReconciliationEntry>;
+pub type ReconciliationSummaryStore = BTreeMap<usize, ReconciliationSummary>;
+
+impl CsvRecord<ReconciliationEntry> for ReconciliationEntry {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> ReconciliationEntry {
+        self.clone()
+    }
+}
+
+impl CsvStore<ReconciliationEntry> for ReconciliationEntry {}
+
+impl CsvRecord<ReconciliationSummary> for ReconciliationSummary {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> ReconciliationSummary {
+        self.clone()
+    }
+}
+
+impl CsvStore<ReconciliationSummary> for ReconciliationSummary {}
+
+impl ReconciliationEntry {
+    // pairs each forecast event with an actual on the same account(s) within window_days
+    // an actual matches when the words of one name run inside the other's and the amount is
+    // within half of the forecast, or the amounts are equal;
+    // the closest name, amount and date wins
+    // statement payments are skipped, their amount is only known once the month runs
+    pub fn reconcile(
+        forecast: &[&PaymentEvent],
+        actuals: &[&PaymentEvent],
+        window_days: i64,
+    ) -> ReconciliationEntryStore {
+        let mut forecast: Vec<&PaymentEvent> = forecast
+            .iter()
+            .filter(|event| event.event_type != "statement_payment")
+            .copied()
+            .collect();
+        forecast.sort_by_key(|event| event.completed_at);
+        let mut unmatched: Vec<&PaymentEvent> = actuals.to_vec();
+        unmatched.sort_by_key(|event| event.completed_at);
+
+        let mut entries = ReconciliationEntryStore::new();
+        for event in forecast.into_iter() {
+            let best = unmatched
+                .iter()
+                .enumerate()
+                .filter(|(_, actual)| {
+                    accounts(actual) == accounts(event)
+                        && actual.event_type == event.event_type
+                        && days_apart(actual, event) <= window_days
+                        && ((names_match(actual, event) && amounts_close(actual, event))
+                            || actual.amount == event.amount)
+                })
+                .min_by_key(|(_, actual)| {
+                    (
+                        !names_match(actual, event),
+                        (actual.amount - event.amount).abs(),
+                        days_apart(actual, event),
+                    )
+                })
+                .map(|(index, _)| index);
+            let actual = best.map(|index| unmatched.remove(index));
+            let entry = ReconciliationEntry {
+                id: None,
+                year_month: year_month(event).display(),
+                status: match actual {
+                    Some(_) => ReconciliationStatus::Matched,
+                    None => ReconciliationStatus::Missing,
+                },
+                event_type: event.event_type.clone(),
+                name: event.name.clone(),
+                account_name: describe_accounts(event),
+                forecast_date: Some(event.completed_at.date()),
+                actual_date: actual.map(|actual| actual.completed_at.date()),
+                forecast_amount: Some(event.amount),
+                actual_amount: actual.map(|actual| actual.amount),
+                variance: actual.map_or(Decimal::new(0, 0), |actual| actual.amount) - event.amount,
+            };
+            ReconciliationEntry::save_to_store(entry, &mut entries);
+        }
+
+        for actual in unmatched.into_iter() {
+            let entry = ReconciliationEntry {
+                id: None,
+                year_month: year_month(actual).display(),
+                status: ReconciliationStatus::Unexpected,
+                event_type: actual.event_type.clone(),
+                name: actual.name.clone(),
+                account_name: describe_accounts(actual),
+                forecast_date: None,
+                actual_date: Some(actual.completed_at.date()),
+                forecast_amount: None,
+                actual_amount: Some(actual.amount),
+                variance: actual.amount,
+            };
+            ReconciliationEntry::save_to_store(entry, &mut entries);
+        }
+        entries
+    }
+
+    pub fn summarize(entries: &ReconciliationEntryStore) -> ReconciliationSummaryStore {
+        // (year_month, event_type, name) => (forecast_total, actual_total)
+        let mut totals: BTreeMap<(String, String, String), (Decimal, Decimal)> = BTreeMap::new();
+        for entry in entries.values() {
+            let total = totals
+                .entry((
+                    entry.year_month.clone(),
+                    entry.event_type.clone(),
+                    entry.name.clone(),
+                ))
+                .or_default();
+            total.0 += entry.forecast_amount.unwrap_or_default();
+            total.1 += entry.actual_amount.unwrap_or_default();
+        }
+
+        let mut summary_store = ReconciliationSummaryStore::new();
+        for ((year_month, event_type, name), (forecast_total, actual_total)) in totals.into_iter() {
+            ReconciliationSummary::save_to_store(
+                ReconciliationSummary {
+                    id: None,
+                    year_month,
+                    event_type,
+                    name,
+                    forecast_total,
+                    actual_total,
+                    variance: actual_total - forecast_total,
+                },
+                &mut summary_store,
+            );
+        }
+        summary_store
+    }
+}
+
+fn accounts(event: &PaymentEvent) -> (&str, Option<&String>, Option<&String>) {
+    (
+        event.account_name.as_str(),
+        event.from_account_name.as_ref(),
+        event.to_account_name.as_ref(),
+    )
+}
+
+fn describe_accounts(event: &PaymentEvent) -> String {
+    match (&event.from_account_name, &event.to_account_name) {
+        (Some(from), Some(to)) => format!("{from} -> {to}"),
+        _ => event.account_name.clone(),
+    }
+}
+
+fn days_apart(a: &PaymentEvent, b: &PaymentEvent) -> i64 {
+    (a.completed_at.date() - b.completed_at.date())
+        .num_days()
+        .abs()
+}
+
+// bank memos carry more than the forecast name, e.g. "SPACE MAN PAYROLL 0315"
+// whole words only, "Gas" does not match "VEGAS"
+fn names_match(actual: &PaymentEvent, forecast: &PaymentEvent) -> bool {
+    let actual_words = words(&actual.name);
+    let forecast_words = words(&forecast.name);
+    contains_words(&actual_words, &forecast_words) || contains_words(&forecast_words, &actual_words)
+}
+
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn contains_words(haystack: &[String], needle: &[String]) -> bool {
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+// a name match may be off the forecast by up to half, "Gas Station Snacks" is not "Gas"
+fn amounts_close(actual: &PaymentEvent, forecast: &PaymentEvent) -> bool {
+    (actual.amount - forecast.amount).abs() <= forecast.amount.abs() * Decimal::new(5, 1)
+}
+
+fn year_month(event: &PaymentEvent) -> YM {
+    YM::new(
+        event.completed_at.year(),
+        Month::key_from_id(event.completed_at.month()),
+    )
+}
+
+#[cfg(test)]
+mod reconciliation_spec {
+    use super::*;
+
+    fn event(event_type: &str, name: &str, day: u32, amount: i64) -> PaymentEvent {
+        PaymentEvent {
+            event_type: event_type.to_string(),
+            name: name.to_string(),
+            account_name: "Big Bank".to_string(),
+            amount: Decimal::new(amount, 0),
+            completed_at: NaiveDate::from_ymd_opt(2023, 3, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn reconcile__matches_missing_and_unexpected() {
+        let forecast = [
+            event("payment", "Groceries", 2, 200),
+            event("payment_received", "Space Man", 15, 2500),
+            event("payment", "Mortgage", 5, 2000),
+        ];
+        let actuals = [
+            // a day late and over budget
+            event("payment", "GROCERIES STORE #12", 3, 240),
+            // memo unrelated to the name, matched on amount
+            event("payment_received", "ACH DEPOSIT", 14, 2500),
+            event("payment", "Car Repair", 12, 600),
+        ];
+        let forecast_refs: Vec<&PaymentEvent> = forecast.iter().collect();
+        let actual_refs: Vec<&PaymentEvent> = actuals.iter().collect();
+
+        let entries = ReconciliationEntry::reconcile(&forecast_refs, &actual_refs, 3);
+        let entries: Vec<&ReconciliationEntry> = entries.values().collect();
+        assert_eq!(4, entries.len());
+
+        assert_eq!("Groceries", entries[0].name);
+        assert_eq!(ReconciliationStatus::Matched, entries[0].status);
+        assert_eq!(Decimal::new(40, 0), entries[0].variance);
+
+        assert_eq!("Mortgage", entries[1].name);
+        assert_eq!(ReconciliationStatus::Missing, entries[1].status);
+        assert_eq!(Decimal::new(-2000, 0), entries[1].variance);
+
+        assert_eq!("Space Man", entries[2].name);
+        assert_eq!(ReconciliationStatus::Matched, entries[2].status);
+
+        assert_eq!("Car Repair", entries[3].name);
+        assert_eq!(ReconciliationStatus::Unexpected, entries[3].status);
+        assert_eq!("2023-03", entries[3].year_month);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn reconcile__does_not_match_outside_window() {
+        let forecast = [event("payment", "Groceries", 2, 200)];
+        let actuals = [event("payment", "Groceries", 9, 200)];
+
+        let entries = ReconciliationEntry::reconcile(
+            &forecast.iter().collect::<Vec<&PaymentEvent>>(),
+            &actuals.iter().collect::<Vec<&PaymentEvent>>(),
+            3,
+        );
+        let statuses: Vec<ReconciliationStatus> =
+            entries.values().map(|entry| entry.status).collect();
+        assert_eq!(
+            vec![
+                ReconciliationStatus::Missing,
+                ReconciliationStatus::Unexpected
+            ],
+            statuses
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn reconcile__does_not_match_a_near_miss_name() {
+        let forecast = [event("payment", "Gas", 5, 60)];
+        let actuals = [
+            // same first word, far off the forecast amount
+            event("payment", "Gas Station Snacks", 5, 6),
+            // contains "gas" but not as a word
+            event("payment", "VEGAS PARKING", 5, 55),
+        ];
+
+        let entries = ReconciliationEntry::reconcile(
+            &forecast.iter().collect::<Vec<&PaymentEvent>>(),
+            &actuals.iter().collect::<Vec<&PaymentEvent>>(),
+            3,
+        );
+        let statuses: Vec<ReconciliationStatus> =
+            entries.values().map(|entry| entry.status).collect();
+        assert_eq!(
+            vec![
+                ReconciliationStatus::Missing,
+                ReconciliationStatus::Unexpected,
+                ReconciliationStatus::Unexpected
+            ],
+            statuses
+        );
+
+        // within tolerance the longer memo still matches
+        let actuals = [event("payment", "GAS STATION 0042", 6, 52)];
+        let entries = ReconciliationEntry::reconcile(
+            &forecast.iter().collect::<Vec<&PaymentEvent>>(),
+            &actuals.iter().collect::<Vec<&PaymentEvent>>(),
+            3,
+        );
+        assert_eq!(1, entries.len());
+        assert_eq!(ReconciliationStatus::Matched, entries[&1].status);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn summarize__totals_variance_per_name() {
+        let forecast = [
+            event("payment", "Groceries", 2, 200),
+            event("payment", "Groceries", 9, 200),
+        ];
+        let actuals = [
+            event("payment", "Groceries", 2, 180),
+            event("payment", "Groceries", 10, 260),
+        ];
+
+        let entries = ReconciliationEntry::reconcile(
+            &forecast.iter().collect::<Vec<&PaymentEvent>>(),
+            &actuals.iter().collect::<Vec<&PaymentEvent>>(),
+            3,
+        );
+        let summary = ReconciliationEntry::summarize(&entries);
+        assert_eq!(1, summary.len());
+        let groceries = summary.values().next().unwrap();
+        assert_eq!(Decimal::new(400, 0), groceries.forecast_total);
+        assert_eq!(Decimal::new(440, 0), groceries.actual_total);
+        assert_eq!(Decimal::new(40, 0), groceries.variance);
+    }
+}
@@ -1,16 +1,15 @@
 use crate::calendar::calendar_slice::CalendarSlice;
 use crate::calendar::month::Month;
 use crate::calendar::year_month::YearMonth as YM;
-use crate::composite::payment_event::PaymentEvent;
 use crate::composite::payment_event::PaymentEventBinStore;
-use crate::schema::recurrance::Every;
+use crate::composite::payment_event::{fetch_json_events, write_json_events, PaymentEvent};
+use crate::error::budget_error::BudgetError;
+use crate::schema::recurrance::{Every, Roll};
 use crate::traits::csv_store::CsvStore;
 use chrono::Datelike;
 use chrono::{Days, Months, NaiveDate};
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
-use std::fs;
 
 use super::payment_event::RecurrenceState;
 
@@ -19,21 +18,46 @@ pub struct RecurringPaymentEvent {
     pub id: Option<usize>,
     pub event_type: String,
     pub name: String,
+    #[serde(default)] // unused by transfers
     pub account_name: String,
+    pub from_account_name: Option<String>, // transfers only
+    pub to_account_name: Option<String>,   // transfers only
+    #[serde(with = "rust_decimal::serde::float")]
     pub amount: Decimal,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub amount_low: Option<Decimal>, // used by monte carlo runs
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub amount_high: Option<Decimal>,
+    #[serde(default)]
+    pub currency: Option<String>, // amount's currency, None => the account's, from_account's for transfers
+    #[serde(default)]
+    pub tags: Vec<String>, // applied to the Expense/Income the event records
     pub start: NaiveDate,
-    pub end: NaiveDate, // TODO: turn into Option<NaiveDate>
+    #[serde(default)]
+    pub end: Option<NaiveDate>, // None => open ended
+    #[serde(default)]
+    pub count: Option<usize>, // stop after this many payments, counted from start
+    #[serde(default)]
+    pub until_balance_zero: bool, // transfers only, stop once to_account is paid off
     pub recurrence: Every,
+    #[serde(default)]
+    pub roll: Roll, // move weekend and holiday payments to a banking day
 }
 
-pub type RecurringPaymentEventBinResult = Result<(), Box<dyn Error>>;
-pub type RecurringPaymentEventFetchResult = Result<Vec<RecurringPaymentEvent>, Box<dyn Error>>;
+pub type RecurringPaymentEventBinResult = Result<(), BudgetError>;
+pub type RecurringPaymentEventFetchResult = Result<Vec<RecurringPaymentEvent>, BudgetError>;
+pub type RecurringPaymentEventWriteResult = Result<(), BudgetError>;
 
 impl RecurringPaymentEvent {
     pub fn fetch_events(path: String) -> RecurringPaymentEventFetchResult {
-        let data: String = fs::read_to_string(path)?.parse()?;
-        let recc_payment_events: Vec<RecurringPaymentEvent> = serde_json::from_str(&data)?;
-        Ok(recc_payment_events)
+        fetch_json_events(&path)
+    }
+
+    pub fn write_events(
+        path: String,
+        recc_payment_events: &[RecurringPaymentEvent],
+    ) -> RecurringPaymentEventWriteResult {
+        write_json_events(&path, recc_payment_events)
     }
 
     pub fn fetch_and_bin_recurring_events(
@@ -62,12 +86,23 @@ impl RecurringPaymentEvent {
     pub fn payment_events(&self, cal_slice: &CalendarSlice) -> Vec<PaymentEvent> {
         let mut payment_events: Vec<PaymentEvent> = self
             .payment_dates(cal_slice)
+            .iter()
+            .map(|date| self.to_payment_event(date))
+            .collect();
+        if let Some(payment_event) = payment_events.first_mut() {
+            payment_event.recurrence_state = RecurrenceState::First;
+        }
+        // only the final payment of the recurrence, not the last one inside cal_slice
+        // the recurrence steps from the date before roll
+        let scheduled_dates: Vec<NaiveDate> = self
+            .dates(cal_slice)
             .into_iter()
-            .map(|date| self.to_payment_event(&date))
+            .map(|(scheduled, _rolled)| scheduled)
             .collect();
-        payment_events[0].recurrence_state = RecurrenceState::First;
-        if let Some(payment_event) = payment_events.last_mut() {
-            payment_event.recurrence_state = RecurrenceState::Last;
+        if let Some(last_date) = scheduled_dates.last() {
+            if !self.includes(self.next_payment_date(*last_date), scheduled_dates.len()) {
+                payment_events.last_mut().unwrap().recurrence_state = RecurrenceState::Last;
+            }
         }
         // println!("{payment_events:#?}");
         payment_events
@@ -79,27 +114,54 @@ impl RecurringPaymentEvent {
             event_type: self.event_type.clone(),
             name: self.name.clone(),
             account_name: self.account_name.clone(),
+            from_account_name: self.from_account_name.clone(),
+            to_account_name: self.to_account_name.clone(),
             amount: self.amount,
+            amount_low: self.amount_low,
+            amount_high: self.amount_high,
+            currency: self.currency.clone(),
+            tags: self.tags.clone(),
             completed_at: date.and_hms_opt(12, 0, 0).unwrap(), // TODO: consider how to handle time
             recurrence_state: RecurrenceState::Active,
+            until_balance_zero: self.until_balance_zero,
         }
     }
 
+    // scheduled dates after roll
     pub fn payment_dates(&self, cal_slice: &CalendarSlice) -> Vec<NaiveDate> {
-        let mut payment_dates: Vec<NaiveDate> = vec![self.start];
+        self.dates(cal_slice)
+            .into_iter()
+            .map(|(_scheduled, rolled)| rolled)
+            .collect()
+    }
 
-        if self.start == self.end {
-            return payment_dates;
+    // (scheduled, rolled) for each payment that lands before cal_slice ends, in order
+    // the slice is checked after roll, July 1 rolled back to June 30 is a June payment
+    // the recurrence steps from the scheduled date
+    // LastBusinessDay steps back past holidays whatever the roll
+    fn dates(&self, cal_slice: &CalendarSlice) -> Vec<(NaiveDate, NaiveDate)> {
+        let roll = match self.recurrence {
+            Every::LastBusinessDay => Roll::Back,
+            _ => self.roll,
+        };
+        let slice_end = cal_slice.end.start_of_next_month();
+        let mut dates: Vec<(NaiveDate, NaiveDate)> = vec![];
+        let mut curr_date = self.recurrence.on_or_after(self.start);
+        while self.includes(curr_date, dates.len()) {
+            let rolled = roll.apply(curr_date, &cal_slice.holidays);
+            if rolled >= slice_end {
+                break;
+            }
+            dates.push((curr_date, rolled));
+            curr_date = self.next_payment_date(curr_date);
         }
 
-        let mut curr_date = self.next_payment_date(self.start);
-        while curr_date <= self.end && curr_date < cal_slice.end.start_of_next_month() {
-            payment_dates.push(curr_date);
-            let next_date = self.next_payment_date(curr_date);
-            curr_date = next_date;
-        }
+        dates
+    }
 
-        payment_dates
+    // whether a payment on date, preceded by `paid` payments, is part of the recurrence
+    fn includes(&self, date: NaiveDate, paid: usize) -> bool {
+        self.end.map_or(true, |end| date <= end) && self.count.map_or(true, |count| paid < count)
     }
 
     pub fn next_payment_date(&self, last_payment_date: NaiveDate) -> NaiveDate {
@@ -110,14 +172,29 @@ impl RecurringPaymentEvent {
             Every::Weeks(n) => last_payment_date
                 .checked_add_days(Days::new(7 * n))
                 .expect("Error Computing Next Payment Event Date by Week"),
-            Every::Months(n) => last_payment_date
-                .checked_add_months(Months::new(n))
+            // step from start so a payment on the 31st is not clamped to the 30th for good
+            Every::Months(n) => self
+                .start
+                .checked_add_months(Months::new(self.months_since_start(last_payment_date) + n))
                 .expect("Error Computing Next Payment Event Date by Month"),
-            Every::Years(n) => last_payment_date
-                .checked_add_months(Months::new(12 * n))
+            Every::Years(n) => self
+                .start
+                .checked_add_months(Months::new(
+                    self.months_since_start(last_payment_date) + 12 * n,
+                ))
                 .expect("Error Computing Next Payment Event Date by Year"),
+            rule => rule.on_or_after(
+                last_payment_date
+                    .succ_opt()
+                    .expect("Error Computing Next Payment Event Date by Rule"),
+            ),
         }
     }
+
+    fn months_since_start(&self, date: NaiveDate) -> u32 {
+        let months = |date: NaiveDate| date.year() * 12 + date.month() as i32;
+        (months(date) - months(self.start)).max(0) as u32
+    }
 }
 
 #[cfg(test)]
@@ -138,10 +215,19 @@ mod payment_composite_spec {
             event_type: "payment".to_string(),
             name: "dog food".to_string(),
             account_name: "piggybank".to_string(),
+            from_account_name: None,
+            to_account_name: None,
             amount: Decimal::new(50, 0),
+            amount_low: None,
+            amount_high: None,
+            currency: None,
+            tags: vec![],
             start,
-            end,
+            end: Some(end),
+            count: None,
+            until_balance_zero: false,
             recurrence,
+            roll: Roll::None,
         }
     }
 
@@ -149,8 +235,8 @@ mod payment_composite_spec {
     #[allow(non_snake_case)]
     fn payment_events__returns_vector_of_payment_events_according_to_recurrence() {
         // start end chosen so that both are payment_event_dates
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2023, 06, 16).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 16).unwrap();
         let cal_slice = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Jun)).unwrap();
         let reccurring_payment_event = reccurring_payment_event(start, end, Every::Weeks(2));
         let payment_events: Vec<PaymentEvent> = reccurring_payment_event.payment_events(&cal_slice);
@@ -164,70 +250,70 @@ mod payment_composite_spec {
         }
         assert_eq!(
             payment_events[0].completed_at,
-            NaiveDate::from_ymd_opt(2023, 02, 10)
+            NaiveDate::from_ymd_opt(2023, 2, 10)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[1].completed_at,
-            NaiveDate::from_ymd_opt(2023, 02, 24)
+            NaiveDate::from_ymd_opt(2023, 2, 24)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[2].completed_at,
-            NaiveDate::from_ymd_opt(2023, 03, 10)
+            NaiveDate::from_ymd_opt(2023, 3, 10)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[3].completed_at,
-            NaiveDate::from_ymd_opt(2023, 03, 24)
+            NaiveDate::from_ymd_opt(2023, 3, 24)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[4].completed_at,
-            NaiveDate::from_ymd_opt(2023, 04, 07)
+            NaiveDate::from_ymd_opt(2023, 4, 7)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[5].completed_at,
-            NaiveDate::from_ymd_opt(2023, 04, 21)
+            NaiveDate::from_ymd_opt(2023, 4, 21)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[6].completed_at,
-            NaiveDate::from_ymd_opt(2023, 05, 05)
+            NaiveDate::from_ymd_opt(2023, 5, 5)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[7].completed_at,
-            NaiveDate::from_ymd_opt(2023, 05, 19)
+            NaiveDate::from_ymd_opt(2023, 5, 19)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[8].completed_at,
-            NaiveDate::from_ymd_opt(2023, 06, 02)
+            NaiveDate::from_ymd_opt(2023, 6, 2)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
         );
         assert_eq!(
             payment_events[9].completed_at,
-            NaiveDate::from_ymd_opt(2023, 06, 16)
+            NaiveDate::from_ymd_opt(2023, 6, 16)
                 .unwrap()
                 .and_hms_opt(12, 0, 0)
                 .unwrap()
@@ -238,8 +324,8 @@ mod payment_composite_spec {
     #[allow(non_snake_case)]
     fn payment_dates__returns_vector_of_naivedates_according_to_recurrence() {
         // start end chosen so that both are payment_event_dates
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2023, 06, 16).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 16).unwrap();
         let cal_slice = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Jun)).unwrap();
         let reccurring_payment_event = reccurring_payment_event(start, end, Every::Weeks(2));
         let payment_dates: Vec<NaiveDate> = reccurring_payment_event.payment_dates(&cal_slice);
@@ -248,45 +334,45 @@ mod payment_composite_spec {
         assert_eq!(payment_dates[0], reccurring_payment_event.start);
         assert_eq!(
             payment_dates[1],
-            NaiveDate::from_ymd_opt(2023, 02, 24).unwrap()
+            NaiveDate::from_ymd_opt(2023, 2, 24).unwrap()
         );
         assert_eq!(
             payment_dates[2],
-            NaiveDate::from_ymd_opt(2023, 03, 10).unwrap()
+            NaiveDate::from_ymd_opt(2023, 3, 10).unwrap()
         );
         assert_eq!(
             payment_dates[3],
-            NaiveDate::from_ymd_opt(2023, 03, 24).unwrap()
+            NaiveDate::from_ymd_opt(2023, 3, 24).unwrap()
         );
         assert_eq!(
             payment_dates[4],
-            NaiveDate::from_ymd_opt(2023, 04, 7).unwrap()
+            NaiveDate::from_ymd_opt(2023, 4, 7).unwrap()
         );
         assert_eq!(
             payment_dates[5],
-            NaiveDate::from_ymd_opt(2023, 04, 21).unwrap()
+            NaiveDate::from_ymd_opt(2023, 4, 21).unwrap()
         );
         assert_eq!(
             payment_dates[6],
-            NaiveDate::from_ymd_opt(2023, 05, 5).unwrap()
+            NaiveDate::from_ymd_opt(2023, 5, 5).unwrap()
         );
         assert_eq!(
             payment_dates[7],
-            NaiveDate::from_ymd_opt(2023, 05, 19).unwrap()
+            NaiveDate::from_ymd_opt(2023, 5, 19).unwrap()
         );
         assert_eq!(
             payment_dates[8],
-            NaiveDate::from_ymd_opt(2023, 06, 02).unwrap()
+            NaiveDate::from_ymd_opt(2023, 6, 2).unwrap()
         );
-        assert_eq!(payment_dates[9], reccurring_payment_event.end);
+        assert_eq!(payment_dates[9], reccurring_payment_event.end.unwrap());
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn payment_dates__does_not_include_events_after_end() {
         // end chosen to be day before next_payment_event
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2023, 06, 15).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 15).unwrap();
         let reccurring_payment_event = reccurring_payment_event(start, end, Every::Weeks(2));
         let cal_slice = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Jun)).unwrap();
         let payment_dates: Vec<NaiveDate> = reccurring_payment_event.payment_dates(&cal_slice);
@@ -295,91 +381,139 @@ mod payment_composite_spec {
         assert_eq!(payment_dates[0], reccurring_payment_event.start);
         assert_eq!(
             payment_dates[8],
-            NaiveDate::from_ymd_opt(2023, 06, 02).unwrap()
+            NaiveDate::from_ymd_opt(2023, 6, 2).unwrap()
         );
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_dates__stops_after_count() {
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 16).unwrap();
+        let mut reccurring_payment_event = reccurring_payment_event(start, end, Every::Weeks(2));
+        reccurring_payment_event.end = None;
+        reccurring_payment_event.count = Some(3);
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Jun)).unwrap();
+        let payment_events = reccurring_payment_event.payment_events(&cal_slice);
+
+        assert_eq!(payment_events.len(), 3);
+        assert_eq!(
+            payment_events[2].completed_at.date(),
+            NaiveDate::from_ymd_opt(2023, 3, 10).unwrap()
+        );
+        assert!(matches!(
+            payment_events[0].recurrence_state,
+            RecurrenceState::First
+        ));
+        assert!(matches!(
+            payment_events[2].recurrence_state,
+            RecurrenceState::Last
+        ));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_events__open_ended_recurrence_has_no_last_payment() {
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 16).unwrap();
+        let mut reccurring_payment_event = reccurring_payment_event(start, end, Every::Weeks(2));
+        reccurring_payment_event.end = None;
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Jun)).unwrap();
+        let payment_events = reccurring_payment_event.payment_events(&cal_slice);
+
+        // runs to the end of cal_slice
+        assert_eq!(payment_events.len(), 11);
+        assert_eq!(
+            payment_events[10].completed_at.date(),
+            NaiveDate::from_ymd_opt(2023, 6, 30).unwrap()
+        );
+        assert!(matches!(
+            payment_events[10].recurrence_state,
+            RecurrenceState::Active
+        ));
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn next_payment_date__handles_day_recurrence() {
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2023, 06, 10).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 10).unwrap();
         let recurring_payment_event = reccurring_payment_event(start, end, Every::Days(5));
 
         let mut next_payment_date = recurring_payment_event.next_payment_date(start);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 02, 15).unwrap()
+            NaiveDate::from_ymd_opt(2023, 2, 15).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 02, 20).unwrap()
+            NaiveDate::from_ymd_opt(2023, 2, 20).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 02, 25).unwrap()
+            NaiveDate::from_ymd_opt(2023, 2, 25).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 03, 02).unwrap()
+            NaiveDate::from_ymd_opt(2023, 3, 2).unwrap()
         );
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn next_payment_date__handles_week_recurrence() {
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2023, 06, 10).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 10).unwrap();
         let recurring_payment_event = reccurring_payment_event(start, end, Every::Weeks(2));
 
         let mut next_payment_date = recurring_payment_event.next_payment_date(start);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 02, 24).unwrap()
+            NaiveDate::from_ymd_opt(2023, 2, 24).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 03, 10).unwrap()
+            NaiveDate::from_ymd_opt(2023, 3, 10).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 03, 24).unwrap()
+            NaiveDate::from_ymd_opt(2023, 3, 24).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 04, 07).unwrap()
+            NaiveDate::from_ymd_opt(2023, 4, 7).unwrap()
         );
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn next_payment_date__handles_month_recurrence() {
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2024, 02, 10).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
         let recurring_payment_event = reccurring_payment_event(start, end, Every::Months(3));
 
         let mut next_payment_date = recurring_payment_event.next_payment_date(start);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 05, 10).unwrap()
+            NaiveDate::from_ymd_opt(2023, 5, 10).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2023, 08, 10).unwrap()
+            NaiveDate::from_ymd_opt(2023, 8, 10).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
@@ -391,39 +525,167 @@ mod payment_composite_spec {
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2024, 02, 10).unwrap()
+            NaiveDate::from_ymd_opt(2024, 2, 10).unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn next_payment_date__month_recurrence_does_not_drift_from_the_31st() {
+        let start = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let recurring_payment_event = reccurring_payment_event(start, end, Every::Months(1));
+
+        let mut next_payment_date = recurring_payment_event.next_payment_date(start);
+        assert_eq!(
+            next_payment_date,
+            NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()
+        );
+
+        next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
+        assert_eq!(
+            next_payment_date,
+            NaiveDate::from_ymd_opt(2023, 3, 31).unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_dates__handles_semi_monthly_rule() {
+        let start = NaiveDate::from_ymd_opt(2023, 2, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 3, 31).unwrap();
+        let recurring_payment_event = reccurring_payment_event(start, end, Every::SemiMonthly(15));
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Jun)).unwrap();
+
+        assert_eq!(
+            recurring_payment_event.payment_dates(&cal_slice),
+            vec![
+                NaiveDate::from_ymd_opt(2023, 2, 15).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 2, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 3, 15).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 3, 31).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_dates__keeps_last_business_day_off_holidays_without_a_roll() {
+        use crate::calendar::holiday::{Holiday, HolidayCalendar, HolidayStore};
+
+        // 2023-06-30 is a Friday
+        let start = NaiveDate::from_ymd_opt(2023, 5, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 6, 30).unwrap();
+        let recurring_payment_event = reccurring_payment_event(start, end, Every::LastBusinessDay);
+        let mut cal_slice =
+            CalendarSlice::new(YM::new(2023, MK::May), YM::new(2023, MK::Jun)).unwrap();
+        let mut holidays = HolidayStore::new();
+        Holiday::save_to_store(
+            Holiday {
+                id: None,
+                date: NaiveDate::from_ymd_opt(2023, 6, 30).unwrap(),
+                name: "Bank Holiday".to_string(),
+            },
+            &mut holidays,
+        );
+        cal_slice.set_holidays(HolidayCalendar::new(&holidays));
+
+        assert_eq!(Roll::None, recurring_payment_event.roll);
+        assert_eq!(
+            recurring_payment_event.payment_dates(&cal_slice),
+            vec![
+                NaiveDate::from_ymd_opt(2023, 5, 31).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 6, 29).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_dates__filters_to_the_slice_after_roll() {
+        // 2023-07-01 is a Saturday
+        let start = NaiveDate::from_ymd_opt(2023, 5, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+        let mut recurring_payment_event = reccurring_payment_event(start, end, Every::Months(1));
+        recurring_payment_event.roll = Roll::Back;
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::May), YM::new(2023, MK::Jun)).unwrap();
+
+        assert_eq!(
+            recurring_payment_event.payment_dates(&cal_slice),
+            vec![
+                NaiveDate::from_ymd_opt(2023, 5, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 6, 30).unwrap(),
+            ]
+        );
+        let payment_events = recurring_payment_event.payment_events(&cal_slice);
+        assert_eq!(3, payment_events.len());
+        assert!(matches!(
+            payment_events[2].recurrence_state,
+            RecurrenceState::Active
+        ));
+
+        // 2023-09-30 is a Saturday, rolled forward it lands after the slice
+        recurring_payment_event.start = NaiveDate::from_ymd_opt(2023, 8, 30).unwrap();
+        recurring_payment_event.roll = Roll::Forward;
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Aug), YM::new(2023, MK::Sep)).unwrap();
+        assert_eq!(
+            recurring_payment_event.payment_dates(&cal_slice),
+            vec![NaiveDate::from_ymd_opt(2023, 8, 30).unwrap()]
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_dates__rolls_weekend_dates_without_shifting_the_schedule() {
+        // 2023-04-15 is a Saturday, 2023-04-30 a Sunday
+        let start = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2023, 5, 31).unwrap();
+        let mut recurring_payment_event =
+            reccurring_payment_event(start, end, Every::SemiMonthly(15));
+        recurring_payment_event.roll = Roll::Back;
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Apr), YM::new(2023, MK::May)).unwrap();
+
+        assert_eq!(
+            recurring_payment_event.payment_dates(&cal_slice),
+            vec![
+                NaiveDate::from_ymd_opt(2023, 4, 14).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 4, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 5, 15).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 5, 31).unwrap(),
+            ]
         );
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn next_payment_date__handles_year_recurrence() {
-        let start = NaiveDate::from_ymd_opt(2023, 02, 10).unwrap();
-        let end = NaiveDate::from_ymd_opt(2024, 02, 10).unwrap();
+        let start = NaiveDate::from_ymd_opt(2023, 2, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
         let recurring_payment_event = reccurring_payment_event(start, end, Every::Years(3));
 
         let mut next_payment_date = recurring_payment_event.next_payment_date(start);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2026, 02, 10).unwrap()
+            NaiveDate::from_ymd_opt(2026, 2, 10).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2029, 02, 10).unwrap()
+            NaiveDate::from_ymd_opt(2029, 2, 10).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2032, 02, 10).unwrap()
+            NaiveDate::from_ymd_opt(2032, 2, 10).unwrap()
         );
 
         next_payment_date = recurring_payment_event.next_payment_date(next_payment_date);
         assert_eq!(
             next_payment_date,
-            NaiveDate::from_ymd_opt(2035, 02, 10).unwrap()
+            NaiveDate::from_ymd_opt(2035, 2, 10).unwrap()
         );
     }
 }
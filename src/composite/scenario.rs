@@ -0,0 +1,375 @@
+use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::payment_event::{PaymentEvent, PaymentEventBinStore};
+use crate::composite::recurring_payment_event::RecurringPaymentEvent;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::error_handler::ErrorHandler;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+// a what-if overlay on the baseline events, read from a json file
+// removals run first, then modifications, then the added events are binned
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: String, // defaults to the file name without extension
+    #[serde(default)]
+    pub remove: Vec<ScenarioChange>,
+    #[serde(default)]
+    pub modify: Vec<ScenarioChange>,
+    #[serde(default)]
+    pub one_off: Vec<PaymentEvent>,
+    #[serde(default)]
+    pub recurring: Vec<RecurringPaymentEvent>,
+}
+
+// the events named name, optionally narrowed by type, account and an inclusive date range
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct ScenarioChange {
+    pub name: String,
+    #[serde(default)]
+    pub event_type: Option<String>,
+    #[serde(default)]
+    pub account_name: Option<String>, // account, from or to account
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub amount: Option<Decimal>, // modify only, the new amount
+}
+
+// month-end balance of one account in the baseline and in a scenario
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScenarioDiff {
+    pub id: Option<usize>,
+    pub scenario: String,
+    pub year_month: String,
+    pub account_name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub baseline_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub scenario_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub difference: Decimal, // scenario - baseline
+}
+
+pub type ScenarioDiffStore = BTreeMap<usize, ScenarioDiff>;
+
+// account name => month => balance at the end of the month
+// by name since a scenario may create accounts the baseline does not have
+pub type MonthEndBalanceStore = BTreeMap<String, BTreeMap<YM, Decimal>>;
+
+impl CsvRecord<ScenarioDiff> for ScenarioDiff {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> ScenarioDiff {
+        self.clone()
+    }
+}
+
+impl CsvStore<ScenarioDiff> for ScenarioDiff {}
+
+impl Scenario {
+    pub fn fetch(path: &str) -> BudgetResult<Scenario> {
+        let data = fs::read_to_string(path).map_err(|err| BudgetError::json(path, None, err))?;
+        let mut scenario: Scenario = serde_json::from_str(&data)
+            .map_err(|err| BudgetError::json_at(path, None, err, (1, 1)))?;
+        if let Some(index) = scenario.modify.iter().position(|m| m.amount.is_none()) {
+            return Err(BudgetError::json(
+                path,
+                Some(index),
+                format!("modify {:?} has no amount", scenario.modify[index].name),
+            ));
+        }
+        if scenario.name.is_empty() {
+            scenario.name = Path::new(path)
+                .file_stem()
+                .map_or(path.to_string(), |stem| stem.to_string_lossy().to_string());
+        }
+        Ok(scenario)
+    }
+
+    // months up to and including frozen_through are left alone, they already happened
+    pub fn apply(
+        &self,
+        bins: &mut PaymentEventBinStore,
+        cal_slice: &CalendarSlice,
+        frozen_through: Option<YM>,
+    ) {
+        let open = |month: &YM| frozen_through.map_or(true, |through| *month > through);
+
+        for change in self.remove.iter() {
+            let mut removed: usize = 0;
+            for (_month, bin) in bins.iter_mut().filter(|(month, _)| open(month)) {
+                let before = bin.len();
+                bin.retain(|_id, event| !change.matches(event));
+                removed += before - bin.len();
+            }
+            self.warn_unmatched("remove", change, removed);
+        }
+
+        for change in self.modify.iter() {
+            let mut modified: usize = 0;
+            for (_month, bin) in bins.iter_mut().filter(|(month, _)| open(month)) {
+                for event in bin.values_mut().filter(|event| change.matches(event)) {
+                    change.modify(event);
+                    modified += 1;
+                }
+            }
+            self.warn_unmatched("modify", change, modified);
+        }
+
+        self.bin_added_events(bins, cal_slice, frozen_through);
+    }
+
+    // the scenario's one-off and recurring events in the slice, after frozen_through
+    pub fn bin_added_events(
+        &self,
+        bins: &mut PaymentEventBinStore,
+        cal_slice: &CalendarSlice,
+        frozen_through: Option<YM>,
+    ) {
+        let open = |month: &YM| frozen_through.map_or(true, |through| *month > through);
+        let added = self.one_off.iter().map(|event| event.clone_record()).chain(
+            self.recurring
+                .iter()
+                .flat_map(|event| event.payment_events(cal_slice)),
+        );
+        for event in added {
+            let month = YM::new(
+                event.completed_at.year(),
+                Month::key_from_id(event.completed_at.month()),
+            );
+            if cal_slice.start <= month && month <= cal_slice.end && open(&month) {
+                PaymentEvent::save_to_store(event, bins.entry(month).or_default());
+            }
+        }
+    }
+
+    // a change that matches nothing is most likely a misspelled name
+    fn warn_unmatched(&self, action: &str, change: &ScenarioChange, count: usize) {
+        if count == 0 {
+            ErrorHandler::log(From::from(format!(
+                "WARNING: scenario {:?}: {action} {:?} matched no event.",
+                self.name, change.name
+            )));
+        }
+    }
+}
+
+impl ScenarioChange {
+    pub fn matches(&self, event: &PaymentEvent) -> bool {
+        let date = event.completed_at.date();
+        event.name == self.name
+            && self
+                .event_type
+                .as_ref()
+                .map_or(true, |event_type| *event_type == event.event_type)
+            && self.account_name.as_ref().map_or(true, |name| {
+                *name == event.account_name
+                    || Some(name) == event.from_account_name.as_ref()
+                    || Some(name) == event.to_account_name.as_ref()
+            })
+            && self.from.map_or(true, |from| from <= date)
+            && self.until.map_or(true, |until| date <= until)
+    }
+
+    // a ranged amount keeps its spread around the new amount
+    fn modify(&self, event: &mut PaymentEvent) {
+        if let Some(amount) = self.amount {
+            let shift = amount - event.amount;
+            event.amount = amount;
+            event.amount_low = event.amount_low.map(|low| low + shift);
+            event.amount_high = event.amount_high.map(|high| high + shift);
+        }
+    }
+}
+
+impl ScenarioDiff {
+    // one row per account per month, accounts missing from either side count as 0
+    pub fn diff(
+        scenario: &str,
+        baseline: &MonthEndBalanceStore,
+        balances: &MonthEndBalanceStore,
+        diffs: &mut ScenarioDiffStore,
+    ) {
+        let account_names: BTreeSet<&String> = baseline.keys().chain(balances.keys()).collect();
+        for account_name in account_names.into_iter() {
+            let baseline_months = baseline.get(account_name);
+            let scenario_months = balances.get(account_name);
+            let months: BTreeSet<&YM> = baseline_months
+                .into_iter()
+                .chain(scenario_months)
+                .flat_map(|months| months.keys())
+                .collect();
+            for month in months.into_iter() {
+                let balance = |months: Option<&BTreeMap<YM, Decimal>>| {
+                    months
+                        .and_then(|months| months.get(month))
+                        .copied()
+                        .unwrap_or_default()
+                };
+                let baseline_balance = balance(baseline_months);
+                let scenario_balance = balance(scenario_months);
+                ScenarioDiff::save_to_store(
+                    ScenarioDiff {
+                        id: None,
+                        scenario: scenario.to_string(),
+                        year_month: month.display(),
+                        account_name: account_name.clone(),
+                        baseline_balance,
+                        scenario_balance,
+                        difference: scenario_balance - baseline_balance,
+                    },
+                    diffs,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod scenario_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use crate::composite::payment_event::PaymentEventStore;
+
+    fn event(name: &str, month: u32, amount: i64) -> PaymentEvent {
+        PaymentEvent {
+            event_type: "payment".to_string(),
+            name: name.to_string(),
+            account_name: "Big Bank".to_string(),
+            amount: Decimal::new(amount, 0),
+            completed_at: NaiveDate::from_ymd_opt(2023, month, 5)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn bins() -> PaymentEventBinStore {
+        let mut bins = PaymentEventBinStore::new();
+        for month in [3, 4, 5] {
+            let bin: &mut PaymentEventStore = bins
+                .entry(YM::new(2023, Month::key_from_id(month)))
+                .or_default();
+            PaymentEvent::save_to_store(event("Tuition", month, 1500), bin);
+            PaymentEvent::save_to_store(event("Mortgage", month, 2000), bin);
+        }
+        bins
+    }
+
+    fn names_and_amounts(bins: &PaymentEventBinStore, month: MK) -> Vec<(String, Decimal)> {
+        bins[&YM::new(2023, month)]
+            .values()
+            .map(|event| (event.name.clone(), event.amount))
+            .collect()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__removes_modifies_and_adds_events() {
+        let scenario = Scenario {
+            name: "no tuition".to_string(),
+            remove: vec![ScenarioChange {
+                name: "Tuition".to_string(),
+                from: NaiveDate::from_ymd_opt(2023, 4, 1),
+                ..Default::default()
+            }],
+            modify: vec![ScenarioChange {
+                name: "Mortgage".to_string(),
+                until: NaiveDate::from_ymd_opt(2023, 4, 30),
+                amount: Some(Decimal::new(2300, 0)),
+                ..Default::default()
+            }],
+            one_off: vec![event("Car", 5, 400)],
+            recurring: vec![],
+        };
+        let mut bins = bins();
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Mar), YM::new(2023, MK::May)).unwrap();
+        scenario.apply(&mut bins, &cal_slice, None);
+
+        assert_eq!(
+            vec![
+                ("Tuition".to_string(), Decimal::new(1500, 0)),
+                ("Mortgage".to_string(), Decimal::new(2300, 0))
+            ],
+            names_and_amounts(&bins, MK::Mar)
+        );
+        assert_eq!(
+            vec![("Mortgage".to_string(), Decimal::new(2300, 0))],
+            names_and_amounts(&bins, MK::Apr)
+        );
+        assert_eq!(
+            vec![
+                ("Mortgage".to_string(), Decimal::new(2000, 0)),
+                ("Car".to_string(), Decimal::new(400, 0))
+            ],
+            names_and_amounts(&bins, MK::May)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__leaves_frozen_months_alone() {
+        let scenario = Scenario {
+            remove: vec![ScenarioChange {
+                name: "Tuition".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut bins = bins();
+        let cal_slice = CalendarSlice::new(YM::new(2023, MK::Mar), YM::new(2023, MK::May)).unwrap();
+        scenario.apply(&mut bins, &cal_slice, Some(YM::new(2023, MK::Apr)));
+
+        assert_eq!(2, bins[&YM::new(2023, MK::Apr)].len());
+        assert_eq!(1, bins[&YM::new(2023, MK::May)].len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn diff__compares_each_account_month_and_counts_missing_accounts_as_zero() {
+        let mar = YM::new(2023, MK::Mar);
+        let mut baseline = MonthEndBalanceStore::new();
+        baseline
+            .entry("Big Bank".to_string())
+            .or_default()
+            .insert(mar, Decimal::new(1000, 0));
+        let mut balances = MonthEndBalanceStore::new();
+        balances
+            .entry("Big Bank".to_string())
+            .or_default()
+            .insert(mar, Decimal::new(2500, 0));
+        balances
+            .entry("Savings".to_string())
+            .or_default()
+            .insert(mar, Decimal::new(300, 0));
+
+        let mut diffs = ScenarioDiffStore::new();
+        ScenarioDiff::diff("no tuition", &baseline, &balances, &mut diffs);
+        let diffs: Vec<&ScenarioDiff> = diffs.values().collect();
+        assert_eq!(2, diffs.len());
+        assert_eq!("Big Bank", diffs[0].account_name);
+        assert_eq!(Decimal::new(1500, 0), diffs[0].difference);
+        assert_eq!("Savings", diffs[1].account_name);
+        assert_eq!(Decimal::new(0, 0), diffs[1].baseline_balance);
+        assert_eq!(Decimal::new(300, 0), diffs[1].difference);
+    }
+}
@@ -0,0 +1,624 @@
+use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::interest_accrual::InterestAccrualStore;
+use crate::error::budget_error::BudgetResult;
+use crate::schema::account::AccountKind;
+use crate::schema::fx_rate::{FxRate, DEFAULT_CURRENCY};
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, Duration, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// income by Income, expenses by Expense and their net, one block per month
+// interest is a line per account, earned under income and charged under expenses
+// every amount is in the base currency, converted at the rate on the day it was paid
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IncomeStatementLine {
+    pub id: Option<usize>,
+    pub year_month: String,
+    pub section: IncomeStatementSection,
+    pub name: String, // empty on the net line
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IncomeStatementSection {
+    Income,
+    Expense,
+    Net, // income - expenses
+}
+
+// each Account's month-end balance and their net, one block per month
+// balance is in the base currency at the month-end rate, native_balance in the account's
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BalanceSheetLine {
+    pub id: Option<usize>,
+    pub year_month: String,
+    pub section: BalanceSheetSection,
+    pub account_name: String, // empty on the net worth line
+    #[serde(with = "rust_decimal::serde::float")]
+    pub balance: Decimal, // liabilities below 0 are owed
+    pub currency: String,     // the account's, the base currency on the net worth line
+    #[serde(with = "rust_decimal::serde::float")]
+    pub native_balance: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceSheetSection {
+    Asset,
+    Liability,
+    NetWorth, // assets - liabilities
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NetWorth {
+    pub id: Option<usize>,
+    pub year_month: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub assets: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub liabilities: Decimal, // owed, so usually above 0
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net_worth: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub change: Decimal, // since the end of the month before
+    #[serde(with = "rust_decimal::serde::float")]
+    pub cumulative_change: Decimal, // since the slice start
+}
+
+pub type IncomeStatementStore = BTreeMap<usize, IncomeStatementLine>;
+pub type BalanceSheetStore = BTreeMap<usize, BalanceSheetLine>;
+pub type NetWorthStore = BTreeMap<usize, NetWorth>;
+
+impl CsvRecord<IncomeStatementLine> for IncomeStatementLine {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> IncomeStatementLine {
+        self.clone()
+    }
+}
+
+impl CsvStore<IncomeStatementLine> for IncomeStatementLine {}
+
+impl CsvRecord<BalanceSheetLine> for BalanceSheetLine {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> BalanceSheetLine {
+        self.clone()
+    }
+}
+
+impl CsvStore<BalanceSheetLine> for BalanceSheetLine {}
+
+impl CsvRecord<NetWorth> for NetWorth {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> NetWorth {
+        self.clone()
+    }
+}
+
+impl CsvStore<NetWorth> for NetWorth {}
+
+impl IncomeStatementLine {
+    // payments, payments received and interest in the slice, totalled per month
+    // by Income and Expense id, and by account for interest
+    pub fn construct(
+        store: &Store,
+        cal_slice: &CalendarSlice,
+        interest_accruals: &InterestAccrualStore,
+        base_currency: &str,
+    ) -> BudgetResult<IncomeStatementStore> {
+        let in_slice = |completed_at| {
+            let ym = year_month(completed_at);
+            cal_slice.start <= ym && ym <= cal_slice.end
+        };
+        // (month, id) => total
+        let mut incomes: BTreeMap<(YM, usize), Decimal> = BTreeMap::new();
+        for payment_received in store
+            .payments_received
+            .values()
+            .filter(|payment_received| in_slice(payment_received.completed_at))
+        {
+            let amount = FxRate::convert_amount(
+                payment_received.amount_id,
+                payment_received.account_id,
+                payment_received.completed_at.date(),
+                base_currency,
+                store,
+            )?;
+            *incomes
+                .entry((
+                    year_month(payment_received.completed_at),
+                    payment_received.income_id,
+                ))
+                .or_default() += amount;
+        }
+        let mut expenses: BTreeMap<(YM, usize), Decimal> = BTreeMap::new();
+        for payment in store
+            .payments
+            .values()
+            .filter(|payment| in_slice(payment.completed_at))
+        {
+            let amount = FxRate::convert_amount(
+                payment.amount_id,
+                payment.account_id,
+                payment.completed_at.date(),
+                base_currency,
+                store,
+            )?;
+            *expenses
+                .entry((year_month(payment.completed_at), payment.expense_id))
+                .or_default() += amount;
+        }
+
+        // (month, account id) => interest, earned above 0 and charged below
+        let mut interest: BTreeMap<(YM, usize), Decimal> = BTreeMap::new();
+        for accrual in interest_accruals
+            .values()
+            .filter(|accrual| in_slice(accrual.accrued_at))
+        {
+            let currency = store
+                .accounts
+                .get(&accrual.account_id)
+                .map_or(DEFAULT_CURRENCY, |account| account.currency());
+            let amount = FxRate::convert(
+                accrual.interest.unwrap_or_default(),
+                currency,
+                base_currency,
+                accrual.accrued_at.date(),
+                &store.fx_rates,
+            )?;
+            *interest
+                .entry((year_month(accrual.accrued_at), accrual.account_id))
+                .or_default() += amount;
+        }
+        let interest_name = |account_id: &usize| {
+            store
+                .accounts
+                .get(account_id)
+                .map_or(format!("Account Id: {account_id}"), |account| {
+                    account.name.clone()
+                })
+                + " Interest"
+        };
+
+        let mut statement = IncomeStatementStore::new();
+        for month in cal_slice.months().into_iter() {
+            let mut line = |section, name: String, amount| {
+                IncomeStatementLine::save_to_store(
+                    IncomeStatementLine {
+                        id: None,
+                        year_month: month.display(),
+                        section,
+                        name,
+                        amount,
+                    },
+                    &mut statement,
+                );
+            };
+            let mut net = Decimal::new(0, 0);
+            for ((_, income_id), amount) in incomes.range((month, 0)..=(month, usize::MAX)) {
+                let name = store
+                    .incomes
+                    .get(income_id)
+                    .map_or(format!("Income Id: {income_id}"), |income| {
+                        income.name.clone()
+                    });
+                line(IncomeStatementSection::Income, name, *amount);
+                net += amount;
+            }
+            let month_interest = interest.range((month, 0)..=(month, usize::MAX));
+            for ((_, account_id), amount) in month_interest.clone() {
+                if amount > &Decimal::new(0, 0) {
+                    line(
+                        IncomeStatementSection::Income,
+                        interest_name(account_id),
+                        *amount,
+                    );
+                    net += amount;
+                }
+            }
+            for ((_, expense_id), amount) in expenses.range((month, 0)..=(month, usize::MAX)) {
+                let name = store
+                    .expenses
+                    .get(expense_id)
+                    .map_or(format!("Expense Id: {expense_id}"), |expense| {
+                        expense.name.clone()
+                    });
+                line(IncomeStatementSection::Expense, name, *amount);
+                net -= amount;
+            }
+            for ((_, account_id), amount) in month_interest {
+                if amount < &Decimal::new(0, 0) {
+                    line(
+                        IncomeStatementSection::Expense,
+                        interest_name(account_id),
+                        -*amount,
+                    );
+                    net += amount;
+                }
+            }
+            line(IncomeStatementSection::Net, String::new(), net);
+        }
+        Ok(statement)
+    }
+}
+
+impl BalanceSheetLine {
+    pub fn construct(
+        store: &mut Store,
+        cal_slice: &CalendarSlice,
+        base_currency: &str,
+    ) -> BudgetResult<BalanceSheetStore> {
+        let mut balance_sheet = BalanceSheetStore::new();
+        for month in cal_slice.months().into_iter() {
+            let month_end = month.start_of_next_month().and_hms_opt(0, 0, 0).unwrap();
+            let last_day = month_end.date().pred_opt().unwrap();
+            let mut net_worth = Decimal::new(0, 0);
+            // assets first, then liabilities, each in account id order
+            for kind in [AccountKind::Asset, AccountKind::Liability] {
+                for account in store
+                    .accounts
+                    .values()
+                    .filter(|account| account.kind == kind)
+                {
+                    let native_balance = account.balance_at(month_end, &mut store.account_balances);
+                    let balance = FxRate::convert(
+                        native_balance,
+                        account.currency(),
+                        base_currency,
+                        last_day,
+                        &store.fx_rates,
+                    )?;
+                    net_worth += balance;
+                    BalanceSheetLine::save_to_store(
+                        BalanceSheetLine {
+                            id: None,
+                            year_month: month.display(),
+                            section: match kind {
+                                AccountKind::Asset => BalanceSheetSection::Asset,
+                                AccountKind::Liability => BalanceSheetSection::Liability,
+                            },
+                            account_name: account.name.clone(),
+                            balance,
+                            currency: account.currency().to_string(),
+                            native_balance,
+                        },
+                        &mut balance_sheet,
+                    );
+                }
+            }
+            BalanceSheetLine::save_to_store(
+                BalanceSheetLine {
+                    id: None,
+                    year_month: month.display(),
+                    section: BalanceSheetSection::NetWorth,
+                    account_name: String::new(),
+                    balance: net_worth,
+                    currency: base_currency.to_string(),
+                    native_balance: net_worth,
+                },
+                &mut balance_sheet,
+            );
+        }
+        Ok(balance_sheet)
+    }
+}
+
+impl NetWorth {
+    // month by month from the balance sheet, measured against the balances at the slice start
+    // in the balance sheet's base currency
+    pub fn construct(
+        store: &mut Store,
+        cal_slice: &CalendarSlice,
+        balance_sheet: &BalanceSheetStore,
+        base_currency: &str,
+    ) -> BudgetResult<NetWorthStore> {
+        let slice_start = cal_slice
+            .start
+            .start_of_month()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        // balances reported at the start itself, e.g. opening balances, count as the start
+        let mut opening = Decimal::new(0, 0);
+        for account in store.accounts.values() {
+            opening += FxRate::convert(
+                account.balance_at(
+                    slice_start + Duration::seconds(1),
+                    &mut store.account_balances,
+                ),
+                account.currency(),
+                base_currency,
+                slice_start.date(),
+                &store.fx_rates,
+            )?;
+        }
+
+        // year_month => (assets, liabilities owed)
+        let mut totals: BTreeMap<&String, (Decimal, Decimal)> = BTreeMap::new();
+        for line in balance_sheet.values() {
+            let total = totals.entry(&line.year_month).or_default();
+            match line.section {
+                BalanceSheetSection::Asset => total.0 += line.balance,
+                BalanceSheetSection::Liability => total.1 -= line.balance,
+                BalanceSheetSection::NetWorth => (),
+            }
+        }
+
+        let mut net_worth_store = NetWorthStore::new();
+        let mut previous = opening;
+        for (year_month, (assets, liabilities)) in totals.into_iter() {
+            let net_worth = assets - liabilities;
+            NetWorth::save_to_store(
+                NetWorth {
+                    id: None,
+                    year_month: year_month.clone(),
+                    assets,
+                    liabilities,
+                    net_worth,
+                    change: net_worth - previous,
+                    cumulative_change: net_worth - opening,
+                },
+                &mut net_worth_store,
+            );
+            previous = net_worth;
+        }
+        Ok(net_worth_store)
+    }
+}
+
+fn year_month(completed_at: NaiveDateTime) -> YM {
+    YM::new(
+        completed_at.year(),
+        Month::key_from_id(completed_at.month()),
+    )
+}
+
+#[cfg(test)]
+mod statement_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use crate::schema::account::Account;
+    use crate::test::spec::Spec;
+    use chrono::NaiveDate;
+
+    fn cal_slice() -> CalendarSlice {
+        CalendarSlice::new(YM::new(2023, MK::Jan), YM::new(2023, MK::Feb)).unwrap()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct__totals_income_and_expenses_per_month_with_net() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let statement = IncomeStatementLine::construct(
+            &store,
+            &cal_slice(),
+            &InterestAccrualStore::new(),
+            "USD",
+        )
+        .unwrap();
+        let january: Vec<(IncomeStatementSection, Decimal)> = statement
+            .values()
+            .filter(|line| line.year_month == "2023-01")
+            .map(|line| (line.section, line.amount))
+            .collect();
+        assert_eq!(
+            vec![
+                (IncomeStatementSection::Income, Decimal::new(640000, 2)),
+                (IncomeStatementSection::Income, Decimal::new(150000, 2)),
+                (IncomeStatementSection::Expense, Decimal::new(620000, 2)),
+                (IncomeStatementSection::Expense, Decimal::new(25000, 2)),
+                (IncomeStatementSection::Expense, Decimal::new(4500, 2)),
+                (IncomeStatementSection::Net, Decimal::new(140500, 2)),
+            ],
+            january
+        );
+        // a month without activity still nets to 0
+        let february: Vec<&IncomeStatementLine> = statement
+            .values()
+            .filter(|line| line.year_month == "2023-02")
+            .collect();
+        assert_eq!(1, february.len());
+        assert_eq!(Decimal::new(0, 0), february[0].amount);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct__nets_liabilities_against_assets() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store
+            .accounts
+            .update(2, |account| account.kind = AccountKind::Liability);
+        let swearjar = Account::by_name("swearjar", &store.accounts).unwrap();
+        assert!(swearjar.is_liability());
+
+        let balance_sheet = BalanceSheetLine::construct(&mut store, &cal_slice(), "USD").unwrap();
+        let january: Vec<(BalanceSheetSection, Decimal)> = balance_sheet
+            .values()
+            .filter(|line| line.year_month == "2023-01")
+            .map(|line| (line.section, line.balance))
+            .collect();
+        assert_eq!(
+            vec![
+                (BalanceSheetSection::Asset, Decimal::new(20000, 2)),
+                (BalanceSheetSection::Liability, Decimal::new(40000, 2)),
+                (BalanceSheetSection::NetWorth, Decimal::new(60000, 2)),
+            ],
+            january
+        );
+
+        let net_worth =
+            NetWorth::construct(&mut store, &cal_slice(), &balance_sheet, "USD").unwrap();
+        let net_worth: Vec<&NetWorth> = net_worth.values().collect();
+        assert_eq!(2, net_worth.len());
+        assert_eq!(Decimal::new(-40000, 2), net_worth[0].liabilities);
+        assert_eq!(Decimal::new(60000, 2), net_worth[0].change);
+        assert_eq!(Decimal::new(0, 0), net_worth[1].change);
+        assert_eq!(Decimal::new(60000, 2), net_worth[1].cumulative_change);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct__converts_balances_to_the_base_currency_at_the_month_end_rate() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store
+            .accounts
+            .update(2, |account| account.currency = Some("EUR".to_string()));
+        for (day, rate) in [(2, 110), (31, 120)] {
+            FxRate::save_to_store(
+                FxRate {
+                    id: None,
+                    date: NaiveDate::from_ymd_opt(2023, 1, day).unwrap(),
+                    pair: "EUR/USD".to_string(),
+                    rate: Decimal::new(rate, 2),
+                },
+                &mut store.fx_rates,
+            );
+        }
+
+        let balance_sheet = BalanceSheetLine::construct(&mut store, &cal_slice(), "USD").unwrap();
+        let swearjar = balance_sheet
+            .values()
+            .find(|line| line.year_month == "2023-01" && line.account_name == "swearjar")
+            .unwrap();
+        assert_eq!("EUR", swearjar.currency);
+        assert_eq!(Decimal::new(40000, 2), swearjar.native_balance);
+        assert_eq!(Decimal::new(48000, 2), swearjar.balance);
+        let net_worth = balance_sheet
+            .values()
+            .find(|line| {
+                line.year_month == "2023-01" && line.section == BalanceSheetSection::NetWorth
+            })
+            .unwrap();
+        assert_eq!(Decimal::new(68000, 2), net_worth.balance);
+
+        // without a EUR/GBP rate there is no GBP balance sheet
+        assert!(BalanceSheetLine::construct(&mut store, &cal_slice(), "GBP").is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct__converts_only_payments_in_the_slice() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        // swearjar's January payments have no EUR/USD rate
+        store
+            .accounts
+            .update(2, |account| account.currency = Some("EUR".to_string()));
+
+        let jan = CalendarSlice::new(YM::new(2023, MK::Jan), YM::new(2023, MK::Jan)).unwrap();
+        assert!(
+            IncomeStatementLine::construct(&store, &jan, &InterestAccrualStore::new(), "USD")
+                .is_err()
+        );
+        let feb = CalendarSlice::new(YM::new(2023, MK::Feb), YM::new(2023, MK::Feb)).unwrap();
+        let statement =
+            IncomeStatementLine::construct(&store, &feb, &InterestAccrualStore::new(), "USD")
+                .unwrap();
+        assert!(statement
+            .values()
+            .all(|line| line.section == IncomeStatementSection::Net));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct__net_income_reconciles_with_the_change_in_net_worth_when_interest_posts() {
+        use crate::composite::interest_accrual::InterestAccrual;
+        use crate::schema::account_balance::AccountBalance;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        // swearjar owes 1200 going into February, nothing is paid in February
+        AccountBalance::save_to_store(
+            AccountBalance {
+                id: None,
+                account_id: 2,
+                reported_at: NaiveDate::from_ymd_opt(2023, 1, 31)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+                amount: Decimal::new(-1200, 0),
+            },
+            &mut store.account_balances,
+        );
+        let accrued_at = NaiveDate::from_ymd_opt(2023, 2, 28)
+            .unwrap()
+            .and_hms_opt(23, 59, 59)
+            .unwrap();
+        let mut interest_accruals = InterestAccrualStore::new();
+        for (id, rate) in [(1, 6), (2, 12)] {
+            let mut account = Account::by_id(id, &mut store.accounts).unwrap();
+            account.interest_rate = Some(Decimal::new(rate, 2));
+            let balance = account.current_balance(&mut store.account_balances);
+            let mut accrual = InterestAccrual::new(&account, 12, accrued_at);
+            let accrued = InterestAccrual::period_interest(&account, balance, 12);
+            accrual.create_accrual(&mut store, accrued).unwrap();
+            InterestAccrual::save_to_store(accrual, &mut interest_accruals);
+        }
+
+        let statement =
+            IncomeStatementLine::construct(&store, &cal_slice(), &interest_accruals, "USD")
+                .unwrap();
+        let february: Vec<(IncomeStatementSection, &str, Decimal)> = statement
+            .values()
+            .filter(|line| line.year_month == "2023-02")
+            .map(|line| (line.section, line.name.as_str(), line.amount))
+            .collect();
+        assert_eq!(
+            vec![
+                // 200 * 0.06 / 12
+                (
+                    IncomeStatementSection::Income,
+                    "piggybank Interest",
+                    Decimal::new(1, 0)
+                ),
+                // 1200 * 0.12 / 12
+                (
+                    IncomeStatementSection::Expense,
+                    "swearjar Interest",
+                    Decimal::new(12, 0)
+                ),
+                (IncomeStatementSection::Net, "", Decimal::new(-11, 0)),
+            ],
+            february
+        );
+
+        let balance_sheet = BalanceSheetLine::construct(&mut store, &cal_slice(), "USD").unwrap();
+        let net_worth =
+            NetWorth::construct(&mut store, &cal_slice(), &balance_sheet, "USD").unwrap();
+        assert_eq!(february[2].2, net_worth[&2].change);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::composite::payment_event::{PaymentEvent, PaymentEventBinStore, RecurrenceState};
+use crate::schema::account::{Account, AccountStore};
+use crate::traits::csv_store::CsvStore;
+use rust_decimal::prelude::*;
+
+// generates one "statement_payment" event per month for each liability account
+// that has a payment_due_day and a funding_account_id
+// the amount is left at 0 and set from the closed statement when the event is executed
+pub struct StatementPaymentEvent;
+
+impl StatementPaymentEvent {
+    pub fn bin_statement_payment_events(
+        accounts: &AccountStore,
+        cal_slice: &CalendarSlice,
+        bin_store: &mut PaymentEventBinStore,
+    ) {
+        for (_id, account) in accounts.iter() {
+            for (ym, payment_event) in
+                StatementPaymentEvent::payment_events(account, accounts, cal_slice)
+            {
+                let store = bin_store.entry(ym).or_default();
+                PaymentEvent::save_to_store(payment_event, store);
+            }
+        }
+    }
+
+    pub fn payment_events(
+        account: &Account,
+        accounts: &AccountStore,
+        cal_slice: &CalendarSlice,
+    ) -> Vec<(YM, PaymentEvent)> {
+        let mut payment_events = vec![];
+        if !account.is_liability() {
+            return payment_events;
+        }
+        let (due_day, funding_id) = match (account.payment_due_day, account.funding_account_id) {
+            (Some(due_day), Some(funding_id)) => (due_day, funding_id),
+            _ => return payment_events,
+        };
+        let funding_account = match accounts.get(&funding_id) {
+            Some(funding_account) => funding_account,
+            None => return payment_events,
+        };
+
+        for ym in cal_slice.months().into_iter() {
+            let due_date = Account::clamped_date(ym.year, Month::id(ym.month), due_day);
+            payment_events.push((
+                ym,
+                PaymentEvent {
+                    id: None,
+                    event_type: "statement_payment".to_string(),
+                    name: format!("{} Statement Payment", account.name),
+                    account_name: "".to_string(),
+                    from_account_name: Some(funding_account.name.clone()),
+                    to_account_name: Some(account.name.clone()),
+                    amount: Decimal::new(0, 0),
+                    amount_low: None,
+                    amount_high: None,
+                    currency: None, // to_account's, see TransferComposite
+                    tags: vec![],
+                    completed_at: due_date.and_hms_opt(12, 0, 0).unwrap(),
+                    recurrence_state: RecurrenceState::None,
+                    until_balance_zero: false,
+                },
+            ));
+        }
+        payment_events
+    }
+}
+
+#[cfg(test)]
+mod statement_payment_event_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use crate::schema::account::AccountKind;
+    use chrono::NaiveDate;
+
+    fn accounts() -> AccountStore {
+        let mut accounts = AccountStore::new();
+        Account::save_to_store(
+            Account {
+                id: None,
+                name: "Checking".to_string(),
+                ..Default::default()
+            },
+            &mut accounts,
+        );
+        Account::save_to_store(
+            Account {
+                id: None,
+                name: "Card".to_string(),
+                kind: AccountKind::Liability,
+                credit_limit: Some(Decimal::new(5000, 0)),
+                statement_closing_day: Some(25),
+                payment_due_day: Some(31),
+                funding_account_id: Some(1),
+                ..Default::default()
+            },
+            &mut accounts,
+        );
+        accounts
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_events__returns_one_event_per_month_on_due_day() {
+        let accounts = accounts();
+        let cal_slice = CalendarSlice::new(YM::new(2024, MK::Jan), YM::new(2024, MK::Mar)).unwrap();
+        let payment_events =
+            StatementPaymentEvent::payment_events(&accounts[&2], &accounts, &cal_slice);
+
+        assert_eq!(3, payment_events.len());
+        let (ym, feb_event) = &payment_events[1];
+        assert_eq!(YM::new(2024, MK::Feb), *ym);
+        assert_eq!("statement_payment", feb_event.event_type);
+        assert_eq!(Some("Checking".to_string()), feb_event.from_account_name);
+        assert_eq!(Some("Card".to_string()), feb_event.to_account_name);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            feb_event.completed_at.date()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn payment_events__returns_no_events_for_asset_accounts() {
+        let accounts = accounts();
+        let cal_slice = CalendarSlice::new(YM::new(2024, MK::Jan), YM::new(2024, MK::Mar)).unwrap();
+        assert!(
+            StatementPaymentEvent::payment_events(&accounts[&1], &accounts, &cal_slice).is_empty()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn bin_statement_payment_events__adds_events_to_month_bins() {
+        let accounts = accounts();
+        let cal_slice = CalendarSlice::new(YM::new(2024, MK::Jan), YM::new(2024, MK::Mar)).unwrap();
+        let mut bin_store = PaymentEventBinStore::new();
+        StatementPaymentEvent::bin_statement_payment_events(&accounts, &cal_slice, &mut bin_store);
+
+        assert_eq!(3, bin_store.len());
+        assert_eq!(1, bin_store[&YM::new(2024, MK::Mar)].len());
+    }
+}
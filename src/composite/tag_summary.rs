@@ -0,0 +1,35 @@
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// totals across every Expense and Income carrying a Tag
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct TagSummary {
+    pub id: Option<usize>,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub expense_total: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub income_total: Decimal,
+}
+
+pub type TagSummaryStore = BTreeMap<usize, TagSummary>;
+
+impl CsvRecord<TagSummary> for TagSummary {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> TagSummary {
+        self.clone()
+    }
+}
+
+impl CsvStore<TagSummary> for TagSummary {}
@@ -0,0 +1,520 @@
+use crate::composite::payment_display::PaymentDisplay;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::error_handler::ErrorHandler;
+use crate::schema::account::Account;
+use crate::schema::account_balance::AccountBalance;
+use crate::schema::fx_rate::FxRate;
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::{NaiveDateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::payment_event::RecurrenceState;
+
+// move money between two accounts without recording an Expense or Income
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct TransferComposite {
+    pub id: Option<usize>,
+    pub name: String,
+    pub from_account_id: Option<usize>,
+    pub from_account_name: String,
+    pub from_account_balance_id: Option<usize>, // id of account_balance debiting from_account
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub from_prev_balance: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub from_ending_balance: Option<Decimal>,
+    pub to_account_id: Option<usize>,
+    pub to_account_name: String,
+    pub to_account_balance_id: Option<usize>, // id of account_balance crediting to_account
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub to_prev_balance: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub to_ending_balance: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount_standard: Decimal,
+    #[serde(default)]
+    pub currency: Option<String>, // amount_standard's, None => from_account's
+    pub transfer_completed_at: NaiveDateTime,
+    pub recurrence_state: RecurrenceState,
+    // amount_standard is set from to_account's closed statement when executed
+    #[serde(default)]
+    pub statement_payment: bool,
+    // amount_standard is capped at what to_account owes when executed
+    #[serde(default)]
+    pub until_balance_zero: bool,
+}
+
+impl CsvRecord<TransferComposite> for TransferComposite {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> TransferComposite {
+        self.clone()
+    }
+}
+
+impl CsvStore<TransferComposite> for TransferComposite {}
+
+pub type TransferCompositeStore = BTreeMap<usize, TransferComposite>;
+
+type CreateTransferResult = Result<(), BudgetError>;
+
+impl TransferComposite {
+    pub fn display(&self) -> PaymentDisplay {
+        PaymentDisplay {
+            id: self.id,
+            name: self.name.clone(),
+            amount: self.amount_standard,
+            account_name: format!("{} -> {}", self.from_account_name, self.to_account_name),
+            completed_at: self.transfer_completed_at,
+            prev_balance: self.from_prev_balance,
+            ending_balance: self.from_ending_balance,
+        }
+    }
+
+    pub fn create_transfer(
+        &mut self,
+        store: &mut Store,
+        complete_at: Option<NaiveDateTime>,
+    ) -> CreateTransferResult {
+        if let Some(id) = self.from_account_balance_id {
+            ErrorHandler::log(From::from(format!(
+                "Transfer already executed, see AccountBalance {id}."
+            )))
+        }
+
+        if self.from_account_name == self.to_account_name {
+            return Err(From::from(format!(
+                "Transfer {:?} must move money between two different accounts, found {:?} twice.",
+                self.name, self.from_account_name
+            )));
+        }
+
+        if self.from_account_id.is_none() {
+            self.from_account_id = Some(TransferComposite::account_id_by_name(
+                &self.from_account_name,
+                store,
+            ));
+        }
+
+        if self.to_account_id.is_none() {
+            self.to_account_id = Some(TransferComposite::account_id_by_name(
+                &self.to_account_name,
+                store,
+            ));
+        }
+
+        self.transfer_completed_at = match complete_at {
+            None => Utc::now().naive_local(),
+            Some(ndt) => ndt,
+        };
+
+        // statement payments are in to_account's currency
+        let currency = match self.statement_payment {
+            true => self.account_currency(self.to_account_id, store),
+            false => match &self.currency {
+                Some(currency) => currency.clone(),
+                None => self.account_currency(self.from_account_id, store),
+            },
+        };
+        self.currency = Some(currency.clone());
+
+        if self.statement_payment {
+            self.amount_standard = self.statement_balance(store);
+            if self.amount_standard <= Decimal::new(0, 0) {
+                return Ok(()); // nothing owed
+            }
+        }
+
+        if self.until_balance_zero {
+            let to_account =
+                Account::by_id(self.to_account_id.unwrap(), &mut store.accounts).unwrap();
+            let owed = -to_account.current_balance(&mut store.account_balances);
+            let owed = FxRate::convert(
+                owed.max(Decimal::new(0, 0)),
+                to_account.currency(),
+                &currency,
+                self.transfer_completed_at.date(),
+                &store.fx_rates,
+            )?;
+            // this payment, or one before it, pays to_account off and ends the recurrence
+            if self.amount_standard >= owed {
+                self.recurrence_state = RecurrenceState::Last;
+            }
+            self.amount_standard = self.amount_standard.min(owed);
+            if self.amount_standard.is_zero() {
+                return Ok(()); // paid off
+            }
+        }
+
+        // each side moves in its own currency, at the rate on the day
+        let from_amount = self.account_amount(self.from_account_id, &currency, store)?;
+        let to_amount = self.account_amount(self.to_account_id, &currency, store)?;
+
+        // read both balances before writing either so that the pair is recorded together
+        let from_prev_balance = Account::by_id(self.from_account_id.unwrap(), &mut store.accounts)
+            .unwrap()
+            .current_balance(&mut store.account_balances);
+        let to_prev_balance = Account::by_id(self.to_account_id.unwrap(), &mut store.accounts)
+            .unwrap()
+            .current_balance(&mut store.account_balances);
+
+        self.from_prev_balance = Some(from_prev_balance);
+        self.to_prev_balance = Some(to_prev_balance);
+        self.from_ending_balance = Some(from_prev_balance - from_amount);
+        self.to_ending_balance = Some(to_prev_balance + to_amount);
+
+        self.from_account_balance_id = Some(AccountBalance::save_to_store(
+            AccountBalance {
+                id: None,
+                account_id: self.from_account_id.unwrap(),
+                amount: self.from_ending_balance.unwrap(),
+                reported_at: self.transfer_completed_at,
+            },
+            &mut store.account_balances,
+        ));
+        self.to_account_balance_id = Some(AccountBalance::save_to_store(
+            AccountBalance {
+                id: None,
+                account_id: self.to_account_id.unwrap(),
+                amount: self.to_ending_balance.unwrap(),
+                reported_at: self.transfer_completed_at,
+            },
+            &mut store.account_balances,
+        ));
+
+        Ok(())
+    }
+
+    // amount owed on to_account as of its last statement closing before this transfer
+    fn statement_balance(&self, store: &mut Store) -> Decimal {
+        let card = Account::by_id(self.to_account_id.unwrap(), &mut store.accounts).unwrap();
+        match card.last_statement_close(self.transfer_completed_at.date()) {
+            None => Decimal::new(0, 0),
+            Some(closed_on) => {
+                let closed_at = closed_on.succ_opt().unwrap().and_hms_opt(0, 0, 0).unwrap();
+                -card.balance_at(closed_at, &mut store.account_balances)
+            }
+        }
+    }
+
+    fn account_currency(&self, account_id: Option<usize>, store: &mut Store) -> String {
+        Account::by_id(account_id.unwrap(), &mut store.accounts)
+            .unwrap()
+            .currency()
+            .to_string()
+    }
+
+    // amount_standard, in currency, as the account's currency
+    fn account_amount(
+        &self,
+        account_id: Option<usize>,
+        currency: &str,
+        store: &mut Store,
+    ) -> BudgetResult<Decimal> {
+        let account = Account::by_id(account_id.unwrap(), &mut store.accounts).unwrap();
+        FxRate::convert(
+            self.amount_standard,
+            currency,
+            account.currency(),
+            self.transfer_completed_at.date(),
+            &store.fx_rates,
+        )
+    }
+
+    fn account_id_by_name(name: &str, store: &mut Store) -> usize {
+        match Account::by_name(name, &store.accounts) {
+            // create Account record
+            None => Account::save_to_store(
+                Account {
+                    id: None,
+                    name: name.to_string(),
+                    ..Default::default()
+                },
+                &mut store.accounts,
+            ),
+            Some(acc) => acc.id.unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod transfer_composite_spec {
+    use super::*;
+    use crate::schema::account::AccountKind;
+    use crate::test::spec::Spec;
+    use chrono::NaiveDate;
+
+    fn transfer_comp() -> TransferComposite {
+        TransferComposite {
+            id: None,
+            name: "savings sweep".to_string(),
+            from_account_id: None,
+            from_account_name: "piggybank".to_string(),
+            from_account_balance_id: None,
+            from_prev_balance: None,
+            from_ending_balance: None,
+            to_account_id: None,
+            to_account_name: "swearjar".to_string(),
+            to_account_balance_id: None,
+            to_prev_balance: None,
+            to_ending_balance: None,
+            amount_standard: Decimal::new(5000, 2),
+            currency: None,
+            transfer_completed_at: NaiveDate::from_ymd_opt(2023, 2, 17)
+                .unwrap()
+                .and_hms_opt(13, 00, 00)
+                .unwrap(),
+            recurrence_state: RecurrenceState::None,
+            statement_payment: false,
+            until_balance_zero: false,
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__retrieves_account_ids_by_name() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut transfer_comp = transfer_comp();
+        transfer_comp.create_transfer(&mut store, None).unwrap();
+        assert_eq!(1, transfer_comp.from_account_id.unwrap());
+        assert_eq!(2, transfer_comp.to_account_id.unwrap());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__debits_from_account_and_credits_to_account() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut transfer_comp = transfer_comp();
+        transfer_comp.create_transfer(&mut store, None).unwrap();
+        assert_eq!(
+            Decimal::new(200, 0),
+            transfer_comp.from_prev_balance.unwrap()
+        );
+        assert_eq!(
+            Decimal::new(150, 0),
+            transfer_comp.from_ending_balance.unwrap()
+        );
+        assert_eq!(Decimal::new(400, 0), transfer_comp.to_prev_balance.unwrap());
+        assert_eq!(
+            Decimal::new(450, 0),
+            transfer_comp.to_ending_balance.unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__creates_paired_account_balance_records() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut transfer_comp = transfer_comp();
+        assert_eq!(4, store.account_balances.len());
+        transfer_comp.create_transfer(&mut store, None).unwrap();
+        assert_eq!(6, store.account_balances.len());
+
+        let from_bal = store.account_balances[&5];
+        let to_bal = store.account_balances[&6];
+        assert_eq!(1, from_bal.account_id);
+        assert_eq!(Decimal::new(150, 0), from_bal.amount);
+        assert_eq!(2, to_bal.account_id);
+        assert_eq!(Decimal::new(450, 0), to_bal.amount);
+        assert_eq!(from_bal.reported_at, to_bal.reported_at);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__does_not_create_payment_expense_or_income_records() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut transfer_comp = transfer_comp();
+        transfer_comp.create_transfer(&mut store, None).unwrap();
+        assert_eq!(4, store.payments.len());
+        assert_eq!(3, store.payments_received.len());
+        assert_eq!(3, store.expenses.len());
+        assert_eq!(2, store.incomes.len());
+        assert_eq!(5, store.amounts.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__returns_err_when_from_and_to_account_are_the_same() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut transfer_comp = transfer_comp();
+        transfer_comp.to_account_name = "piggybank".to_string();
+        assert!(transfer_comp.create_transfer(&mut store, None).is_err());
+        assert_eq!(4, store.account_balances.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__credits_to_account_in_its_own_currency() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store
+            .accounts
+            .update(2, |account| account.currency = Some("EUR".to_string()));
+        FxRate::save_to_store(
+            FxRate {
+                id: None,
+                date: NaiveDate::from_ymd_opt(2023, 2, 1).unwrap(),
+                pair: "EUR/USD".to_string(),
+                rate: Decimal::new(110, 2),
+            },
+            &mut store.fx_rates,
+        );
+
+        let mut transfer_comp = transfer_comp();
+        let at = transfer_comp.transfer_completed_at;
+        transfer_comp.create_transfer(&mut store, Some(at)).unwrap();
+
+        // 50 USD out of piggybank, 50 / 1.10 EUR into swearjar
+        assert_eq!(Some("USD".to_string()), transfer_comp.currency);
+        assert_eq!(
+            Decimal::new(150, 0),
+            transfer_comp.from_ending_balance.unwrap()
+        );
+        assert_eq!(
+            Decimal::new(44545, 2),
+            transfer_comp.to_ending_balance.unwrap()
+        );
+    }
+
+    fn card_store() -> Store {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        Account::save_to_store(
+            Account {
+                id: None,
+                name: "Card".to_string(),
+                kind: AccountKind::Liability,
+                statement_closing_day: Some(25),
+                payment_due_day: Some(20),
+                funding_account_id: Some(1),
+                ..Default::default()
+            },
+            &mut store.accounts,
+        );
+        for (day, amount) in [(10, -100), (24, -250), (27, -400)] {
+            AccountBalance::save_to_store(
+                AccountBalance {
+                    id: None,
+                    account_id: 3,
+                    reported_at: NaiveDate::from_ymd_opt(2023, 2, day)
+                        .unwrap()
+                        .and_hms_opt(12, 0, 0)
+                        .unwrap(),
+                    amount: Decimal::new(amount, 0),
+                },
+                &mut store.account_balances,
+            );
+        }
+        store
+    }
+
+    fn statement_payment_comp() -> TransferComposite {
+        let mut transfer_comp = transfer_comp();
+        transfer_comp.name = "Card Statement Payment".to_string();
+        transfer_comp.to_account_name = "Card".to_string();
+        transfer_comp.amount_standard = Decimal::new(0, 0);
+        transfer_comp.statement_payment = true;
+        transfer_comp
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__pays_closed_statement_balance_when_statement_payment() {
+        let mut store = card_store();
+
+        let mut transfer_comp = statement_payment_comp();
+        let due = NaiveDate::from_ymd_opt(2023, 3, 20)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        transfer_comp
+            .create_transfer(&mut store, Some(due))
+            .unwrap();
+
+        // charges after the 2/25 close roll into the next statement
+        assert_eq!(Decimal::new(250, 0), transfer_comp.amount_standard);
+        assert_eq!(
+            Decimal::new(-50, 0),
+            transfer_comp.from_ending_balance.unwrap()
+        );
+        assert_eq!(
+            Decimal::new(-150, 0),
+            transfer_comp.to_ending_balance.unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__skips_statement_payment_when_nothing_is_owed() {
+        let mut store = card_store();
+
+        let mut transfer_comp = statement_payment_comp();
+        let due = NaiveDate::from_ymd_opt(2023, 2, 20)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let balance_count = store.account_balances.len();
+        transfer_comp
+            .create_transfer(&mut store, Some(due))
+            .unwrap();
+
+        assert_eq!(balance_count, store.account_balances.len());
+        assert_eq!(None, transfer_comp.from_ending_balance);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn create_transfer__caps_amount_at_balance_owed_when_until_balance_zero() {
+        let mut store = card_store();
+
+        let mut transfer_comp = transfer_comp();
+        transfer_comp.to_account_name = "Card".to_string();
+        transfer_comp.amount_standard = Decimal::new(1000, 0);
+        transfer_comp.until_balance_zero = true;
+        let paid_at = NaiveDate::from_ymd_opt(2023, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        transfer_comp
+            .create_transfer(&mut store, Some(paid_at))
+            .unwrap();
+
+        assert_eq!(Decimal::new(400, 0), transfer_comp.amount_standard);
+        assert_eq!(Decimal::new(0, 0), transfer_comp.to_ending_balance.unwrap());
+        assert!(matches!(
+            transfer_comp.recurrence_state,
+            RecurrenceState::Last
+        ));
+
+        // once paid off, later occurrences move nothing
+        let mut next_comp = transfer_comp.clone_record();
+        next_comp.from_account_balance_id = None;
+        next_comp.amount_standard = Decimal::new(1000, 0);
+        let balance_count = store.account_balances.len();
+        next_comp
+            .create_transfer(&mut store, Some(paid_at))
+            .unwrap();
+        assert_eq!(balance_count, store.account_balances.len());
+        assert!(next_comp.amount_standard.is_zero());
+    }
+}
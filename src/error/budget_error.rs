@@ -0,0 +1,330 @@
+use chrono::NaiveDate;
+use csv::StringRecord;
+use serde::Serialize;
+use std::fmt;
+
+// serialized with its kind as a structured log entry
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BudgetError {
+    // row is None when the file could not be opened or written
+    // column is None when csv cannot attribute the error, e.g. decimal and date fields
+    Csv {
+        file: String,
+        row: Option<u64>,
+        column: Option<String>,
+        message: String,
+    },
+    // index of the event within the file, None when the file itself is unreadable
+    // line and column within the file, when serde_json could place the error
+    Json {
+        file: String,
+        index: Option<usize>,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    // line of the <STMTTRN> the error is in
+    Ofx {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    UnknownAccount {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        did_you_mean: Option<String>,
+    },
+    UnknownExpense {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        did_you_mean: Option<String>,
+    },
+    UnknownIncome {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        did_you_mean: Option<String>,
+    },
+    Calendar {
+        message: String,
+    },
+    // --strict found names that would have been created
+    Strict {
+        unknown: usize,
+    },
+    // simulate --fail-on-overdraft found alerts
+    Overdraft {
+        alerts: usize,
+    },
+    // no rate in fx_rates.csv converts between the two currencies by date
+    MissingFxRate {
+        from: String,
+        to: String,
+        date: NaiveDate,
+    },
+    Io {
+        message: String,
+    },
+    Sqlite {
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+pub type BudgetResult<T> = Result<T, BudgetError>;
+
+impl BudgetError {
+    pub fn csv(file: &str, headers: Option<&StringRecord>, err: csv::Error) -> BudgetError {
+        let row = err.position().map(|position| position.line());
+        let (column, message) = match err.kind() {
+            csv::ErrorKind::Deserialize { err, .. } => (
+                err.field()
+                    .and_then(|field| headers.and_then(|headers| headers.get(field as usize)))
+                    .map(String::from),
+                err.kind().to_string(),
+            ),
+            _ => (None, err.to_string()),
+        };
+        BudgetError::Csv {
+            file: file.to_string(),
+            row,
+            column,
+            message,
+        }
+    }
+
+    pub fn json(file: &str, index: Option<usize>, err: impl fmt::Display) -> BudgetError {
+        BudgetError::Json {
+            file: file.to_string(),
+            index,
+            line: None,
+            column: None,
+            message: err.to_string(),
+        }
+    }
+
+    // start is the (line, column) in file where the text serde_json parsed begins
+    pub fn json_at(
+        file: &str,
+        index: Option<usize>,
+        err: serde_json::Error,
+        start: (usize, usize),
+    ) -> BudgetError {
+        if err.line() == 0 {
+            return BudgetError::json(file, index, err);
+        }
+        let (line, column) = match err.line() {
+            1 => (start.0, start.1 + err.column() - 1),
+            line => (start.0 + line - 1, err.column()),
+        };
+        // serde_json appends the position relative to the text it was given
+        let message = err.to_string();
+        let suffix = format!(" at line {} column {}", err.line(), err.column());
+        BudgetError::Json {
+            file: file.to_string(),
+            index,
+            line: Some(line),
+            column: Some(column),
+            message: message
+                .strip_suffix(&suffix)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetError::Csv {
+                file,
+                row,
+                column,
+                message,
+            } => {
+                write!(f, "{file}")?;
+                if let Some(row) = row {
+                    write!(f, " row {row}")?;
+                }
+                if let Some(column) = column {
+                    write!(f, " column {column:?}")?;
+                }
+                write!(f, ": {message}")
+            }
+            BudgetError::Json {
+                file,
+                index,
+                line,
+                column,
+                message,
+            } => {
+                write!(f, "{file}")?;
+                if let Some(index) = index {
+                    write!(f, " event {index}")?;
+                }
+                if let (Some(line), Some(column)) = (line, column) {
+                    write!(f, " line {line} column {column}")?;
+                }
+                write!(f, ": {message}")
+            }
+            BudgetError::Ofx {
+                file,
+                line,
+                message,
+            } => write!(f, "{file} line {line}: {message}"),
+            BudgetError::UnknownAccount { name, did_you_mean } => {
+                write!(f, "Unknown account {name:?}")?;
+                write_suggestion(f, did_you_mean)
+            }
+            BudgetError::UnknownExpense { name, did_you_mean } => {
+                write!(f, "Unknown expense {name:?}")?;
+                write_suggestion(f, did_you_mean)
+            }
+            BudgetError::UnknownIncome { name, did_you_mean } => {
+                write!(f, "Unknown income {name:?}")?;
+                write_suggestion(f, did_you_mean)
+            }
+            BudgetError::Strict { unknown } => write!(
+                f,
+                "{unknown} unknown name(s), strict mode does not create accounts, expenses or incomes."
+            ),
+            BudgetError::Overdraft { alerts } => {
+                write!(f, "{alerts} overdraft or over credit limit alert(s), see alerts.csv.")
+            }
+            BudgetError::MissingFxRate { from, to, date } => write!(
+                f,
+                "No fx rate from {from} to {to} on or before {date}, add one to fx_rates.csv."
+            ),
+            BudgetError::Calendar { message }
+            | BudgetError::Io { message }
+            | BudgetError::Sqlite { message }
+            | BudgetError::Other { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+fn write_suggestion(f: &mut fmt::Formatter<'_>, did_you_mean: &Option<String>) -> fmt::Result {
+    match did_you_mean {
+        Some(suggestion) => write!(f, ", did you mean {suggestion:?}?"),
+        None => write!(f, "."),
+    }
+}
+
+impl std::error::Error for BudgetError {}
+
+impl From<String> for BudgetError {
+    fn from(message: String) -> BudgetError {
+        BudgetError::Other { message }
+    }
+}
+
+impl From<&str> for BudgetError {
+    fn from(message: &str) -> BudgetError {
+        BudgetError::Other {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for BudgetError {
+    fn from(err: std::io::Error) -> BudgetError {
+        BudgetError::Io {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for BudgetError {
+    fn from(err: rusqlite::Error) -> BudgetError {
+        BudgetError::Sqlite {
+            message: err.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod budget_error_spec {
+    use super::*;
+    use crate::composite::payment_event::PaymentEvent;
+    use crate::schema::payment::{Payment, PaymentStore};
+    use crate::traits::csv_store::CsvStore;
+    use std::fs;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn csv__reports_file_row_and_column() {
+        let path = format!(
+            "{}/monthly_budget_bad_payments.csv",
+            std::env::temp_dir().display()
+        );
+        fs::write(
+            &path,
+            "id,account_id,amount_id,expense_id,completed_at\n\
+             1,1,1,1,2023-03-01T12:00:00\n\
+             2,1,oops,1,2023-03-02T12:00:00\n",
+        )
+        .unwrap();
+
+        let mut store = PaymentStore::new();
+        match Payment::init_store(&mut store, &path) {
+            Err(BudgetError::Csv {
+                file, row, column, ..
+            }) => {
+                assert_eq!(path, file);
+                assert_eq!(Some(3), row);
+                assert_eq!(Some("amount_id".to_string()), column);
+            }
+            res => panic!("expected csv error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn json__reports_file_and_event_index() {
+        let path = format!(
+            "{}/monthly_budget_bad_events.json",
+            std::env::temp_dir().display()
+        );
+        fs::write(
+            &path,
+            r#"[
+                { "event_type": "payment", "name": "Rent", "account_name": "Bank", "amount": 10.0,
+                  "completed_at": "2023-03-01T12:00:00", "recurrence_state": { "rs": "None" } },
+                { "event_type": "payment", "name": "Rent", "account_name": "Bank", "amount": 10.0 }
+            ]"#,
+        )
+        .unwrap();
+
+        match PaymentEvent::fetch_events(path.clone()) {
+            Err(BudgetError::Json {
+                file,
+                index,
+                line,
+                column,
+                message,
+            }) => {
+                assert_eq!(path, file);
+                assert_eq!(Some(1), index);
+                assert_eq!(Some(4), line);
+                assert_eq!(Some(99), column);
+                assert_eq!("missing field `completed_at`", message);
+            }
+            res => panic!("expected json error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn serialize__tags_entries_with_their_kind() {
+        let err = BudgetError::UnknownAccount {
+            name: "Big Bank".to_string(),
+            did_you_mean: None,
+        };
+        assert_eq!(
+            r#"{"kind":"unknown_account","name":"Big Bank"}"#,
+            serde_json::to_string(&err).unwrap()
+        );
+    }
+}
@@ -1,35 +1,57 @@
+use crate::error::budget_error::BudgetError;
 use chrono;
-use std::error::Error;
+use serde::Serialize;
 use std::fs::OpenOptions;
 use std::io::prelude::*;
+use std::sync::Mutex;
 
 pub struct ErrorHandler;
 
+// errors are only printed until a log path is set
+static LOG_PATH: Mutex<Option<String>> = Mutex::new(None);
+
+// one json object per line
+#[derive(Serialize)]
+struct LogEntry<'a> {
+    logged_at: String,
+    display: String,
+    #[serde(flatten)]
+    error: &'a BudgetError,
+}
+
 impl ErrorHandler {
-    pub fn log(err: Box<dyn Error>) {
+    pub fn set_log_path(path: String) {
+        *LOG_PATH.lock().unwrap() = Some(path);
+    }
+
+    pub fn log(err: BudgetError) {
         println!("{err}");
-        write_error_to_log(err);
+        write_error_to_log(&err);
     }
 }
 
-fn write_error_to_log(err: Box<dyn Error>) {
-    let error: String = format!(
-        "{:?} - {:?}",
-        chrono::offset::Local::now().to_string(),
-        err.to_string()
-    );
+fn write_error_to_log(err: &BudgetError) {
+    let path = match LOG_PATH.lock().unwrap().clone() {
+        None => return,
+        Some(path) => path,
+    };
+    let entry = LogEntry {
+        logged_at: chrono::offset::Local::now().to_rfc3339(),
+        display: err.to_string(),
+        error: err,
+    };
 
-    let file = OpenOptions::new()
-        .write(true)
-        .append(true)
-        .open("src/error/error_log");
+    let file = OpenOptions::new().create(true).append(true).open(&path);
 
     match file {
-        Err(e) => println!("ERROR LOG FILE LOAD ERROR: {e}"),
-        Ok(mut file) => {
-            if let Err(e) = writeln!(file, "{error}") {
-                println!("ERROR LOG ERROR: {e}");
+        Err(e) => println!("ERROR LOG FILE LOAD ERROR: {path}: {e}"),
+        Ok(mut file) => match serde_json::to_string(&entry) {
+            Err(e) => println!("ERROR LOG ERROR: {e}"),
+            Ok(line) => {
+                if let Err(e) = writeln!(file, "{line}") {
+                    println!("ERROR LOG ERROR: {e}");
+                }
             }
-        }
+        },
     }
 }
@@ -1 +1,3 @@
+pub mod budget_error;
 pub mod error_handler;
+pub mod suggestion;
@@ -0,0 +1,68 @@
+// the closest of candidates to name, ignoring case
+// None when nothing is within a third of the name's length, at least 2 edits
+pub fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let name_lower = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            (
+                edit_distance(&name_lower, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+// levenshtein distance, one row at a time
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = match a_char == *b_char {
+                true => diagonal,
+                false => 1 + diagonal.min(above).min(row[j]),
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod suggestion_spec {
+    use super::*;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn did_you_mean__suggests_the_closest_name() {
+        let accounts = ["Big Bank", "Credit Union", "Credit Card"];
+        assert_eq!(
+            Some("Credit Union".to_string()),
+            did_you_mean("Credit Unoin", accounts)
+        );
+        assert_eq!(
+            Some("Big Bank".to_string()),
+            did_you_mean("big bank", accounts)
+        );
+        assert_eq!(None, did_you_mean("Brokerage", accounts));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn edit_distance__counts_insertions_deletions_and_substitutions() {
+        assert_eq!(0, edit_distance("rent", "rent"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(4, edit_distance("", "rent"));
+    }
+}
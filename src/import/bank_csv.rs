@@ -0,0 +1,182 @@
+use crate::app::cli::CsvMappingArgs;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::import::imported_transaction::{parse_amount, ImportedTransaction};
+use chrono::NaiveDate;
+use csv::StringRecord;
+use rust_decimal::Decimal;
+
+enum AmountColumns {
+    Signed(usize),
+    DebitCredit(usize, usize),
+}
+
+// reads a bank's csv export, columns are found by header name
+// either --amount-column (signed) or --debit-column/--credit-column (both positive)
+pub fn read_bank_csv(
+    path: &str,
+    mapping: &CsvMappingArgs,
+) -> BudgetResult<Vec<ImportedTransaction>> {
+    let mut rdr = csv::Reader::from_path(path).map_err(|err| BudgetError::csv(path, None, err))?;
+    let headers = rdr
+        .headers()
+        .map_err(|err| BudgetError::csv(path, None, err))?
+        .clone();
+    let column = |name: &str| -> BudgetResult<usize> {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| BudgetError::Csv {
+                file: path.to_string(),
+                row: Some(1),
+                column: Some(name.to_string()),
+                message: "column not found in header".to_string(),
+            })
+    };
+    let date_index = column(&mapping.date_column)?;
+    let memo_index = column(&mapping.memo_column)?;
+    let amount_columns = match (&mapping.debit_column, &mapping.credit_column) {
+        (Some(debit), Some(credit)) => AmountColumns::DebitCredit(column(debit)?, column(credit)?),
+        _ => AmountColumns::Signed(column(&mapping.amount_column)?),
+    };
+
+    let mut transactions: Vec<ImportedTransaction> = vec![];
+    for result in rdr.records() {
+        let record = result.map_err(|err| BudgetError::csv(path, Some(&headers), err))?;
+        let row = record.position().map(|position| position.line());
+        let error = |index: usize, message: String| BudgetError::Csv {
+            file: path.to_string(),
+            row,
+            column: headers.get(index).map(String::from),
+            message,
+        };
+
+        let date = field(&record, date_index);
+        let date = NaiveDate::parse_from_str(date, &mapping.date_format).map_err(|err| {
+            error(
+                date_index,
+                format!("invalid date {date:?} for {:?}: {err}", mapping.date_format),
+            )
+        })?;
+        let amount = match amount_columns {
+            AmountColumns::Signed(amount_index) => read_amount(&record, amount_index)
+                .map_err(|message| error(amount_index, message))?,
+            AmountColumns::DebitCredit(debit_index, credit_index) => {
+                let debit = read_amount(&record, debit_index)
+                    .map_err(|message| error(debit_index, message))?;
+                let credit = read_amount(&record, credit_index)
+                    .map_err(|message| error(credit_index, message))?;
+                credit - debit.abs()
+            }
+        };
+        if amount.is_zero() {
+            continue;
+        }
+
+        transactions.push(ImportedTransaction {
+            id: None,
+            date,
+            amount,
+            memo: field(&record, memo_index).to_string(),
+        });
+    }
+    Ok(transactions)
+}
+
+fn field(record: &StringRecord, index: usize) -> &str {
+    record.get(index).unwrap_or_default().trim()
+}
+
+// a blank cell is 0, e.g. the credit column of a debit row
+fn read_amount(record: &StringRecord, index: usize) -> Result<Decimal, String> {
+    let amount = field(record, index);
+    if amount.is_empty() {
+        return Ok(Decimal::new(0, 0));
+    }
+    parse_amount(amount).ok_or_else(|| format!("invalid amount {amount:?}"))
+}
+
+#[cfg(test)]
+mod bank_csv_spec {
+    use super::*;
+    use std::fs;
+
+    fn mapping() -> CsvMappingArgs {
+        CsvMappingArgs {
+            date_column: "Date".to_string(),
+            amount_column: "Amount".to_string(),
+            debit_column: None,
+            credit_column: None,
+            memo_column: "Description".to_string(),
+            date_format: "%m/%d/%Y".to_string(),
+        }
+    }
+
+    fn write(name: &str, content: &str) -> String {
+        let path = format!("{}/{name}", std::env::temp_dir().display());
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_bank_csv__reads_signed_amount_column() {
+        let path = write(
+            "monthly_budget_export.csv",
+            "Date,Description,Amount,Balance\n\
+             03/01/2023,COFFEE SHOP,-4.50,995.50\n\
+             03/02/2023,PAYROLL,\"$2,500.00\",3495.50\n",
+        );
+
+        let transactions = read_bank_csv(&path, &mapping()).unwrap();
+        assert_eq!(2, transactions.len());
+        assert_eq!(Decimal::new(-450, 2), transactions[0].amount);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 3, 2).unwrap(),
+            transactions[1].date
+        );
+        assert_eq!(Decimal::new(250000, 2), transactions[1].amount);
+        assert_eq!("PAYROLL", transactions[1].memo);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_bank_csv__reads_debit_and_credit_columns() {
+        let path = write(
+            "monthly_budget_debit_credit_export.csv",
+            "Posted,Payee,Debit,Credit\n\
+             2023-03-01,COFFEE SHOP,4.50,\n\
+             2023-03-02,PAYROLL,,2500.00\n",
+        );
+        let mapping = CsvMappingArgs {
+            date_column: "Posted".to_string(),
+            debit_column: Some("Debit".to_string()),
+            credit_column: Some("Credit".to_string()),
+            memo_column: "Payee".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            ..mapping()
+        };
+
+        let transactions = read_bank_csv(&path, &mapping).unwrap();
+        assert_eq!(Decimal::new(-450, 2), transactions[0].amount);
+        assert_eq!(Decimal::new(250000, 2), transactions[1].amount);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_bank_csv__reports_row_and_column_of_bad_amount() {
+        let path = write(
+            "monthly_budget_bad_export.csv",
+            "Date,Description,Amount\n\
+             03/01/2023,COFFEE SHOP,-4.50\n\
+             03/02/2023,PAYROLL,lots\n",
+        );
+
+        match read_bank_csv(&path, &mapping()) {
+            Err(BudgetError::Csv { row, column, .. }) => {
+                assert_eq!(Some(3), row);
+                assert_eq!(Some("Amount".to_string()), column);
+            }
+            res => panic!("expected csv error, got {res:?}"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::composite::payment_event::{PaymentEvent, RecurrenceState};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
+
+// one row of a bank statement export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedTransaction {
+    pub id: Option<String>, // OFX FITID, None for csv rows
+    pub date: NaiveDate,
+    pub amount: Decimal, // negative => money left the account
+    pub memo: String,
+}
+
+type DuplicateKey = (NaiveDate, String, Decimal, String); // date, event_type, amount, memo
+
+impl ImportedTransaction {
+    pub fn event_type(&self) -> &'static str {
+        match self.amount < Decimal::new(0, 0) {
+            true => "payment",
+            false => "payment_received",
+        }
+    }
+
+    pub fn to_payment_event(&self, account_name: &str) -> PaymentEvent {
+        PaymentEvent {
+            id: None,
+            event_type: self.event_type().to_string(),
+            name: self.memo.clone(),
+            account_name: account_name.to_string(),
+            from_account_name: None,
+            to_account_name: None,
+            amount: self.amount.abs(),
+            amount_low: None,
+            amount_high: None,
+            currency: None,
+            tags: vec![],
+            completed_at: self.date.and_hms_opt(12, 0, 0).unwrap(),
+            recurrence_state: RecurrenceState::None,
+            until_balance_zero: false,
+        }
+    }
+
+    // drops transactions already in events for account_name, matched by date, amount and memo
+    // two identical rows on the same day are both kept unless events already has both
+    // or they share an id, a statement can list the same FITID twice
+    // returns (new transactions, duplicates skipped)
+    pub fn without_duplicates(
+        transactions: Vec<ImportedTransaction>,
+        events: &[PaymentEvent],
+        account_name: &str,
+    ) -> (Vec<ImportedTransaction>, usize) {
+        let mut existing: BTreeMap<DuplicateKey, usize> = BTreeMap::new();
+        for event in events.iter().filter(|e| e.account_name == account_name) {
+            let key = (
+                event.completed_at.date(),
+                event.event_type.clone(),
+                event.amount.normalize(),
+                event.name.clone(),
+            );
+            *existing.entry(key).or_default() += 1;
+        }
+
+        let mut skipped: usize = 0;
+        let mut ids: BTreeSet<String> = BTreeSet::new();
+        let mut new_transactions: Vec<ImportedTransaction> = vec![];
+        for transaction in transactions.into_iter() {
+            if let Some(id) = &transaction.id {
+                if !ids.insert(id.clone()) {
+                    skipped += 1;
+                    continue;
+                }
+            }
+            let key = (
+                transaction.date,
+                transaction.event_type().to_string(),
+                transaction.amount.abs().normalize(),
+                transaction.memo.clone(),
+            );
+            match existing.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    skipped += 1;
+                }
+                _ => new_transactions.push(transaction),
+            }
+        }
+        (new_transactions, skipped)
+    }
+}
+
+// "$1,234.50", "-45.00" and "(45.00)" => Decimal
+pub fn parse_amount(amount: &str) -> Option<Decimal> {
+    let trimmed = amount.trim();
+    let (negative, digits) = match trimmed.strip_prefix('(').and_then(|a| a.strip_suffix(')')) {
+        Some(digits) => (true, digits),
+        None => (false, trimmed),
+    };
+    let digits: String = digits.chars().filter(|c| *c != '$' && *c != ',').collect();
+    let parsed: Decimal = digits.parse().ok()?;
+    match negative {
+        true => Some(-parsed),
+        false => Some(parsed),
+    }
+}
+
+#[cfg(test)]
+mod imported_transaction_spec {
+    use super::*;
+
+    fn transaction(day: u32, amount: i64, memo: &str) -> ImportedTransaction {
+        ImportedTransaction {
+            id: None,
+            date: NaiveDate::from_ymd_opt(2023, 3, day).unwrap(),
+            amount: Decimal::new(amount, 2),
+            memo: memo.to_string(),
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn without_duplicates__skips_events_matching_date_amount_and_memo() {
+        let events = vec![
+            transaction(1, -450, "Coffee").to_payment_event("Big Bank"),
+            transaction(2, 250000, "Payroll").to_payment_event("Big Bank"),
+            // same row against another account is not a duplicate
+            transaction(3, -1200, "Books").to_payment_event("Credit Union"),
+        ];
+        let transactions = vec![
+            transaction(1, -450, "Coffee"),
+            transaction(1, -450, "Coffee"), // a second coffee that day
+            transaction(2, 250000, "Payroll"),
+            transaction(3, -1200, "Books"),
+        ];
+
+        let (new_transactions, skipped) =
+            ImportedTransaction::without_duplicates(transactions, &events, "Big Bank");
+        assert_eq!(2, skipped);
+        assert_eq!(
+            vec![
+                transaction(1, -450, "Coffee"),
+                transaction(3, -1200, "Books")
+            ],
+            new_transactions
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn without_duplicates__keeps_one_transaction_per_id() {
+        let with_id = |id: &str, day: u32| ImportedTransaction {
+            id: Some(id.to_string()),
+            ..transaction(day, -450, "Coffee")
+        };
+        let transactions = vec![with_id("1", 1), with_id("2", 1), with_id("1", 1)];
+
+        let (new_transactions, skipped) =
+            ImportedTransaction::without_duplicates(transactions, &[], "Big Bank");
+        assert_eq!(1, skipped);
+        assert_eq!(vec![with_id("1", 1), with_id("2", 1)], new_transactions);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn parse_amount__reads_bank_formats() {
+        assert_eq!(Some(Decimal::new(123450, 2)), parse_amount("$1,234.50"));
+        assert_eq!(Some(Decimal::new(-4500, 2)), parse_amount("(45.00)"));
+        assert_eq!(Some(Decimal::new(-4500, 2)), parse_amount(" -45.00 "));
+        assert_eq!(None, parse_amount("n/a"));
+    }
+}
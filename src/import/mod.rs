@@ -0,0 +1,3 @@
+pub mod bank_csv;
+pub mod imported_transaction;
+pub mod ofx;
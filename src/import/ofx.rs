@@ -0,0 +1,151 @@
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::import::imported_transaction::{parse_amount, ImportedTransaction};
+use chrono::NaiveDate;
+use std::fs;
+
+// reads the STMTTRN entries of an OFX/QFX statement
+// handles both SGML (OFX 1.x, unclosed tags) and XML (OFX 2.x) files
+// FITID is kept as the transaction id, import skips a repeated one
+pub fn read_ofx(path: &str) -> BudgetResult<Vec<ImportedTransaction>> {
+    let content = fs::read_to_string(path)?;
+    let mut transactions: Vec<ImportedTransaction> = vec![];
+    let mut fields: Option<Vec<(String, String)>> = None;
+    // line of the element being read, and of the <STMTTRN> that opened fields
+    let mut line: usize = 1 + content
+        .split('<')
+        .next()
+        .unwrap_or_default()
+        .matches('\n')
+        .count();
+    let mut transaction_line: usize = line;
+    // "<TRNAMT>-45.00\n" => ("TRNAMT", "-45.00")
+    for element in content.split('<').skip(1) {
+        let element_line = line;
+        line += element.matches('\n').count();
+        let (tag, value) = element.split_once('>').unwrap_or((element, ""));
+        let tag = tag.trim().to_uppercase();
+        match tag.as_str() {
+            "STMTTRN" => {
+                fields = Some(vec![]);
+                transaction_line = element_line;
+            }
+            "/STMTTRN" => {
+                let error = |message: String| BudgetError::Ofx {
+                    file: path.to_string(),
+                    line: transaction_line,
+                    message,
+                };
+                let fields = fields.take().unwrap_or_default();
+                let field = |name: &str| {
+                    fields
+                        .iter()
+                        .find(|(tag, _)| tag == name)
+                        .map(|(_, value)| value.as_str())
+                };
+                let date =
+                    field("DTPOSTED").ok_or_else(|| error("missing DTPOSTED".to_string()))?;
+                // 20230301120000[-5:EST] => 20230301
+                let date = NaiveDate::parse_from_str(date.get(..8).unwrap_or(date), "%Y%m%d")
+                    .map_err(|err| error(format!("invalid DTPOSTED {date:?}: {err}")))?;
+                let amount = field("TRNAMT").ok_or_else(|| error("missing TRNAMT".to_string()))?;
+                let amount = parse_amount(amount)
+                    .ok_or_else(|| error(format!("invalid TRNAMT {amount:?}")))?;
+                // NAME is the payee, MEMO is often blank or a reference number
+                let memo = field("NAME")
+                    .filter(|name| !name.is_empty())
+                    .or_else(|| field("MEMO"))
+                    .unwrap_or_default();
+                transactions.push(ImportedTransaction {
+                    id: field("FITID")
+                        .filter(|fitid| !fitid.is_empty())
+                        .map(String::from),
+                    date,
+                    amount,
+                    memo: memo.to_string(),
+                });
+            }
+            _ => {
+                if let Some(fields) = fields.as_mut() {
+                    if !tag.starts_with('/') {
+                        fields.push((tag, value.trim().to_string()));
+                    }
+                }
+            }
+        }
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod ofx_spec {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn write(name: &str, content: &str) -> String {
+        let path = format!("{}/{name}", std::env::temp_dir().display());
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_ofx__reads_sgml_transactions() {
+        let path = write(
+            "monthly_budget_statement.qfx",
+            "OFXHEADER:100\nDATA:OFXSGML\n\n<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><BANKTRANLIST>\n\
+             <STMTTRN>\n<TRNTYPE>DEBIT\n<DTPOSTED>20230301120000[-5:EST]\n<TRNAMT>-45.00\n\
+             <FITID>1\n<NAME>COFFEE SHOP\n<MEMO>POS 1234\n</STMTTRN>\n\
+             <STMTTRN>\n<TRNTYPE>CREDIT\n<DTPOSTED>20230315\n<TRNAMT>2500.00\n\
+             <FITID>2\n<MEMO>PAYROLL\n</STMTTRN>\n\
+             </BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>\n",
+        );
+
+        let transactions = read_ofx(&path).unwrap();
+        assert_eq!(2, transactions.len());
+        assert_eq!(Some("1".to_string()), transactions[0].id);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+            transactions[0].date
+        );
+        assert_eq!(Decimal::new(-4500, 2), transactions[0].amount);
+        assert_eq!("COFFEE SHOP", transactions[0].memo);
+        // falls back to MEMO without a NAME
+        assert_eq!("PAYROLL", transactions[1].memo);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_ofx__reads_xml_transactions() {
+        let path = write(
+            "monthly_budget_statement.ofx",
+            r#"<?xml version="1.0"?><OFX><BANKTRANLIST>
+                <STMTTRN><DTPOSTED>20230302</DTPOSTED><TRNAMT>-12.50</TRNAMT><NAME>Books</NAME></STMTTRN>
+            </BANKTRANLIST></OFX>"#,
+        );
+
+        let transactions = read_ofx(&path).unwrap();
+        assert_eq!(1, transactions.len());
+        assert_eq!(Decimal::new(-1250, 2), transactions[0].amount);
+        assert_eq!("Books", transactions[0].memo);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_ofx__reports_transaction_missing_amount() {
+        let path = write(
+            "monthly_budget_bad_statement.ofx",
+            "<OFX>\n<STMTTRN><DTPOSTED>20230301<TRNAMT>-1.00</STMTTRN>\n\
+             <STMTTRN>\n<DTPOSTED>20230302\n<NAME>Books\n</STMTTRN>\n</OFX>",
+        );
+
+        let err = read_ofx(&path).unwrap_err();
+        match &err {
+            BudgetError::Ofx { line, message, .. } => {
+                assert_eq!(3, *line);
+                assert_eq!("missing TRNAMT", message);
+            }
+            res => panic!("expected ofx error, got {res:?}"),
+        }
+        assert!(err.to_string().ends_with("line 3: missing TRNAMT"));
+    }
+}
@@ -1,27 +1,74 @@
-use crate::app::cli::Cli;
+use crate::app::cli::SimulateArgs;
 use crate::calendar::calendar_slice::CalendarSlice;
+use crate::calendar::holiday::HolidayCalendar;
 use crate::calendar::year_month::YearMonth as YM;
 use crate::composite::account_summary::AccountSummary;
+use crate::composite::alert::{Alert, AlertStore};
+use crate::composite::budget_summary::BudgetRolloverStore;
+use crate::composite::goal_summary::{GoalSummary, GoalSummaryStore};
+use crate::composite::interest_accrual::{InterestAccrual, InterestAccrualStore};
+use crate::composite::loan::{Loan, LoanPayment, LoanSummary};
+use crate::composite::monte_carlo_summary::{
+    MonteCarloSample, MonteCarloSummary, MonteCarloSummaryStore,
+};
 use crate::composite::payment_event::PaymentEvent;
-use crate::composite::payment_event::PaymentEventBinStore;
+use crate::composite::payment_event::{PaymentEventBinStore, PaymentEventStore};
 use crate::composite::payment_summary::{PaymentSummary, PaymentSummaryStore};
+use crate::composite::reconciliation::{
+    ReconciliationEntry, ReconciliationStatus, ReconciliationSummary,
+};
 use crate::composite::recurring_payment_event::RecurringPaymentEvent;
+use crate::composite::scenario::{MonthEndBalanceStore, Scenario, ScenarioDiff, ScenarioDiffStore};
+use crate::composite::statement::{BalanceSheetLine, IncomeStatementLine, NetWorth};
+use crate::composite::statement_payment_event::StatementPaymentEvent;
+use crate::composite::tag_summary::{TagSummary, TagSummaryStore};
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::error_handler::ErrorHandler;
 use crate::programs::month_model::MonthModel;
+use crate::schema::account::{Account, AccountStore};
 use crate::schema::expense::Expense;
+use crate::schema::expense_tag::ExpenseTag;
+use crate::schema::fx_rate::DEFAULT_CURRENCY;
+use crate::schema::goal::{Goal, GoalStore};
 use crate::schema::income::Income;
+use crate::schema::income_tag::IncomeTag;
+use crate::schema::opening_balance::{OpeningBalance, OpeningBalanceStore};
+use crate::storage::backend::{StoreBackend, StoreKind};
 use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use crate::traits::file_io::FileIO;
-use std::error::Error;
+use crate::traits::tag_link::TagLink;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
 
 pub struct CalendarSliceModel {
     start: YM,
     end: YM,
     output_results: bool,
     path: String,
+    store_kind: StoreKind,
+    fail_on_overdraft: bool,
+    actuals_through: Option<YM>,
+    strict: bool,
+    base_currency: String,
 }
 
-pub type CalendarSliceModelResult = Result<(), Box<dyn Error>>;
+pub type CalendarSliceModelResult = Result<(), BudgetError>;
+type PaymentEventBinResult = Result<PaymentEventBinStore, BudgetError>;
+type CalendarSliceResult = Result<CalendarSlice, BudgetError>;
+type StoreResult = Result<Store, BudgetError>;
+type OpeningBalanceResult = Result<OpeningBalanceStore, BudgetError>;
+type LoanResult = Result<Vec<Loan>, BudgetError>;
+type GoalResult = Result<GoalStore, BudgetError>;
+type MonthEndBalanceResult = Result<MonthEndBalanceStore, BudgetError>;
+
+// account_id => month => one sample per run
+pub type MonteCarloSampleStore = BTreeMap<usize, BTreeMap<YM, Vec<MonteCarloSample>>>;
+type MonteCarloSampleResult = Result<(MonteCarloSampleStore, Store), BudgetError>;
 
 impl FileIO<CalendarSliceModel> for CalendarSliceModel {
     fn path(&self) -> String {
@@ -36,51 +83,155 @@ impl CalendarSliceModel {
             end,
             path,
             output_results,
+            store_kind: StoreKind::Csv,
+            fail_on_overdraft: false,
+            actuals_through: None,
+            strict: false,
+            base_currency: DEFAULT_CURRENCY.to_string(),
         }
     }
 
-    pub fn run_cli(cli: Cli) -> CalendarSliceModelResult {
-        let start = YM::parse(cli.start_yyyy_mm);
-        let end = YM::parse(cli.end_yyyy_mm);
+    pub fn set_store_kind(&mut self, store_kind: StoreKind) {
+        self.store_kind = store_kind;
+    }
+
+    // run returns Err after writing reports when an overdraft or over_credit_limit alert was raised
+    // below_minimum alerts are reported only, run_monte_carlo does not check alerts
+    pub fn set_fail_on_overdraft(&mut self, fail_on_overdraft: bool) {
+        self.fail_on_overdraft = fail_on_overdraft;
+    }
+
+    // months up to and including through run events/actuals.json instead of the forecast
+    // so later months open from the balances the actuals left behind
+    pub fn set_actuals_through(&mut self, actuals_through: Option<YM>) {
+        self.actuals_through = actuals_through;
+    }
+
+    // refuse to run when an event names an account, expense or income not in the store
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // currency the statement reports are written in
+    pub fn set_base_currency(&mut self, base_currency: String) {
+        self.base_currency = base_currency;
+    }
+
+    pub fn actuals_path(&self) -> String {
+        format!("{}/{}", self.path_events(), "actuals.json")
+    }
+
+    fn store_backend(&self) -> Box<dyn StoreBackend> {
+        self.store_kind.backend(self.path_in(), self.path_out())
+    }
+
+    pub fn run_cli(path: String, args: SimulateArgs) -> CalendarSliceModelResult {
         println!("Running from Cli...");
-        println!("Using data from {:?}", cli.path);
-        println!("Start from: {:?} - {:?}", start.year, start.month);
-        println!("End at: {:?} - {:?}", end.year, end.month);
+        println!("Using data from {path:?}");
+        println!("Start from: {:?} - {:?}", args.start.year, args.start.month);
+        println!("End at: {:?} - {:?}", args.end.year, args.end.month);
 
-        CalendarSliceModel::new(start, end, cli.path, true).run()
+        let mut model = CalendarSliceModel::new(args.start, args.end, path, !args.dry_run);
+        model.set_store_kind(args.store.store);
+        model.set_fail_on_overdraft(args.fail_on_overdraft);
+        model.set_actuals_through(args.actuals_through);
+        model.set_strict(args.strict);
+        model.set_base_currency(args.base_currency);
+        // read every overlay up front so a bad file fails before the baseline runs
+        let scenarios = args
+            .scenarios
+            .iter()
+            .map(|path| Scenario::fetch(path))
+            .collect::<Result<Vec<Scenario>, BudgetError>>()?;
+        // scenarios start from the store as it was before the baseline saved over it
+        let saved_store = match scenarios.is_empty() {
+            true => Store::new(),
+            false => model.saved_store()?,
+        };
+        model.check_scenario_names(&scenarios, &saved_store)?;
+        let result = match args.monte_carlo_runs {
+            0 => model.run(),
+            runs => model.run_monte_carlo(runs, args.seed),
+        };
+        // an overdraft still leaves a baseline to compare against
+        if scenarios.is_empty() || !matches!(result, Ok(()) | Err(BudgetError::Overdraft { .. })) {
+            return result;
+        }
+        model.run_scenarios(&scenarios, &saved_store)?;
+        result
     }
 
-    pub fn run(&self) -> CalendarSliceModelResult {
+    // run only the start month against the loaded store
+    pub fn run_month(&self) -> CalendarSliceModelResult {
         println!(
-            "Running Calendar Slice Model From: {:#?}-{:#?} to {:#?}-{:#?}",
-            self.start.year, self.start.month, self.end.year, self.end.month
+            "Running Month Model For: {:#?}-{:#?}",
+            self.start.year, self.start.month
         );
 
-        let mut store = Store::new();
-        store.init(Some(self.path_in()))?;
+        let cal_slice = self.cal_slice(self.start, self.start)?;
+        let mut store = self.load_store(&cal_slice)?;
+        let mut payment_event_month_bins = self.bin_payment_events(&cal_slice, &store.accounts)?;
+        self.check_unknown_names(&store, &payment_event_month_bins)?;
+        self.warn_missing_opening_balances(&cal_slice, &store, &payment_event_month_bins)?;
+        let pe_bin_store = payment_event_month_bins.entry(self.start).or_default();
 
-        let cal_slice = CalendarSlice::new(self.start, self.end)?;
-        let mut payment_event_month_bins = PaymentEventBinStore::new();
+        let mut month_model = MonthModel::new(self.start, self.path(), self.output_results);
+        month_model.set_base_currency(self.base_currency.clone());
+        month_model.set_store_kind(self.store_kind.clone());
+        month_model.run(pe_bin_store, Some(&mut store))?;
+        CalendarSliceModel::warn_unplaced_events(month_model.unplaced_events());
 
-        let recurring_events_path = format!("{}/{}", self.path_events(), "reccurring.json");
-        RecurringPaymentEvent::fetch_and_bin_recurring_events(
-            recurring_events_path,
-            &cal_slice,
-            &mut payment_event_month_bins,
-        )?;
+        if self.output_results {
+            self.store_backend().save(&store)?;
+        }
 
-        let one_off_events_path = format!("{}/{}", self.path_events(), "one_off.json");
-        PaymentEvent::fetch_and_bin_one_off_events(
-            one_off_events_path,
-            &cal_slice,
-            &mut payment_event_month_bins,
-        )?;
+        println!("===============================================");
+
+        Ok(())
+    }
+
+    pub fn run(&self) -> CalendarSliceModelResult {
+        println!(
+            "Running Calendar Slice Model From: {:#?}-{:#?} to {:#?}-{:#?}",
+            self.start.year, self.start.month, self.end.year, self.end.month
+        );
+
+        let cal_slice = self.cal_slice(self.start, self.end)?;
+        let mut store = self.load_store(&cal_slice)?;
+        let mut payment_event_month_bins = self.bin_payment_events(&cal_slice, &store.accounts)?;
+        self.check_unknown_names(&store, &payment_event_month_bins)?;
+        self.warn_missing_opening_balances(&cal_slice, &store, &payment_event_month_bins)?;
 
+        let mut unplaced_events: usize = 0;
+        let mut budget_rollover = BudgetRolloverStore::new();
+        let mut paid_off = BTreeSet::new();
+        let mut alerts = AlertStore::new();
+        let mut interest_accruals = InterestAccrualStore::new();
         for month in cal_slice.months().iter() {
             // year_months in chrono order thx to Eq, PartialEq, PartialOrd, Ord Traits and BTreeMap
             let pe_bin_store = payment_event_month_bins.entry(*month).or_default();
-            MonthModel::new(*month, self.path(), true).run(pe_bin_store, Some(&mut store))?;
+            let mut month_model = MonthModel::new(*month, self.path(), self.output_results);
+            month_model.set_base_currency(self.base_currency.clone());
+            month_model.set_store_kind(self.store_kind.clone());
+            month_model.set_budget_rollover(budget_rollover);
+            month_model.set_paid_off(paid_off);
+            month_model.run(pe_bin_store, Some(&mut store))?;
+            paid_off = month_model.paid_off();
+            unplaced_events += month_model.unplaced_events();
+            budget_rollover = month_model.budget_rollover();
+            for (_id, mut alert) in month_model.alerts().into_iter() {
+                alert.id = None; // renumber across the slice
+                Alert::save_to_store(alert, &mut alerts);
+            }
+            for (_id, mut accrual) in month_model.interest_accruals().into_iter() {
+                accrual.id = None;
+                InterestAccrual::save_to_store(accrual, &mut interest_accruals);
+            }
         }
+        CalendarSliceModel::warn_unplaced_events(unplaced_events);
+        CalendarSliceModel::warn_alerts(&alerts);
+        let goal_summary = GoalSummary::summarize(&self.goals()?, &store, &cal_slice);
+        CalendarSliceModel::print_goals(&goal_summary);
 
         if self.output_results {
             let account_ids: Vec<usize> = store.accounts.keys().cloned().collect();
@@ -90,20 +241,81 @@ impl CalendarSliceModel {
                 AccountSummary::write_to_csv(&account_summary_store, path.as_str())?;
             }
 
-            let expense_summary = CalendarSliceModel::construct_payment_summary(&mut store);
+            let expense_summary =
+                CalendarSliceModel::construct_payment_summary(&mut store, &self.base_currency)?;
             PaymentSummary::write_to_csv(
                 &expense_summary,
                 self.format_path("expense_summary".to_string()).as_str(),
             )?;
 
-            let income_summary = CalendarSliceModel::construct_payment_received_summary(&mut store);
+            let income_summary = CalendarSliceModel::construct_payment_received_summary(
+                &mut store,
+                &self.base_currency,
+            )?;
             PaymentSummary::write_to_csv(
                 &income_summary,
                 self.format_path("income_summary".to_string()).as_str(),
             )?;
 
+            let tag_summary =
+                CalendarSliceModel::construct_tag_summary(&mut store, &self.base_currency)?;
+            if !tag_summary.is_empty() {
+                TagSummary::write_to_csv(
+                    &tag_summary,
+                    self.format_path("tag_summary".to_string()).as_str(),
+                )?;
+            }
+
+            // month by month across the slice, where the summaries above are slice totals
+            IncomeStatementLine::write_to_csv(
+                &IncomeStatementLine::construct(
+                    &store,
+                    &cal_slice,
+                    &interest_accruals,
+                    &self.base_currency,
+                )?,
+                self.format_path("income_statement".to_string()).as_str(),
+            )?;
+            let balance_sheet =
+                BalanceSheetLine::construct(&mut store, &cal_slice, &self.base_currency)?;
+            BalanceSheetLine::write_to_csv(
+                &balance_sheet,
+                self.format_path("balance_sheet".to_string()).as_str(),
+            )?;
+            NetWorth::write_to_csv(
+                &NetWorth::construct(&mut store, &cal_slice, &balance_sheet, &self.base_currency)?,
+                self.format_path("net_worth".to_string()).as_str(),
+            )?;
+
+            Alert::write_to_csv(&alerts, self.format_path("alerts".to_string()).as_str())?;
+
+            if !goal_summary.is_empty() {
+                GoalSummary::write_to_csv(
+                    &goal_summary,
+                    self.format_path("goal_summary".to_string()).as_str(),
+                )?;
+            }
+
+            // payoff dates and interest over the life of each loan, not just the slice
+            let loans = self.loans()?;
+            if !loans.is_empty() {
+                LoanSummary::write_to_csv(
+                    &Loan::summarize(&loans),
+                    self.format_path("loan_summary".to_string()).as_str(),
+                )?;
+                LoanPayment::write_to_csv(
+                    &Loan::schedules(&loans),
+                    self.format_path("loan_schedule".to_string()).as_str(),
+                )?;
+            }
+
             // write main store
-            store.write_to_csv(Some(self.path_out()))?;
+            self.store_backend().save(&store)?;
+        }
+
+        let overdrafts = Alert::overdrafts(&alerts);
+        if self.fail_on_overdraft && overdrafts > 0 {
+            return Err(BudgetError::Overdraft { alerts: overdrafts });
         }
 
         // println!("Payment Event Bins: {payment_event_month_bins:#?}");
@@ -113,39 +325,873 @@ impl CalendarSliceModel {
         Ok(())
     }
 
+    // run the slice `runs` times, drawing each ranged event amount from [amount_low, amount_high]
+    pub fn run_monte_carlo(&self, runs: usize, seed: u64) -> CalendarSliceModelResult {
+        println!(
+            "Running {runs} Monte Carlo Simulations (seed {seed}) From: {:#?}-{:#?} to {:#?}-{:#?}",
+            self.start.year, self.start.month, self.end.year, self.end.month
+        );
+
+        let (samples, mut store) = self.monte_carlo_samples(runs, seed)?;
+
+        if self.output_results {
+            let mut slice_summary_store = MonteCarloSummaryStore::new();
+            for (account_id, months) in samples.iter() {
+                let name = match Account::by_id(*account_id, &mut store.accounts) {
+                    None => format!("No Name Found for Account Id: {account_id}"),
+                    Some(account) => account.name,
+                };
+
+                let mut month_summary_store = MonteCarloSummaryStore::new();
+                for (year_month, month_samples) in months.iter() {
+                    MonteCarloSummary::save_to_store(
+                        MonteCarloSummary::from_samples(
+                            name.clone(),
+                            year_month.display(),
+                            month_samples,
+                        ),
+                        &mut month_summary_store,
+                    );
+                }
+                let path = self.format_path(format!("monte_carlo_account_{account_id}_summary"));
+                MonteCarloSummary::write_to_csv(&month_summary_store, path.as_str())?;
+
+                // final balance of each run, negative if any month of the run went negative
+                let slice_samples: Vec<MonteCarloSample> = (0..runs)
+                    .map(|run| MonteCarloSample {
+                        month_end_balance: months.values().last().unwrap()[run].month_end_balance,
+                        went_negative: months.values().any(|m| m[run].went_negative),
+                    })
+                    .collect();
+                let mut slice_summary = MonteCarloSummary::from_samples(
+                    name,
+                    format!("{}..{}", self.start.display(), self.end.display()),
+                    &slice_samples,
+                );
+                slice_summary.id = Some(*account_id);
+                MonteCarloSummary::save_to_store(slice_summary, &mut slice_summary_store);
+            }
+            MonteCarloSummary::write_to_csv(
+                &slice_summary_store,
+                self.format_path("monte_carlo_summary".to_string()).as_str(),
+            )?;
+        }
+
+        println!("===============================================");
+
+        Ok(())
+    }
+
+    // returns the store of the final run alongside the samples
+    pub fn monte_carlo_samples(&self, runs: usize, seed: u64) -> MonteCarloSampleResult {
+        let cal_slice = self.cal_slice(self.start, self.end)?;
+        let mut store = self.load_store(&cal_slice)?;
+        let payment_event_month_bins = self.bin_payment_events(&cal_slice, &store.accounts)?;
+        self.check_unknown_names(&store, &payment_event_month_bins)?;
+        self.warn_missing_opening_balances(&cal_slice, &store, &payment_event_month_bins)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut samples = MonteCarloSampleStore::new();
+        let mut unplaced_events: usize = 0;
+
+        for _run in 0..runs {
+            store = self.load_store(&cal_slice)?;
+
+            let mut paid_off = BTreeSet::new();
+            for month in cal_slice.months().iter() {
+                let mut pe_bin_store = PaymentEventStore::new();
+                if let Some(bin) = payment_event_month_bins.get(month) {
+                    for (id, payment_event) in bin.iter() {
+                        let mut randomized = payment_event.clone_record();
+                        randomized.randomize_amount(&mut rng);
+                        pe_bin_store.entry(*id).or_insert(randomized);
+                    }
+                }
+                let mut month_model = MonthModel::new(*month, self.path(), false);
+                month_model.set_base_currency(self.base_currency.clone());
+                month_model.set_store_kind(self.store_kind.clone());
+                month_model.set_paid_off(paid_off);
+                month_model.run(&pe_bin_store, Some(&mut store))?;
+                paid_off = month_model.paid_off();
+                unplaced_events += month_model.unplaced_events();
+
+                let month_start = month.start_of_month().and_hms_opt(0, 0, 0).unwrap();
+                let month_end = month.start_of_next_month().and_hms_opt(0, 0, 0).unwrap();
+                let account_ids: Vec<usize> = store.accounts.keys().cloned().collect();
+                for account_id in account_ids.iter() {
+                    let account = Account::by_id(*account_id, &mut store.accounts).unwrap();
+                    let month_end_balance = account.current_balance(&mut store.account_balances);
+                    let min_balance = account
+                        .min_balance_between(month_start, month_end, &mut store.account_balances)
+                        .unwrap_or(month_end_balance);
+                    samples
+                        .entry(*account_id)
+                        .or_default()
+                        .entry(*month)
+                        .or_default()
+                        .push(MonteCarloSample {
+                            month_end_balance,
+                            went_negative: min_balance < Decimal::new(0, 0),
+                        });
+                }
+            }
+        }
+
+        CalendarSliceModel::warn_unplaced_events(unplaced_events);
+        Ok((samples, store))
+    }
+
+    // run the baseline and each scenario over the slice without writing their months
+    // and write scenario_diff.csv with the month-end balance of every account in both
+    // every run starts from a copy of saved_store, the backend may already hold the baseline's results
+    pub fn run_scenarios(
+        &self,
+        scenarios: &[Scenario],
+        saved_store: &Store,
+    ) -> CalendarSliceModelResult {
+        println!(
+            "Running {} Scenario(s) From: {:#?}-{:#?} to {:#?}-{:#?}",
+            scenarios.len(),
+            self.start.year,
+            self.start.month,
+            self.end.year,
+            self.end.month
+        );
+
+        let cal_slice = self.cal_slice(self.start, self.end)?;
+        let store = self.open_store(&cal_slice, saved_store.clone())?;
+        let mut baseline_bins = self.bin_payment_events(&cal_slice, &store.accounts)?;
+        let baseline = self.month_end_balances(&cal_slice, &mut baseline_bins, saved_store)?;
+
+        let mut diffs = ScenarioDiffStore::new();
+        for scenario in scenarios.iter() {
+            let mut bins = self.bin_payment_events(&cal_slice, &store.accounts)?;
+            scenario.apply(&mut bins, &cal_slice, self.actuals_through);
+            let balances = self.month_end_balances(&cal_slice, &mut bins, saved_store)?;
+            ScenarioDiff::diff(&scenario.name, &baseline, &balances, &mut diffs);
+        }
+
+        let end = self.end.display();
+        for diff in diffs.values().filter(|diff| diff.year_month == end) {
+            if !diff.difference.is_zero() {
+                println!(
+                    "{}: {} ends {end} at {} ({:+} vs baseline)",
+                    diff.scenario, diff.account_name, diff.scenario_balance, diff.difference
+                );
+            }
+        }
+
+        if self.output_results {
+            ScenarioDiff::write_to_csv(
+                &diffs,
+                self.format_path("scenario_diff".to_string()).as_str(),
+            )?;
+        }
+
+        println!("===============================================");
+
+        Ok(())
+    }
+
+    // a copy of saved_store run through every month of bins, nothing is written
+    fn month_end_balances(
+        &self,
+        cal_slice: &CalendarSlice,
+        bins: &mut PaymentEventBinStore,
+        saved_store: &Store,
+    ) -> MonthEndBalanceResult {
+        let mut store = self.open_store(cal_slice, saved_store.clone())?;
+        self.check_unknown_names(&store, bins)?;
+        let mut balances = MonthEndBalanceStore::new();
+        let mut paid_off = BTreeSet::new();
+        for month in cal_slice.months().iter() {
+            let pe_bin_store = bins.entry(*month).or_default();
+            let mut month_model = MonthModel::new(*month, self.path(), false);
+            month_model.set_base_currency(self.base_currency.clone());
+            month_model.set_store_kind(self.store_kind.clone());
+            month_model.set_paid_off(paid_off);
+            month_model.run(pe_bin_store, Some(&mut store))?;
+            paid_off = month_model.paid_off();
+            for account in store.accounts.values() {
+                balances
+                    .entry(account.name.clone())
+                    .or_default()
+                    .insert(*month, account.current_balance(&mut store.account_balances));
+            }
+        }
+        Ok(balances)
+    }
+
+    pub fn print_goals(goal_summary: &GoalSummaryStore) {
+        for summary in goal_summary.values() {
+            let projected = match summary.projected_date {
+                Some(date) => format!("reaches {} on {date}", summary.target_amount),
+                None => format!("does not reach {} at this pace", summary.target_amount),
+            };
+            match summary.shortfall.is_zero() {
+                true => println!(
+                    "Goal {:?} {projected}, on track for {}",
+                    summary.name, summary.target_date
+                ),
+                false => println!(
+                    "Goal {:?} {projected}, {} short on {}, save {} more a month",
+                    summary.name,
+                    summary.shortfall,
+                    summary.target_date,
+                    summary.extra_monthly_contribution
+                ),
+            }
+        }
+    }
+
+    // every binned event should land on a Day, anything else was silently dropped
+    pub fn warn_unplaced_events(unplaced_events: usize) {
+        if unplaced_events > 0 {
+            ErrorHandler::log(From::from(format!(
+                "WARNING: {unplaced_events} payment event(s) did not land on any day and were not executed."
+            )));
+        }
+    }
+
+    pub fn warn_alerts(alerts: &AlertStore) {
+        for alert in alerts.values() {
+            println!(
+                "ALERT: {:?} on {} leaves {:?} at {}, {} short of {}.",
+                alert.event_name,
+                alert.occurred_at,
+                alert.account_name,
+                alert.ending_balance,
+                alert.shortfall,
+                alert.floor
+            );
+        }
+    }
+
+    // the backend's store with the opening balances for the slice start applied
+    fn load_store(&self, cal_slice: &CalendarSlice) -> StoreResult {
+        self.open_store(cal_slice, self.saved_store()?)
+    }
+
+    // the backend's store as it is before this run
+    pub fn saved_store(&self) -> StoreResult {
+        let mut store = Store::new();
+        self.store_backend().load(&mut store)?;
+        Ok(store)
+    }
+
+    // loans first so an opening balance may name a loan's account
+    // in strict mode every account opening_balances.csv names is checked before any is applied
+    fn open_store(&self, cal_slice: &CalendarSlice, mut store: Store) -> StoreResult {
+        Loan::apply(&self.loans()?, cal_slice.start, &mut store)?;
+        let opening_balances = self.opening_balances()?;
+        if self.strict {
+            CalendarSliceModel::fail_on_unknown(OpeningBalance::unknown_names(
+                &opening_balances,
+                &store,
+            ))?;
+        }
+        OpeningBalance::apply(&opening_balances, cal_slice.start, &mut store)?;
+        Ok(store)
+    }
+
+    // events/loans.json is optional
+    pub fn loans(&self) -> LoanResult {
+        let path = format!("{}/{}", self.path_events(), "loans.json");
+        match Path::new(&path).exists() {
+            true => Loan::fetch_loans(path),
+            false => Ok(vec![]),
+        }
+    }
+
+    // init/goals.csv is optional, read whichever store backend is used
+    fn goals(&self) -> GoalResult {
+        let mut goals = GoalStore::new();
+        Goal::init_store_if_exists(&mut goals, &format!("{}/{}", self.path_in(), "goals.csv"))?;
+        Ok(goals)
+    }
+
+    // read from init/ whichever store backend is used
+    fn opening_balances(&self) -> OpeningBalanceResult {
+        let mut opening_balances = OpeningBalanceStore::new();
+        OpeningBalance::init_store_if_exists(
+            &mut opening_balances,
+            &format!("{}/{}", self.path_in(), "opening_balances.csv"),
+        )?;
+        Ok(opening_balances)
+    }
+
+    // in strict mode every name an event uses must already be in the store
+    fn check_unknown_names(
+        &self,
+        store: &Store,
+        payment_event_month_bins: &PaymentEventBinStore,
+    ) -> CalendarSliceModelResult {
+        if !self.strict {
+            return Ok(());
+        }
+        let payment_events = payment_event_month_bins
+            .values()
+            .flat_map(|bin| bin.values());
+        CalendarSliceModel::fail_on_unknown(PaymentEvent::unknown_names(payment_events, store))
+    }
+
+    // logs each unknown name and counts them in a single Strict error
+    fn fail_on_unknown(unknown: Vec<BudgetError>) -> CalendarSliceModelResult {
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        let count = unknown.len();
+        for err in unknown.into_iter() {
+            ErrorHandler::log(err);
+        }
+        Err(BudgetError::Strict { unknown: count })
+    }
+
+    // in strict mode the events a scenario adds are checked before the baseline runs
+    pub fn check_scenario_names(
+        &self,
+        scenarios: &[Scenario],
+        saved_store: &Store,
+    ) -> CalendarSliceModelResult {
+        if !self.strict || scenarios.is_empty() {
+            return Ok(());
+        }
+        let cal_slice = self.cal_slice(self.start, self.end)?;
+        let store = self.open_store(&cal_slice, saved_store.clone())?;
+        for scenario in scenarios.iter() {
+            let mut bins = PaymentEventBinStore::new();
+            scenario.bin_added_events(&mut bins, &cal_slice, self.actuals_through);
+            self.check_unknown_names(&store, &bins)?;
+        }
+        Ok(())
+    }
+
+    // every account in the store or named by an event should have a balance at the slice start
+    fn warn_missing_opening_balances(
+        &self,
+        cal_slice: &CalendarSlice,
+        store: &Store,
+        payment_event_month_bins: &PaymentEventBinStore,
+    ) -> CalendarSliceModelResult {
+        let year_months = OpeningBalance::year_months(&self.opening_balances()?)?;
+        if !year_months.is_empty() && !year_months.contains(&cal_slice.start) {
+            let year_months: Vec<String> = year_months.iter().map(|ym| ym.display()).collect();
+            ErrorHandler::log(From::from(format!(
+                "WARNING: opening_balances.csv has no snapshot for {}, only for {}.",
+                cal_slice.start.display(),
+                year_months.join(", ")
+            )));
+        }
+
+        let mut account_names: BTreeSet<String> = store
+            .accounts
+            .values()
+            .map(|account| account.name.clone())
+            .collect();
+        for payment_event in payment_event_month_bins
+            .values()
+            .flat_map(|bin| bin.values())
+        {
+            let names = [
+                Some(&payment_event.account_name),
+                payment_event.from_account_name.as_ref(),
+                payment_event.to_account_name.as_ref(),
+            ];
+            for name in names.into_iter().flatten() {
+                if !name.is_empty() {
+                    account_names.insert(name.clone());
+                }
+            }
+        }
+        for name in OpeningBalance::missing(store, &account_names, cal_slice.start) {
+            ErrorHandler::log(From::from(format!(
+                "WARNING: {name:?} has no opening balance as of {}, it starts at 0.",
+                cal_slice.start.display()
+            )));
+        }
+        Ok(())
+    }
+
+    // holidays are read from init/ whichever store backend is used
+    pub fn cal_slice(&self, start: YM, end: YM) -> CalendarSliceResult {
+        let mut cal_slice = CalendarSlice::new(start, end)?;
+        cal_slice.set_holidays(HolidayCalendar::load(&self.path_in())?);
+        Ok(cal_slice)
+    }
+
+    pub fn bin_payment_events(
+        &self,
+        cal_slice: &CalendarSlice,
+        accounts: &AccountStore,
+    ) -> PaymentEventBinResult {
+        let mut payment_event_month_bins = PaymentEventBinStore::new();
+
+        let recurring_events_path = format!("{}/{}", self.path_events(), "reccurring.json");
+        RecurringPaymentEvent::fetch_and_bin_recurring_events(
+            recurring_events_path,
+            cal_slice,
+            &mut payment_event_month_bins,
+        )?;
+
+        let one_off_events_path = format!("{}/{}", self.path_events(), "one_off.json");
+        PaymentEvent::fetch_and_bin_one_off_events(
+            one_off_events_path,
+            cal_slice,
+            &mut payment_event_month_bins,
+        )?;
+
+        // actuals replace the forecast events, statement and loan payments still run
+        if let Some(through) = self.actuals_through {
+            let mut actual_bins = self.bin_actuals(cal_slice)?;
+            for month in cal_slice.months().into_iter().filter(|m| *m <= through) {
+                payment_event_month_bins
+                    .insert(month, actual_bins.remove(&month).unwrap_or_default());
+            }
+        }
+
+        StatementPaymentEvent::bin_statement_payment_events(
+            accounts,
+            cal_slice,
+            &mut payment_event_month_bins,
+        );
+
+        Loan::bin_loan_payment_events(&self.loans()?, cal_slice, &mut payment_event_month_bins);
+
+        Ok(payment_event_month_bins)
+    }
+
+    fn bin_actuals(&self, cal_slice: &CalendarSlice) -> PaymentEventBinResult {
+        let mut actual_bins = PaymentEventBinStore::new();
+        if Path::new(&self.actuals_path()).exists() {
+            PaymentEvent::fetch_and_bin_one_off_events(
+                self.actuals_path(),
+                cal_slice,
+                &mut actual_bins,
+            )?;
+        }
+        Ok(actual_bins)
+    }
+
+    // compare the forecast for start..end against events/actuals.json
+    pub fn reconcile(&self, window_days: i64) -> CalendarSliceModelResult {
+        let cal_slice = self.cal_slice(self.start, self.end)?;
+        let store = self.load_store(&cal_slice)?;
+        let forecast_bins = self.bin_payment_events(&cal_slice, &store.accounts)?;
+        let actual_bins = self.bin_actuals(&cal_slice)?;
+
+        let forecast: Vec<&PaymentEvent> = forecast_bins
+            .values()
+            .flat_map(|bin| bin.values())
+            .collect();
+        let actuals: Vec<&PaymentEvent> =
+            actual_bins.values().flat_map(|bin| bin.values()).collect();
+        let entries = ReconciliationEntry::reconcile(&forecast, &actuals, window_days);
+        let summary = ReconciliationEntry::summarize(&entries);
+
+        for month in cal_slice.months().iter() {
+            let month_entries: Vec<&ReconciliationEntry> = entries
+                .values()
+                .filter(|entry| entry.year_month == month.display())
+                .collect();
+            let count = |status: ReconciliationStatus| {
+                month_entries
+                    .iter()
+                    .filter(|entry| entry.status == status)
+                    .count()
+            };
+            println!(
+                "{}: {} matched, {} missing, {} unexpected, variance {}",
+                month.display(),
+                count(ReconciliationStatus::Matched),
+                count(ReconciliationStatus::Missing),
+                count(ReconciliationStatus::Unexpected),
+                month_entries
+                    .iter()
+                    .map(|entry| entry.variance)
+                    .sum::<Decimal>()
+            );
+        }
+
+        if self.output_results {
+            ReconciliationEntry::write_to_csv(
+                &entries,
+                self.format_path("reconciliation".to_string()).as_str(),
+            )?;
+            ReconciliationSummary::write_to_csv(
+                &summary,
+                self.format_path("reconciliation_summary".to_string())
+                    .as_str(),
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn format_path(&self, path: String) -> String {
         format!("{}/{}.csv", self.path_out(), path)
     }
 
-    pub fn construct_payment_summary(store: &mut Store) -> PaymentSummaryStore {
+    // totals are converted to base_currency
+    pub fn construct_payment_summary(
+        store: &mut Store,
+        base_currency: &str,
+    ) -> BudgetResult<PaymentSummaryStore> {
         let mut payment_summary_store = PaymentSummaryStore::new();
         let expense_ids: Vec<usize> = store.expenses.keys().cloned().collect();
         for expense_id in expense_ids {
             // sorted expense ids
+            let total = Expense::total_by_id(expense_id, store, base_currency)?;
             payment_summary_store
                 .entry(expense_id)
                 .or_insert(PaymentSummary {
                     id: Some(expense_id),
                     name: Expense::name_by_id(expense_id, &mut store.expenses).to_string(),
-                    total: Expense::total_by_id(expense_id, store),
+                    total,
                 });
         }
-        payment_summary_store
+        Ok(payment_summary_store)
+    }
+
+    pub fn construct_tag_summary(
+        store: &mut Store,
+        base_currency: &str,
+    ) -> BudgetResult<TagSummaryStore> {
+        let mut tag_summary_store = TagSummaryStore::new();
+        let tag_ids: Vec<usize> = store.tags.keys().cloned().collect();
+        for tag_id in tag_ids.iter() {
+            let mut expense_total = Decimal::new(0, 0);
+            for expense_id in ExpenseTag::tagged_ids(*tag_id, &store.expense_tags) {
+                expense_total += Expense::total_by_id(expense_id, store, base_currency)?;
+            }
+            let mut income_total = Decimal::new(0, 0);
+            for income_id in IncomeTag::tagged_ids(*tag_id, &store.income_tags) {
+                income_total += Income::total_by_id(income_id, store, base_currency)?;
+            }
+            tag_summary_store.entry(*tag_id).or_insert(TagSummary {
+                id: Some(*tag_id),
+                name: store.tags[tag_id].content.clone(),
+                expense_total,
+                income_total,
+            });
+        }
+        Ok(tag_summary_store)
     }
 
-    pub fn construct_payment_received_summary(store: &mut Store) -> PaymentSummaryStore {
+    pub fn construct_payment_received_summary(
+        store: &mut Store,
+        base_currency: &str,
+    ) -> BudgetResult<PaymentSummaryStore> {
         let mut payment_received_summary_store = PaymentSummaryStore::new();
         let income_ids: Vec<usize> = store.incomes.keys().cloned().collect();
         for income_id in income_ids {
             // sorted expense ids
+            let total = Income::total_by_id(income_id, store, base_currency)?;
             payment_received_summary_store
                 .entry(income_id)
                 .or_insert(PaymentSummary {
                     id: Some(income_id),
                     name: Income::name_by_id(income_id, &mut store.incomes).to_string(),
-                    total: Income::total_by_id(income_id, store),
+                    total,
                 });
         }
-        payment_received_summary_store
+        Ok(payment_received_summary_store)
+    }
+}
+
+#[cfg(test)]
+mod calendar_slice_model_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use crate::composite::scenario::ScenarioChange;
+
+    fn model() -> CalendarSliceModel {
+        CalendarSliceModel::new(
+            YM::new(2023, MK::Mar),
+            YM::new(2023, MK::Apr),
+            "src/test/end_to_end/calendar_slice_model/data".to_string(),
+            false,
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn monte_carlo_samples__records_one_sample_per_run_per_account_per_month() {
+        let (samples, _store) = model().monte_carlo_samples(3, 1).unwrap();
+        assert_eq!(2, samples.len()); // Big Bank, Credit Union
+        for (_account_id, months) in samples.iter() {
+            assert_eq!(2, months.len());
+            for (_ym, month_samples) in months.iter() {
+                assert_eq!(3, month_samples.len());
+            }
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn monte_carlo_samples__runs_are_identical_when_no_event_has_a_range() {
+        let (samples, _store) = model().monte_carlo_samples(3, 1).unwrap();
+        let credit_union = &samples[&2][&YM::new(2023, MK::Apr)];
+        assert_eq!(
+            credit_union[0].month_end_balance,
+            credit_union[2].month_end_balance
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct_payment_summary__converts_each_payment_to_the_base_currency() {
+        use crate::schema::fx_rate::FxRate;
+        use crate::test::spec::Spec;
+        use chrono::NaiveDate;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        // swearjar pays mortgage 3100 and dog food 45 in EUR
+        store
+            .accounts
+            .update(2, |account| account.currency = Some("EUR".to_string()));
+        FxRate::save_to_store(
+            FxRate {
+                id: None,
+                date: NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+                pair: "EUR/USD".to_string(),
+                rate: Decimal::new(120, 2),
+            },
+            &mut store.fx_rates,
+        );
+
+        let summary = CalendarSliceModel::construct_payment_summary(&mut store, "USD").unwrap();
+        assert_eq!(Decimal::new(6820, 0), summary[&1].total); // 3100 + 3100 * 1.2
+        assert_eq!(Decimal::new(54, 0), summary[&3].total);
+        let tag_summary = CalendarSliceModel::construct_tag_summary(&mut store, "USD").unwrap();
+        assert_eq!(Decimal::new(304, 0), tag_summary[&1].expense_total); // household
+        let summary = CalendarSliceModel::construct_payment_summary(&mut store, "EUR").unwrap();
+        assert_eq!(Decimal::new(568_333, 2), summary[&1].total); // 3100 / 1.2 + 3100
+        assert!(CalendarSliceModel::construct_payment_summary(&mut store, "GBP").is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn bin_payment_events__keeps_statement_payments_in_months_run_from_actuals() {
+        use crate::schema::account::{Account, AccountKind};
+
+        let mut accounts = AccountStore::new();
+        for account in [
+            Account {
+                name: "Big Bank".to_string(),
+                ..Default::default()
+            },
+            Account {
+                name: "Card".to_string(),
+                kind: AccountKind::Liability,
+                payment_due_day: Some(20),
+                funding_account_id: Some(1),
+                ..Default::default()
+            },
+        ] {
+            Account::save_to_store(account, &mut accounts);
+        }
+        let mut model = model();
+        model.set_actuals_through(Some(YM::new(2023, MK::Mar)));
+        let cal_slice = model.cal_slice(model.start, model.end).unwrap();
+
+        // the end-to-end data has no actuals.json, march keeps only the statement payment
+        let bins = model.bin_payment_events(&cal_slice, &accounts).unwrap();
+        let march: Vec<&str> = bins[&YM::new(2023, MK::Mar)]
+            .values()
+            .map(|event| event.event_type.as_str())
+            .collect();
+        assert_eq!(vec!["statement_payment"], march);
+        assert!(bins[&YM::new(2023, MK::Apr)].len() > 1);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn run__returns_err_before_running_when_strict_finds_unknown_names() {
+        let mut model = model();
+        model.set_strict(true);
+        // the end-to-end events create their expenses on first use
+        assert!(matches!(
+            model.run(),
+            Err(BudgetError::Strict { unknown: 4 })
+        ));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn run_scenarios__starts_from_the_store_saved_before_the_baseline_run() {
+        use std::fs;
+
+        // a copy of the end-to-end data, run loads a sqlite database beside it and saves to reports/
+        let path = format!(
+            "{}/monthly_budget_scenarios_sqlite_{}",
+            std::env::temp_dir().display(),
+            std::process::id()
+        );
+        let _ = fs::remove_dir_all(&path);
+        for dir in ["init", "events"] {
+            fs::create_dir_all(format!("{path}/{dir}")).unwrap();
+            let from = format!("src/test/end_to_end/calendar_slice_model/data/{dir}");
+            for entry in fs::read_dir(from).unwrap() {
+                let entry = entry.unwrap();
+                fs::copy(
+                    entry.path(),
+                    format!("{path}/{dir}/{}", entry.file_name().to_str().unwrap()),
+                )
+                .unwrap();
+            }
+        }
+        fs::create_dir_all(format!("{path}/reports")).unwrap();
+        let mut model = CalendarSliceModel::new(
+            YM::new(2023, MK::Mar),
+            YM::new(2023, MK::Apr),
+            path.clone(),
+            true,
+        );
+        model.set_store_kind(StoreKind::Sqlite(format!("{path}/budget.db")));
+
+        let saved_store = model.saved_store().unwrap();
+        model.run().unwrap();
+        // a sqlite run does not also write the csv store after each month
+        assert!(!std::path::Path::new(&format!("{path}/reports/accounts.csv")).exists());
+        let scenario = Scenario {
+            name: "unchanged".to_string(),
+            ..Default::default()
+        };
+        model.run_scenarios(&[scenario], &saved_store).unwrap();
+
+        // the baseline ends where run left the saved accounts, not a second run on top of them
+        let mut final_store = Store::new();
+        model.store_backend().load_saved(&mut final_store).unwrap();
+        let mut diffs = ScenarioDiffStore::new();
+        ScenarioDiff::init_store(&mut diffs, &format!("{path}/reports/scenario_diff.csv")).unwrap();
+        let april: Vec<&ScenarioDiff> = diffs
+            .values()
+            .filter(|diff| diff.year_month == "2023-04")
+            .collect();
+        assert_eq!(2, april.len());
+        for diff in april {
+            let account = Account::by_name(&diff.account_name, &final_store.accounts).unwrap();
+            assert_eq!(
+                account.current_balance(&mut final_store.account_balances),
+                diff.baseline_balance
+            );
+        }
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn check_scenario_names__returns_err_when_strict_and_a_scenario_names_an_unknown_account() {
+        use crate::composite::payment_event::PaymentEvent;
+        use chrono::NaiveDate;
+
+        let scenario = Scenario {
+            name: "save more".to_string(),
+            one_off: vec![PaymentEvent {
+                event_type: "transfer".to_string(),
+                name: "Save".to_string(),
+                from_account_name: Some("Big Bank".to_string()),
+                to_account_name: Some("Savings".to_string()),
+                amount: Decimal::new(300, 0),
+                completed_at: NaiveDate::from_ymd_opt(2023, 4, 15)
+                    .unwrap()
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut model = model();
+        let saved_store = model.saved_store().unwrap();
+        let scenarios = [scenario];
+        assert!(model.check_scenario_names(&scenarios, &saved_store).is_ok());
+
+        model.set_strict(true);
+        assert!(matches!(
+            model.check_scenario_names(&scenarios, &saved_store),
+            Err(BudgetError::Strict { unknown: 1 })
+        ));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn open_store__returns_err_when_strict_and_any_opening_balance_names_an_unknown_account() {
+        use crate::test::spec::Spec;
+        use std::fs;
+
+        let path = format!(
+            "{}/monthly_budget_opening_balances_{}",
+            std::env::temp_dir().display(),
+            std::process::id()
+        );
+        fs::create_dir_all(format!("{path}/init")).unwrap();
+        // the typos are in April, a month the slice does not start in
+        fs::write(
+            format!("{path}/init/opening_balances.csv"),
+            "id,year_month,account_name,amount\n\
+             1,2023-03,piggybank,500.0\n\
+             2,2023-04,piggybnak,700.0\n\
+             3,2023-04,swearjr,100.0\n",
+        )
+        .unwrap();
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let mut model = CalendarSliceModel::new(
+            YM::new(2023, MK::Mar),
+            YM::new(2023, MK::Apr),
+            path.clone(),
+            false,
+        );
+        let cal_slice = model.cal_slice(model.start, model.end).unwrap();
+        assert!(model.open_store(&cal_slice, store.clone()).is_ok());
+
+        model.set_strict(true);
+        let result = model.open_store(&cal_slice, store);
+        fs::remove_dir_all(&path).unwrap();
+        assert!(matches!(result, Err(BudgetError::Strict { unknown: 2 })));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn month_end_balances__reflect_a_scenario_overlay() {
+        let model = model();
+        let cal_slice = model.cal_slice(model.start, model.end).unwrap();
+        let saved_store = model.saved_store().unwrap();
+        let store = model.load_store(&cal_slice).unwrap();
+        let mut baseline_bins = model
+            .bin_payment_events(&cal_slice, &store.accounts)
+            .unwrap();
+        let baseline = model
+            .month_end_balances(&cal_slice, &mut baseline_bins, &saved_store)
+            .unwrap();
+
+        let scenario = Scenario {
+            name: "cheaper repair".to_string(),
+            modify: vec![ScenarioChange {
+                name: "Car Repair".to_string(),
+                amount: Some(Decimal::new(150, 0)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut bins = model
+            .bin_payment_events(&cal_slice, &store.accounts)
+            .unwrap();
+        scenario.apply(&mut bins, &cal_slice, None);
+        let balances = model
+            .month_end_balances(&cal_slice, &mut bins, &saved_store)
+            .unwrap();
+
+        let mut diffs = ScenarioDiffStore::new();
+        ScenarioDiff::diff(&scenario.name, &baseline, &balances, &mut diffs);
+        let credit_union: Vec<Decimal> = diffs
+            .values()
+            .filter(|diff| diff.account_name == "Credit Union")
+            .map(|diff| diff.difference)
+            .collect();
+        // the 400 repair in March becomes 150 and the saving carries into April
+        assert_eq!(
+            vec![Decimal::new(250, 0), Decimal::new(250, 0)],
+            credit_union
+        );
     }
 }
@@ -3,21 +3,32 @@ use crate::calendar::month::Month;
 use crate::calendar::month_key::MonthKey as MK;
 use crate::calendar::year_month::YearMonth as YM;
 use crate::composite::account_summary::{AccountSummary, AccountSummaryStore};
+use crate::composite::alert::AlertStore;
+use crate::composite::budget_summary::{BudgetRolloverStore, BudgetSummary, BudgetSummaryStore};
+use crate::composite::interest_accrual::{InterestAccrualStore, UnpostedInterestStore};
 use crate::composite::payment_composite::PaymentCompositeStore;
 use crate::composite::payment_display::{PaymentDisplay, PaymentDisplayStore};
 use crate::composite::payment_event::PaymentEventStore;
 use crate::composite::payment_received_composite::PaymentReceivedCompositeStore;
 use crate::composite::payment_summary::PaymentSummary;
 use crate::composite::payment_summary::PaymentSummaryStore;
-use crate::schema::expense::{Expense, ExpenseStore};
-use crate::schema::income::{Income, IncomeStore};
+use crate::composite::tag_summary::{TagSummary, TagSummaryStore};
+use crate::composite::transfer_composite::TransferCompositeStore;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::schema::budget::BudgetStore;
+use crate::schema::expense::Expense;
+use crate::schema::expense_tag::ExpenseTag;
+use crate::schema::fx_rate::DEFAULT_CURRENCY;
+use crate::schema::income::Income;
+use crate::schema::income_tag::IncomeTag;
+use crate::storage::backend::StoreKind;
 use crate::storage::store::Store;
 use crate::traits::csv_store::CsvStore;
 use crate::traits::file_io::FileIO;
+use crate::traits::tag_link::TagLink;
 use chrono::NaiveDate;
-use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
-use std::error::Error;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub struct MonthModel {
     year: i32,
@@ -25,6 +36,11 @@ pub struct MonthModel {
     month: Month,
     path: String,
     output_results: bool,
+    unplaced_events: usize, // events in payment_events whose date matched no Day in month
+    budget_rollover: BudgetRolloverStore, // carried in from the previous month, then out to the next
+    base_currency: String,                // summaries and budgets are totalled in it
+    paid_off: BTreeSet<String>, // until_balance_zero recurrences that ended, carried like budget_rollover
+    store_kind: StoreKind,      // csv stores are written out after each month
 }
 
 impl FileIO<MonthModel> for MonthModel {
@@ -41,15 +57,59 @@ impl MonthModel {
             month: Month::new(year_month),
             path,
             output_results,
+            unplaced_events: 0,
+            budget_rollover: BudgetRolloverStore::new(),
+            base_currency: DEFAULT_CURRENCY.to_string(),
+            paid_off: BTreeSet::new(),
+            store_kind: StoreKind::Csv,
         }
     }
 
+    pub fn unplaced_events(&self) -> usize {
+        self.unplaced_events
+    }
+
+    // after run, debits that left an account below its floor
+    pub fn alerts(&self) -> AlertStore {
+        self.month.alerts()
+    }
+
+    pub fn interest_accruals(&self) -> InterestAccrualStore {
+        self.month.interest_accruals()
+    }
+
+    pub fn set_base_currency(&mut self, base_currency: String) {
+        self.base_currency = base_currency;
+    }
+
+    pub fn set_store_kind(&mut self, store_kind: StoreKind) {
+        self.store_kind = store_kind;
+    }
+
+    pub fn set_budget_rollover(&mut self, budget_rollover: BudgetRolloverStore) {
+        self.budget_rollover = budget_rollover;
+    }
+
+    // after run, the unused limits to carry into the next month
+    pub fn budget_rollover(&self) -> BudgetRolloverStore {
+        self.budget_rollover.clone()
+    }
+
+    pub fn set_paid_off(&mut self, paid_off: BTreeSet<String>) {
+        self.paid_off = paid_off;
+    }
+
+    // after run, the until_balance_zero recurrences to skip in the next month
+    pub fn paid_off(&self) -> BTreeSet<String> {
+        self.paid_off.clone()
+    }
+
     // Model Payments and PaymentsReceived occuring at specific times throughout the specified month
     pub fn run(
         &mut self,
         payment_events: &PaymentEventStore,
         store_ext: Option<&mut Store>,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<(), BudgetError> {
         let mut self_store = Store::new();
         let store = match store_ext {
             Some(passed_in) => passed_in,
@@ -70,15 +130,24 @@ impl MonthModel {
             year: self.year,
         };
 
-        self.record_payment_events_in_month(payment_events);
+        self.unplaced_events = self.record_payment_events_in_month(payment_events);
 
         // iterate through the days and execute payments in order
         // each payment event mutates store
-        for (_id, day) in self.month.days.iter_mut() {
+        let last_day = self.month.days.len();
+        // everything accrued is posted on the last day, nothing carries into the next month
+        let mut unposted_interest = UnpostedInterestStore::new();
+        for (id, day) in self.month.days.iter_mut() {
             // iter sorted by key thx to btree_map
+            day.skip_paid_off_transfers(&self.paid_off);
             day.execute_payments_in_order(store)?;
+            self.paid_off.extend(day.end_paid_off_transfers());
+            day.accrue_interest(store, *id == last_day, &mut unposted_interest)?;
         }
 
+        let budget_summary = self.construct_budget_summary(store)?;
+        self.budget_rollover = MonthModel::next_budget_rollover(&budget_summary, &store.budgets);
+
         if self.output_results {
             let account_ids: Vec<usize> = store.accounts.keys().cloned().collect();
             for id in account_ids.iter() {
@@ -90,13 +159,13 @@ impl MonthModel {
                 )?;
             }
 
-            let expense_summary = self.construct_payment_summary(&mut store.expenses);
+            let expense_summary = self.construct_payment_summary(store)?;
             PaymentSummary::write_to_csv(
                 &expense_summary,
                 self.format_path("expense_summary".to_string()).as_str(),
             )?;
 
-            let income_summary = self.construct_payment_received_summary(&mut store.incomes);
+            let income_summary = self.construct_payment_received_summary(store)?;
             PaymentSummary::write_to_csv(
                 &income_summary,
                 self.format_path("income_summary".to_string()).as_str(),
@@ -116,7 +185,31 @@ impl MonthModel {
                     .as_str(),
             )?;
 
-            store.write_to_csv(Some(self.path_out()))?;
+            let all_transfer_disp_store: PaymentDisplayStore = self.month.all_transfers_display();
+            PaymentDisplay::write_to_csv(
+                &all_transfer_disp_store,
+                self.format_path("all_transfers".to_string()).as_str(),
+            )?;
+
+            let tag_summary = self.construct_tag_summary(store)?;
+            if !tag_summary.is_empty() {
+                TagSummary::write_to_csv(
+                    &tag_summary,
+                    self.format_path("tag_summary".to_string()).as_str(),
+                )?;
+            }
+
+            if !budget_summary.is_empty() {
+                BudgetSummary::write_to_csv(
+                    &budget_summary,
+                    self.format_path("budget_vs_actual".to_string()).as_str(),
+                )?;
+            }
+
+            // a sqlite store is saved once, by the slice's backend after the last month
+            if self.store_kind == StoreKind::Csv {
+                store.write_to_csv(Some(self.path_out()))?;
+            }
         }
 
         Ok(())
@@ -156,6 +249,54 @@ impl MonthModel {
                             );
                         }
                     }
+                    "transfer" => {
+                        let ec = day.transfers.get(id).unwrap();
+                        // skipped statement payments have no ending balance
+                        if let (Some(acc_id), Some(balance)) =
+                            (ec.from_account_id, ec.from_ending_balance)
+                        {
+                            if acc_id == account_id {
+                                AccountSummary::save_to_store(
+                                    AccountSummary {
+                                        id: None,
+                                        name: ec.from_account_name.clone(),
+                                        balance,
+                                        reported_at: ec.transfer_completed_at,
+                                    },
+                                    &mut account_summary_store,
+                                );
+                            }
+                        }
+                        if let (Some(acc_id), Some(balance)) =
+                            (ec.to_account_id, ec.to_ending_balance)
+                        {
+                            if acc_id == account_id {
+                                AccountSummary::save_to_store(
+                                    AccountSummary {
+                                        id: None,
+                                        name: ec.to_account_name.clone(),
+                                        balance,
+                                        reported_at: ec.transfer_completed_at,
+                                    },
+                                    &mut account_summary_store,
+                                );
+                            }
+                        }
+                    }
+                    "interest" => {
+                        let ec = day.interest_accruals.get(id).unwrap();
+                        if ec.account_id == account_id {
+                            AccountSummary::save_to_store(
+                                AccountSummary {
+                                    id: None,
+                                    name: ec.account_name.clone(),
+                                    balance: ec.ending_balance.unwrap(),
+                                    reported_at: ec.accrued_at,
+                                },
+                                &mut account_summary_store,
+                            );
+                        }
+                    }
                     _ => (),
                 };
             }
@@ -173,48 +314,123 @@ impl MonthModel {
         )
     }
 
-    pub fn construct_payment_summary(&self, store: &mut ExpenseStore) -> PaymentSummaryStore {
+    pub fn construct_payment_summary(
+        &self,
+        store: &mut Store,
+    ) -> BudgetResult<PaymentSummaryStore> {
         let mut payment_summary_store = PaymentSummaryStore::new();
         for expense_id in self.month.expense_ids().iter() {
-            if let Entry::Occupied(expense) = store.entry(*expense_id) {
-                if expense.get().active {
-                    payment_summary_store
-                        .entry(*expense_id)
-                        .or_insert(PaymentSummary {
-                            id: Some(*expense_id),
-                            name: Expense::name_by_id(*expense_id, store).to_string(),
-                            total: Expense::month_total_by_id(*expense_id, &self.month),
-                        });
-                }
+            let active = store
+                .expenses
+                .get(expense_id)
+                .map_or(false, |expense| expense.active);
+            if active {
+                let total = Expense::month_total_by_id(
+                    *expense_id,
+                    &self.month,
+                    store,
+                    &self.base_currency,
+                )?;
+                payment_summary_store
+                    .entry(*expense_id)
+                    .or_insert(PaymentSummary {
+                        id: Some(*expense_id),
+                        name: Expense::name_by_id(*expense_id, &mut store.expenses).to_string(),
+                        total,
+                    });
             }
         }
-        payment_summary_store
+        Ok(payment_summary_store)
     }
 
     pub fn construct_payment_received_summary(
         &self,
-        store: &mut IncomeStore,
-    ) -> PaymentSummaryStore {
+        store: &mut Store,
+    ) -> BudgetResult<PaymentSummaryStore> {
         let mut payment_rec_summary_store = PaymentSummaryStore::new();
         for income_id in self.month.income_ids().iter() {
-            if let Entry::Occupied(income) = store.entry(*income_id) {
-                if income.get().active {
-                    payment_rec_summary_store
-                        .entry(*income_id)
-                        .or_insert(PaymentSummary {
-                            id: Some(*income_id),
-                            name: Income::name_by_id(*income_id, store).to_string(),
-                            total: Income::month_total_by_id(*income_id, &self.month),
-                        });
+            let active = store
+                .incomes
+                .get(income_id)
+                .map_or(false, |income| income.active);
+            if active {
+                let total =
+                    Income::month_total_by_id(*income_id, &self.month, store, &self.base_currency)?;
+                payment_rec_summary_store
+                    .entry(*income_id)
+                    .or_insert(PaymentSummary {
+                        id: Some(*income_id),
+                        name: Income::name_by_id(*income_id, &mut store.incomes).to_string(),
+                        total,
+                    });
+            }
+        }
+        Ok(payment_rec_summary_store)
+    }
+
+    pub fn construct_budget_summary(&self, store: &Store) -> BudgetResult<BudgetSummaryStore> {
+        let mut budget_summary_store = BudgetSummaryStore::new();
+        for (budget_id, budget) in store.budgets.iter() {
+            let rollover_in = match self.budget_rollover.get(budget_id) {
+                Some(amount) if budget.rollover => *amount,
+                _ => Decimal::new(0, 0),
+            };
+            BudgetSummary::save_to_store(
+                BudgetSummary::new(
+                    budget,
+                    rollover_in,
+                    budget.month_actual(&self.month, store, &self.base_currency)?,
+                ),
+                &mut budget_summary_store,
+            );
+        }
+        Ok(budget_summary_store)
+    }
+
+    pub fn construct_tag_summary(&self, store: &Store) -> BudgetResult<TagSummaryStore> {
+        let mut tag_summary_store = TagSummaryStore::new();
+        for (tag_id, tag) in store.tags.iter() {
+            let mut expense_total = Decimal::new(0, 0);
+            for expense_id in ExpenseTag::tagged_ids(*tag_id, &store.expense_tags) {
+                expense_total += Expense::month_total_by_id(
+                    expense_id,
+                    &self.month,
+                    store,
+                    &self.base_currency,
+                )?;
+            }
+            let mut income_total = Decimal::new(0, 0);
+            for income_id in IncomeTag::tagged_ids(*tag_id, &store.income_tags) {
+                income_total +=
+                    Income::month_total_by_id(income_id, &self.month, store, &self.base_currency)?;
+            }
+            tag_summary_store.entry(*tag_id).or_insert(TagSummary {
+                id: Some(*tag_id),
+                name: tag.content.clone(),
+                expense_total,
+                income_total,
+            });
+        }
+        Ok(tag_summary_store)
+    }
+
+    pub fn next_budget_rollover(
+        budget_summary: &BudgetSummaryStore,
+        budgets: &BudgetStore,
+    ) -> BudgetRolloverStore {
+        let mut budget_rollover = BudgetRolloverStore::new();
+        for (_id, summary) in budget_summary.iter() {
+            if let Some(budget) = budgets.get(&summary.budget_id) {
+                if budget.rollover {
+                    budget_rollover.insert(summary.budget_id, summary.rollover_out());
                 }
             }
         }
-        payment_rec_summary_store
+        budget_rollover
     }
 
-    // TODO: leap years
     pub fn construct_days(year: i32, month: MK) -> DayStore {
-        let length: u32 = Month::length(month);
+        let length: u32 = YM::new(year, month).length();
         let month_id: u32 = Month::id(month);
         let mut days: DayStore = BTreeMap::new();
 
@@ -224,6 +440,9 @@ impl MonthModel {
                 id: Some(id),
                 payments: PaymentCompositeStore::new(),
                 payments_received: PaymentReceivedCompositeStore::new(),
+                transfers: TransferCompositeStore::new(),
+                interest_accruals: InterestAccrualStore::new(),
+                alerts: AlertStore::new(),
                 date: NaiveDate::from_ymd_opt(year, month_id, date).unwrap(),
             });
         }
@@ -231,23 +450,31 @@ impl MonthModel {
         days
     }
 
-    pub fn record_payment_events_in_month(&mut self, payment_events: &PaymentEventStore) {
+    // returns the number of payment events that did not land on a day in the month
+    pub fn record_payment_events_in_month(&mut self, payment_events: &PaymentEventStore) -> usize {
+        let mut unplaced_events: usize = 0;
         for (_id, payment_event) in payment_events.iter() {
+            let mut placed = false;
             for (_id, day) in self.month.days.iter_mut() {
                 // inner loop is O(1)
                 if payment_event.completed_at.date() == day.date {
                     day.add_payment_event(payment_event);
+                    placed = true;
                 }
             }
+            if !placed {
+                unplaced_events += 1;
+            }
         }
+        unplaced_events
     }
 }
 
 #[cfg(test)]
 mod month_model_spec {
     use super::*;
-    // use crate::test::spec::Spec;
-    // use chrono::NaiveDate;
+    use crate::composite::payment_event::PaymentEvent;
+    use crate::test::spec::Spec;
 
     pub fn model() -> MonthModel {
         MonthModel::new(
@@ -257,11 +484,247 @@ mod month_model_spec {
         )
     }
 
-    //TODO: specs
+    fn payment_event(completed_at: NaiveDate) -> PaymentEvent {
+        PaymentEvent {
+            event_type: "payment".to_string(),
+            name: "Leap Day Party".to_string(),
+            account_name: "piggybank".to_string(),
+            amount: Decimal::new(29, 0),
+            completed_at: completed_at.and_hms_opt(12, 0, 0).unwrap(),
+            ..Default::default()
+        }
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn record_payment_events_in_month__adds_payment_composites_to_days() {
-        let _model = model();
+        let mut model = model();
+        model.month.days = MonthModel::construct_days(2023, MK::Feb);
+
+        let mut payment_events = PaymentEventStore::new();
+        PaymentEvent::save_to_store(
+            payment_event(NaiveDate::from_ymd_opt(2023, 2, 14).unwrap()),
+            &mut payment_events,
+        );
+
+        assert_eq!(0, model.record_payment_events_in_month(&payment_events));
+        assert_eq!(1, model.month.days[&14].payments.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn record_payment_events_in_month__places_events_on_leap_day() {
+        let mut model = MonthModel::new(
+            YM::new(2024, MK::Feb),
+            "src/test/data/init".to_string(),
+            false,
+        );
+        model.month.days = MonthModel::construct_days(2024, MK::Feb);
+
+        let mut payment_events = PaymentEventStore::new();
+        PaymentEvent::save_to_store(
+            payment_event(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+            &mut payment_events,
+        );
+
+        assert_eq!(0, model.record_payment_events_in_month(&payment_events));
+        assert_eq!(1, model.month.days[&29].payments.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn run__carries_unused_budget_into_next_month() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut food = payment_event(NaiveDate::from_ymd_opt(2023, 2, 14).unwrap());
+        food.name = "food".to_string();
+        food.amount = Decimal::new(100, 0);
+        let mut payment_events = PaymentEventStore::new();
+        PaymentEvent::save_to_store(food, &mut payment_events);
+
+        let mut model = model();
+        let mut budget_rollover = BudgetRolloverStore::new();
+        budget_rollover.insert(1, Decimal::new(50, 0));
+        model.set_budget_rollover(budget_rollover);
+        model.run(&payment_events, Some(&mut store)).unwrap();
+
+        // 300 limit + 50 carried in - 100 spent
+        assert_eq!(Decimal::new(250, 0), model.budget_rollover()[&1]);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn run__stops_until_balance_zero_transfers_once_paid_off() {
+        use crate::composite::payment_event::RecurrenceState;
+        use crate::schema::account::Account;
+        use crate::schema::account_balance::AccountBalance;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let owes = |amount: i64, on: NaiveDate, store: &mut Store| {
+            AccountBalance::save_to_store(
+                AccountBalance {
+                    id: None,
+                    account_id: 2,
+                    reported_at: on.and_hms_opt(0, 0, 0).unwrap(),
+                    amount: Decimal::new(amount, 0),
+                },
+                &mut store.account_balances,
+            );
+        };
+        let weekly = |month: u32| {
+            let mut payment_events = PaymentEventStore::new();
+            for day in [1, 8, 15, 22] {
+                PaymentEvent::save_to_store(
+                    PaymentEvent {
+                        event_type: "transfer".to_string(),
+                        name: "Pay Off".to_string(),
+                        from_account_name: Some("piggybank".to_string()),
+                        to_account_name: Some("swearjar".to_string()),
+                        amount: Decimal::new(100, 0),
+                        completed_at: NaiveDate::from_ymd_opt(2023, month, day)
+                            .unwrap()
+                            .and_hms_opt(12, 0, 0)
+                            .unwrap(),
+                        recurrence_state: RecurrenceState::Active,
+                        until_balance_zero: true,
+                        ..Default::default()
+                    },
+                    &mut payment_events,
+                );
+            }
+            payment_events
+        };
+        owes(
+            -250,
+            NaiveDate::from_ymd_opt(2023, 1, 31).unwrap(),
+            &mut store,
+        );
+
+        let mut model = model();
+        model.run(&weekly(2), Some(&mut store)).unwrap();
+        let swearjar = Account::by_name("swearjar", &store.accounts).unwrap();
+        assert_eq!(
+            Decimal::new(0, 0),
+            swearjar.current_balance(&mut store.account_balances)
+        );
+        assert_eq!(1, model.month.days[&15].transfers.len());
+        assert!(matches!(
+            model.month.days[&15].transfers[&1].recurrence_state,
+            RecurrenceState::Last
+        ));
+        assert!(model.month.days[&22].transfers.is_empty());
+
+        // owing again does not restart a recurrence that already paid off
+        owes(
+            -100,
+            NaiveDate::from_ymd_opt(2023, 2, 28).unwrap(),
+            &mut store,
+        );
+        let mut march = MonthModel::new(YM::new(2023, MK::Mar), model.path(), false);
+        march.set_paid_off(model.paid_off());
+        march.run(&weekly(3), Some(&mut store)).unwrap();
+        assert!(march
+            .month
+            .days
+            .values()
+            .all(|day| day.transfers.is_empty()));
+        assert_eq!(
+            Decimal::new(-100, 0),
+            swearjar.current_balance(&mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn run__totals_summaries_and_budgets_in_the_base_currency() {
+        use crate::schema::fx_rate::FxRate;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        FxRate::save_to_store(
+            FxRate {
+                id: None,
+                date: NaiveDate::from_ymd_opt(2023, 2, 1).unwrap(),
+                pair: "EUR/USD".to_string(),
+                rate: Decimal::new(110, 2),
+            },
+            &mut store.fx_rates,
+        );
+
+        let mut food = payment_event(NaiveDate::from_ymd_opt(2023, 2, 14).unwrap());
+        food.name = "food".to_string();
+        food.amount = Decimal::new(100, 0);
+        food.currency = Some("EUR".to_string());
+        let mut payment_events = PaymentEventStore::new();
+        PaymentEvent::save_to_store(food, &mut payment_events);
+
+        let mut model = model();
+        model.run(&payment_events, Some(&mut store)).unwrap();
+
+        let summary = model.construct_payment_summary(&mut store).unwrap();
+        assert_eq!(Decimal::new(110, 0), summary[&2].total);
+        assert_eq!(
+            Decimal::new(110, 0),
+            model.construct_budget_summary(&store).unwrap()[&1].actual
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn run__totals_tagged_expenses_for_tag_summary_and_tag_budgets() {
+        use crate::schema::budget::Budget;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        Budget::save_to_store(
+            Budget {
+                id: None,
+                name: "household".to_string(),
+                tag_id: Some(1),
+                limit: Decimal::new(500, 0),
+                ..Default::default()
+            },
+            &mut store.budgets,
+        );
+
+        let mut food = payment_event(NaiveDate::from_ymd_opt(2023, 2, 14).unwrap());
+        food.name = "food".to_string();
+        food.amount = Decimal::new(100, 0);
+        let mut toys = payment_event(NaiveDate::from_ymd_opt(2023, 2, 15).unwrap());
+        toys.name = "toys".to_string();
+        toys.amount = Decimal::new(40, 0);
+        toys.tags = vec!["household".to_string(), "kids".to_string()];
+        let mut payment_events = PaymentEventStore::new();
+        PaymentEvent::save_to_store(food, &mut payment_events);
+        PaymentEvent::save_to_store(toys, &mut payment_events);
+
+        let mut model = model();
+        model.run(&payment_events, Some(&mut store)).unwrap();
+
+        let tag_summary = model.construct_tag_summary(&store).unwrap();
+        assert_eq!(Decimal::new(140, 0), tag_summary[&1].expense_total); // household
+        assert_eq!(Decimal::new(40, 0), tag_summary[&3].expense_total); // kids
+        assert_eq!(
+            Decimal::new(140, 0),
+            model.construct_budget_summary(&store).unwrap()[&2].actual
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn record_payment_events_in_month__counts_events_that_match_no_day() {
+        let mut model = model();
+        model.month.days = MonthModel::construct_days(2023, MK::Feb);
+
+        let mut payment_events = PaymentEventStore::new();
+        PaymentEvent::save_to_store(
+            payment_event(NaiveDate::from_ymd_opt(2023, 3, 1).unwrap()),
+            &mut payment_events,
+        );
+
+        assert_eq!(1, model.record_payment_events_in_month(&payment_events));
     }
 
     #[test]
@@ -292,4 +755,16 @@ mod month_model_spec {
         let dec_days = MonthModel::construct_days(2023, MK::Dec);
         assert_eq!(31, dec_days.len());
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn construct_days__returns_29_days_for_february_of_leap_year() {
+        let feb_days = MonthModel::construct_days(2024, MK::Feb);
+        assert_eq!(29, feb_days.len());
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            feb_days[&29].date
+        );
+        assert_eq!(28, MonthModel::construct_days(2100, MK::Feb).len());
+    }
 }
@@ -1,15 +1,55 @@
-use crate::schema::account_balance::{AccountBalance, AccountBalanceStore};
+use crate::calendar::month::Month;
+use crate::calendar::year_month::YearMonth as YM;
+use crate::schema::account_balance::AccountBalanceStore;
+use crate::schema::fx_rate::DEFAULT_CURRENCY;
+use crate::storage::indexed_store::IndexedStore;
+use crate::traits::csv_index::CsvIndex;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Account {
     pub id: Option<usize>,
     pub name: String,
+    #[serde(default)]
+    pub kind: AccountKind,
+    // liability only
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub credit_limit: Option<Decimal>,
+    #[serde(default)]
+    pub statement_closing_day: Option<u32>,
+    #[serde(default)]
+    pub payment_due_day: Option<u32>,
+    #[serde(default)]
+    pub funding_account_id: Option<usize>, // account that pays the statement balance
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub interest_rate: Option<Decimal>, // annual, 0.045 => 4.5%
+    #[serde(default)]
+    pub compounding: Compounding,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub min_balance: Option<Decimal>, // asset only, debits ending below it are alerted
+    #[serde(default)]
+    pub currency: Option<String>, // ISO 4217 code, None => DEFAULT_CURRENCY
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKind {
+    #[default]
+    Asset, // checking, savings
+    Liability, // credit card, balance below 0 is money owed
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Compounding {
+    #[default]
+    None,
+    Daily,   // interest_rate / 365 posted at the end of each day
+    Monthly, // interest_rate / 12 posted at the end of the last day of the month
 }
 
 impl CsvRecord<Account> for Account {
@@ -24,63 +64,103 @@ impl CsvRecord<Account> for Account {
 
     fn clone_record(&self) -> Account {
         Account {
-            id: self.id,
             name: self.name.clone(),
+            currency: self.currency.clone(),
+            ..*self
         }
     }
 }
 
 impl CsvStore<Account> for Account {}
 
-pub type AccountStore = BTreeMap<usize, Account>;
+pub type AccountStore = IndexedStore<Account>;
+
+impl CsvIndex for Account {
+    type Key = String;
+
+    fn index_key(&self) -> String {
+        self.name.clone()
+    }
+}
 
 impl Account {
     // TODO: maybe avoid clone, return id
     pub fn by_name<'a, 'b: 'a>(name: &'a str, store: &'b AccountStore) -> Option<Account> {
-        let mut account: Option<Account> = None;
-        for (_id, acc) in store.iter() {
-            if acc.name == name {
-                account = Some(acc.clone_record());
-                break;
-            }
-        }
-        account
+        store.first_id(name).map(|id| store[&id].clone_record())
     }
 
+    // oldest first
+    #[allow(unused)]
     pub fn account_balance_ids(&self, store: &mut AccountBalanceStore) -> Vec<usize> {
-        let mut balance_ids: Vec<usize> = vec![];
-        for (id, acc_bal) in store.iter() {
-            if acc_bal.account_id == self.id.unwrap() {
-                balance_ids.push(*id)
-            }
+        store.reported_ids(self.id.unwrap(), ..).collect()
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)
+    }
+
+    pub fn is_liability(&self) -> bool {
+        self.kind == AccountKind::Liability
+    }
+
+    // compounding periods per year when interest accrues on `last_day_of_month`, None if it does not
+    pub fn interest_periods_due(&self, last_day_of_month: bool) -> Option<u32> {
+        self.interest_rate?;
+        match self.compounding {
+            Compounding::Daily => Some(365),
+            Compounding::Monthly if last_day_of_month => Some(12),
+            _ => None,
         }
+    }
 
-        balance_ids
+    // most recent statement closing date strictly before `before`
+    pub fn last_statement_close(&self, before: NaiveDate) -> Option<NaiveDate> {
+        let closing_day = self.statement_closing_day?;
+        let this_month = Account::clamped_date(before.year(), before.month(), closing_day);
+        if this_month < before {
+            return Some(this_month);
+        }
+        let prev = before.checked_sub_months(Months::new(1))?;
+        Some(Account::clamped_date(
+            prev.year(),
+            prev.month(),
+            closing_day,
+        ))
     }
 
-    // last_saved_balance
-    pub fn current_balance(&self, store: &mut AccountBalanceStore) -> Decimal {
-        let mut curr_balance: Option<AccountBalance> = None;
-        for id in self.account_balance_ids(store).iter() {
-            if let Entry::Occupied(acc_bal) = store.entry(*id) {
-                // entry exists
-                match curr_balance {
-                    None => {
-                        // set first
-                        curr_balance = Some(acc_bal.get().clone_record());
-                    }
-                    Some(last_acc_bal_so_far) => {
-                        if acc_bal.get().reported_at > last_acc_bal_so_far.reported_at {
-                            curr_balance = Some(acc_bal.get().clone_record())
-                        }
-                    }
-                }
-            }
+    // day 31 in a 30 day month => day 30
+    pub fn clamped_date(year: i32, month: u32, day: u32) -> NaiveDate {
+        let length = YM::new(year, Month::key_from_id(month)).length();
+        NaiveDate::from_ymd_opt(year, month, day.clamp(1, length)).unwrap()
+    }
+
+    // balance as last reported strictly before `at`
+    pub fn balance_at(&self, at: NaiveDateTime, store: &mut AccountBalanceStore) -> Decimal {
+        match store.reported_ids(self.id.unwrap(), ..at).next_back() {
+            None => Decimal::new(00, 1),
+            Some(id) => store[&id].amount,
         }
+    }
 
-        match curr_balance {
+    // lowest balance reported in [start, end)
+    pub fn min_balance_between(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        store: &mut AccountBalanceStore,
+    ) -> Option<Decimal> {
+        store
+            .reported_ids(self.id.unwrap(), start..end)
+            .map(|id| store[&id].amount)
+            .min()
+    }
+
+    // of two reported at the same moment the later saved one wins
+    // each payment builds on the balance the one before it saved, even at the same timestamp
+    pub fn current_balance(&self, store: &mut AccountBalanceStore) -> Decimal {
+        match store.reported_ids(self.id.unwrap(), ..).next_back() {
             None => Decimal::new(00, 1),
-            Some(bal) => bal.amount,
+            Some(id) => store[&id].amount,
         }
     }
 }
@@ -88,6 +168,7 @@ impl Account {
 #[cfg(test)]
 mod account_spec {
     use super::*;
+    use crate::schema::account_balance::AccountBalance;
     use crate::storage::store::Store;
     use crate::test::spec::Spec;
 
@@ -107,7 +188,7 @@ mod account_spec {
         let mut store = Store::new();
         Spec::init(&mut store);
 
-        let account = Account::by_name("piggybank", &mut store.accounts).unwrap();
+        let account = Account::by_name("piggybank", &store.accounts).unwrap();
         assert_eq!(1, account.id.unwrap())
     }
 
@@ -117,20 +198,152 @@ mod account_spec {
         let mut store = Store::new();
         Spec::init(&mut store);
 
-        let account = Account::by_name("piggybank", &mut store.accounts).unwrap();
+        let account = Account::by_name("piggybank", &store.accounts).unwrap();
         assert_eq!(
             Decimal::new(2000, 1),
             account.current_balance(&mut store.account_balances)
         );
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn current_balance__returns_the_later_saved_of_balances_reported_at_the_same_moment() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let account = Account::by_name("piggybank", &store.accounts).unwrap();
+        let noon =
+            NaiveDateTime::parse_from_str("2023-06-15 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        for amount in [1200, 1000] {
+            AccountBalance::save_to_store(
+                AccountBalance {
+                    id: None,
+                    account_id: 1,
+                    reported_at: noon,
+                    amount: Decimal::new(amount, 0),
+                },
+                &mut store.account_balances,
+            );
+        }
+        assert_eq!(
+            Decimal::new(1000, 0),
+            account.current_balance(&mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn min_balance_between__returns_lowest_balance_reported_in_range() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let account = Account::by_name("swearjar", &store.accounts).unwrap();
+        let start =
+            NaiveDateTime::parse_from_str("2023-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let end =
+            NaiveDateTime::parse_from_str("2023-02-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            Some(Decimal::new(300, 0)),
+            account.min_balance_between(start, end, &mut store.account_balances)
+        );
+        assert_eq!(
+            None,
+            account.min_balance_between(end, end, &mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn balance_at__returns_last_balance_reported_before_datetime() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let account = Account::by_name("piggybank", &store.accounts).unwrap();
+        let at = NaiveDateTime::parse_from_str("2023-01-02 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            Decimal::new(100, 0),
+            account.balance_at(at, &mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn last_statement_close__returns_closing_date_before_due_date() {
+        let card = Account {
+            id: Some(3),
+            name: "Card".to_string(),
+            kind: AccountKind::Liability,
+            statement_closing_day: Some(25),
+            payment_due_day: Some(20),
+            ..Default::default()
+        };
+
+        // due before closing day => previous month's statement
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 2, 25),
+            card.last_statement_close(NaiveDate::from_ymd_opt(2023, 3, 20).unwrap())
+        );
+        // due after closing day => this month's statement
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 3, 25),
+            card.last_statement_close(NaiveDate::from_ymd_opt(2023, 3, 28).unwrap())
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn last_statement_close__clamps_closing_day_to_month_length() {
+        let card = Account {
+            statement_closing_day: Some(31),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 2, 29),
+            card.last_statement_close(NaiveDate::from_ymd_opt(2024, 3, 10).unwrap())
+        );
+        assert_eq!(
+            None,
+            Account::default().last_statement_close(NaiveDate::MIN)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn interest_periods_due__follows_compounding_schedule() {
+        let mut account = Account {
+            interest_rate: Some(Decimal::new(45, 3)),
+            compounding: Compounding::Monthly,
+            ..Default::default()
+        };
+        assert_eq!(None, account.interest_periods_due(false));
+        assert_eq!(Some(12), account.interest_periods_due(true));
+
+        account.compounding = Compounding::Daily;
+        assert_eq!(Some(365), account.interest_periods_due(false));
+
+        account.interest_rate = None;
+        assert_eq!(None, account.interest_periods_due(true));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn init_store__defaults_kind_to_asset_when_column_is_missing() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let account = Account::by_name("piggybank", &store.accounts).unwrap();
+        assert_eq!(AccountKind::Asset, account.kind);
+        assert_eq!(None, account.credit_limit);
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn account_balance_ids__returns_vec_of_ids() {
         let mut store = Store::new();
         Spec::init(&mut store);
 
-        let account = Account::by_name("piggybank", &mut store.accounts).unwrap();
+        let account = Account::by_name("piggybank", &store.accounts).unwrap();
         let account_balance_ids = account.account_balance_ids(&mut store.account_balances);
         assert_eq!(vec![1, 2], account_balance_ids);
 
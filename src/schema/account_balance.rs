@@ -1,9 +1,12 @@
+use crate::storage::indexed_store::IndexedStore;
+use crate::traits::csv_index::CsvIndex;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 pub struct AccountBalance {
@@ -14,7 +17,36 @@ pub struct AccountBalance {
     pub amount: Decimal,
 }
 
-pub type AccountBalanceStore = BTreeMap<usize, AccountBalance>;
+pub type AccountBalanceStore = IndexedStore<AccountBalance>;
+
+// by account, then by when the balance was reported
+impl CsvIndex for AccountBalance {
+    type Key = (usize, NaiveDateTime);
+
+    fn index_key(&self) -> (usize, NaiveDateTime) {
+        (self.account_id, self.reported_at)
+    }
+}
+
+impl AccountBalanceStore {
+    // ids of account_id's balances reported within range, oldest first
+    // of balances reported at the same moment the lower id comes first
+    pub fn reported_ids(
+        &self,
+        account_id: usize,
+        range: impl RangeBounds<NaiveDateTime>,
+    ) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        let key = |bound: Bound<&NaiveDateTime>, unbounded: NaiveDateTime| match bound {
+            Included(at) => Included((account_id, *at)),
+            Excluded(at) => Excluded((account_id, *at)),
+            Unbounded => Included((account_id, unbounded)),
+        };
+        self.ids_in((
+            key(range.start_bound(), NaiveDateTime::MIN),
+            key(range.end_bound(), NaiveDateTime::MAX),
+        ))
+    }
+}
 
 impl CsvRecord<AccountBalance> for AccountBalance {
     fn id(&self) -> Option<usize> {
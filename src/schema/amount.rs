@@ -5,7 +5,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Amount {
     pub id: Option<usize>,
     #[serde(with = "rust_decimal::serde::float")]
@@ -15,6 +15,8 @@ pub struct Amount {
     pub low: Option<Decimal>,
     #[serde(with = "rust_decimal::serde::float_option")]
     pub high: Option<Decimal>,
+    #[serde(default)]
+    pub currency: Option<String>, // None => the currency of the account paid from or into
 }
 
 impl CsvRecord<Amount> for Amount {
@@ -28,7 +30,7 @@ impl CsvRecord<Amount> for Amount {
     }
 
     fn clone_record(&self) -> Amount {
-        *self
+        self.clone()
     }
 }
 impl CsvStore<Amount> for Amount {}
@@ -38,6 +40,11 @@ pub type AmountStore = BTreeMap<usize, Amount>;
 impl Amount {
     #[allow(unused)]
     pub fn randomize(&self) -> Decimal {
+        self.randomize_with(&mut rand::thread_rng())
+    }
+
+    // pass a seeded rng to reproduce a run
+    pub fn randomize_with<R: Rng>(&self, rng: &mut R) -> Decimal {
         let mut low = Decimal::new(00, 1);
         if let Some(num) = self.low {
             low = num
@@ -47,7 +54,10 @@ impl Amount {
             _ => self.standard * Decimal::new(30, 1), // TODO: 3 is a magic number here
                                                       //   tune logic for useful randomization
         };
-        rand::thread_rng().gen_range(low..high)
+        if low >= high {
+            return low;
+        }
+        rng.gen_range(low..high)
     }
 }
 
@@ -78,4 +88,24 @@ mod amount_spec {
         assert!(randomized < amount.high.unwrap());
         assert!(randomized > amount.low.unwrap());
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn randomize_with__returns_same_sequence_for_same_seed() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let amount = Amount::by_id(3, &mut store.amounts).unwrap();
+        let mut rng_a = StdRng::seed_from_u64(42);
+        let mut rng_b = StdRng::seed_from_u64(42);
+        for _ in 0..5 {
+            assert_eq!(
+                amount.randomize_with(&mut rng_a),
+                amount.randomize_with(&mut rng_b)
+            );
+        }
+    }
 }
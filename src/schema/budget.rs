@@ -0,0 +1,117 @@
+use crate::calendar::month::Month;
+use crate::error::budget_error::BudgetResult;
+use crate::schema::expense::{Expense, ExpenseStore};
+use crate::schema::expense_tag::ExpenseTag;
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use crate::traits::tag_link::TagLink;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// monthly spending cap on an Expense (or a Tag)
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Budget {
+    pub id: Option<usize>,
+    pub name: String,
+    #[serde(default)]
+    pub expense_id: Option<usize>,
+    #[serde(default)]
+    pub expense_name: Option<String>, // used when expense_id is blank, events create expenses by name
+    #[serde(default)]
+    pub tag_id: Option<usize>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub limit: Decimal,
+    #[serde(default)]
+    pub rollover: bool, // carry unused limit into the next month
+}
+
+impl CsvRecord<Budget> for Budget {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> Budget {
+        Budget {
+            name: self.name.clone(),
+            expense_name: self.expense_name.clone(),
+            ..*self
+        }
+    }
+}
+impl CsvStore<Budget> for Budget {}
+
+pub type BudgetStore = BTreeMap<usize, Budget>;
+
+impl Budget {
+    pub fn expense_id(&self, expenses: &ExpenseStore) -> Option<usize> {
+        match (self.expense_id, &self.expense_name) {
+            (Some(expense_id), _) => Some(expense_id),
+            (None, Some(name)) => Expense::by_name(name, expenses).and_then(|exp| exp.id),
+            (None, None) => None,
+        }
+    }
+
+    // amount spent against this budget in month
+    pub fn month_actual(
+        &self,
+        month: &Month,
+        store: &Store,
+        base_currency: &str,
+    ) -> BudgetResult<Decimal> {
+        match (self.expense_id(&store.expenses), self.tag_id) {
+            (Some(expense_id), _) => {
+                Expense::month_total_by_id(expense_id, month, store, base_currency)
+            }
+            (None, Some(tag_id)) => ExpenseTag::tagged_ids(tag_id, &store.expense_tags)
+                .into_iter()
+                .map(|expense_id| {
+                    Expense::month_total_by_id(expense_id, month, store, base_currency)
+                })
+                .sum(),
+            (None, None) => Ok(Decimal::new(0, 0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod budget_spec {
+    use super::*;
+    use crate::storage::store::Store;
+    use crate::test::spec::Spec;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn init_store__reads_budgets_from_csv() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let budget = Budget::by_id(1, &mut store.budgets).unwrap();
+        assert_eq!("food".to_string(), budget.name);
+        assert_eq!(Some(2), budget.expense_id);
+        assert_eq!(Decimal::new(300, 0), budget.limit);
+        assert!(budget.rollover);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn expense_id__falls_back_to_expense_name() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let budget = Budget {
+            id: None,
+            name: "dog".to_string(),
+            expense_name: Some("dog food".to_string()),
+            limit: Decimal::new(50, 0),
+            ..Default::default()
+        };
+        assert_eq!(Some(3), budget.expense_id(&store.expenses));
+    }
+}
@@ -1,13 +1,17 @@
 use crate::calendar::month::Month;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::error_handler::ErrorHandler;
+use crate::schema::fx_rate::FxRate;
 use crate::schema::payment::{Payment, PaymentStore};
+use crate::storage::indexed_store::IndexedStore;
 use crate::storage::store::Store;
+use crate::traits::csv_index::CsvIndex;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Expense {
     pub id: Option<usize>,
     pub active: bool,
@@ -33,23 +37,25 @@ impl CsvRecord<Expense> for Expense {
 }
 impl CsvStore<Expense> for Expense {}
 
-pub type ExpenseStore = BTreeMap<usize, Expense>;
+pub type ExpenseStore = IndexedStore<Expense>;
+
+impl CsvIndex for Expense {
+    type Key = String;
+
+    fn index_key(&self) -> String {
+        self.name.clone()
+    }
+}
 
 impl<'a, 'b: 'a> Expense {
     pub fn by_name(name: &'a str, store: &'b ExpenseStore) -> Option<Expense> {
-        let mut expense: Option<Expense> = None;
-        for (_id, exp) in store.iter() {
-            if exp.name == name {
-                expense = Some(exp.clone_record());
-                break;
-            }
-        }
-        expense
+        store.first_id(name).map(|id| store[&id].clone_record())
     }
 
     pub fn mark_all_inactive(store: &mut ExpenseStore) {
-        for (_id, expense) in store.iter_mut() {
-            expense.active = false;
+        let ids: Vec<usize> = store.keys().copied().collect();
+        for id in ids {
+            store.update(id, |expense| expense.active = false);
         }
     }
 
@@ -60,35 +66,55 @@ impl<'a, 'b: 'a> Expense {
         }
     }
 
-    pub fn total_by_id(id: usize, store: &mut Store) -> Decimal {
+    pub fn total_by_id(id: usize, store: &mut Store, base_currency: &str) -> BudgetResult<Decimal> {
         match Expense::by_id(id, &mut store.expenses) {
-            None => Decimal::new(00, 1),
+            None => Ok(Decimal::new(00, 1)),
             Some(expense) => {
                 let payments = expense.payments(&mut store.payments);
-                Payment::total(payments, &store.amounts)
+                Payment::total(&payments, store, base_currency)
             }
         }
     }
 
-    pub fn month_total_by_id(expense_id: usize, month: &Month) -> Decimal {
+    pub fn month_total_by_id(
+        expense_id: usize,
+        month: &Month,
+        store: &Store,
+        base_currency: &str,
+    ) -> BudgetResult<Decimal> {
         let mut total = Decimal::new(0, 0);
         for (_id, day) in month.days.iter() {
             for (_id, payment) in day.payments.iter() {
                 if payment.expense_id.unwrap() == expense_id {
-                    total += payment.amount_standard;
+                    total += FxRate::convert_amount(
+                        payment.amount_id.unwrap(),
+                        payment.account_id.unwrap(),
+                        payment.payment_completed_at.date(),
+                        base_currency,
+                        store,
+                    )?;
                 }
             }
         }
-        total
+        Ok(total)
     }
 
     pub fn payments(&'a self, store: &'b mut PaymentStore) -> PaymentStore {
-        let mut payments: PaymentStore = BTreeMap::new();
+        let mut payments = PaymentStore::new();
+        // an unsaved expense has no payments
+        let expense_id = match self.id {
+            Some(id) => id,
+            None => {
+                ErrorHandler::log(BudgetError::UnknownExpense {
+                    name: self.name.clone(),
+                    did_you_mean: None,
+                });
+                return payments;
+            }
+        };
         for (id, payment) in store.iter() {
-            if payment.expense_id == self.id.unwrap() {
-                payments
-                    .entry(*id)
-                    .or_insert_with(|| payment.clone_record());
+            if payment.expense_id == expense_id {
+                payments.insert(*id, payment.clone_record());
             }
         }
         payments
@@ -124,7 +150,7 @@ mod expense_spec {
         let mut store = Store::new();
         Spec::init(&mut store);
 
-        let expense = Expense::by_name("mortgage", &mut store.expenses).unwrap();
+        let expense = Expense::by_name("mortgage", &store.expenses).unwrap();
         assert_eq!(1, expense.id.unwrap());
     }
 
@@ -167,6 +193,17 @@ mod expense_spec {
         assert_eq!(second_payment.amount_id, 1);
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn payments__returns_empty_store_for_an_unsaved_expense() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut expense = Expense::by_id(1, &mut store.expenses).unwrap();
+        expense.id = None;
+        assert!(expense.payments(&mut store.payments).is_empty());
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn last_payment__returns_most_recent_payment() {
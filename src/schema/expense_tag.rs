@@ -0,0 +1,82 @@
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use crate::traits::tag_link::TagLink;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// join table, an Expense may have many Tags and a Tag many Expenses
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct ExpenseTag {
+    pub id: Option<usize>,
+    pub expense_id: usize,
+    pub tag_id: usize,
+}
+
+impl CsvRecord<ExpenseTag> for ExpenseTag {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> ExpenseTag {
+        *self
+    }
+}
+impl CsvStore<ExpenseTag> for ExpenseTag {}
+
+pub type ExpenseTagStore = BTreeMap<usize, ExpenseTag>;
+
+impl TagLink<ExpenseTag> for ExpenseTag {
+    fn new_link(record_id: usize, tag_id: usize) -> ExpenseTag {
+        ExpenseTag {
+            id: None,
+            expense_id: record_id,
+            tag_id,
+        }
+    }
+
+    fn record_id(&self) -> usize {
+        self.expense_id
+    }
+
+    fn tag_id(&self) -> usize {
+        self.tag_id
+    }
+}
+
+#[cfg(test)]
+mod expense_tag_spec {
+    use super::*;
+    use crate::storage::store::Store;
+    use crate::test::spec::Spec;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn tagged_ids__returns_expenses_with_tag() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        assert_eq!(vec![2, 3], ExpenseTag::tagged_ids(1, &store.expense_tags));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn tag_record__creates_tags_and_skips_existing_links() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        ExpenseTag::tag_record(
+            2,
+            &["household".to_string(), "groceries".to_string()],
+            &mut store.tags,
+            &mut store.expense_tags,
+        );
+        assert_eq!(3, store.tags.len());
+        assert_eq!(3, store.expense_tags.len());
+        assert_eq!(vec![2], ExpenseTag::tagged_ids(3, &store.expense_tags));
+    }
+}
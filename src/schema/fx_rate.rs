@@ -0,0 +1,169 @@
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// accounts and amounts without a currency
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+// "on 2024-01-02, 1 EUR = 1.09 USD", read from init/fx_rates.csv
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FxRate {
+    pub id: Option<usize>,
+    pub date: NaiveDate,
+    pub pair: String, // BASE/QUOTE, e.g. EUR/USD
+    #[serde(with = "rust_decimal::serde::float")]
+    pub rate: Decimal, // units of QUOTE per unit of BASE
+}
+
+pub type FxRateStore = BTreeMap<usize, FxRate>;
+
+impl CsvRecord<FxRate> for FxRate {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> FxRate {
+        self.clone()
+    }
+}
+
+impl CsvStore<FxRate> for FxRate {}
+
+impl FxRate {
+    // amount in `from` as `to` at the latest rate for the pair, either way round, on or before date
+    pub fn convert(
+        amount: Decimal,
+        from: &str,
+        to: &str,
+        date: NaiveDate,
+        store: &FxRateStore,
+    ) -> BudgetResult<Decimal> {
+        if from == to {
+            return Ok(amount);
+        }
+        let direct = format!("{from}/{to}");
+        let inverse = format!("{to}/{from}");
+        let latest = store
+            .values()
+            .filter(|fx_rate| fx_rate.date <= date && !fx_rate.rate.is_zero())
+            .filter(|fx_rate| fx_rate.pair == direct || fx_rate.pair == inverse)
+            // of two on the same date the later id wins
+            .max_by_key(|fx_rate| (fx_rate.date, fx_rate.id));
+        match latest {
+            Some(fx_rate) if fx_rate.pair == direct => Ok((amount * fx_rate.rate).round_dp(2)),
+            Some(fx_rate) => Ok((amount / fx_rate.rate).round_dp(2)),
+            None => Err(BudgetError::MissingFxRate {
+                from: from.to_string(),
+                to: to.to_string(),
+                date,
+            }),
+        }
+    }
+
+    // the Amount paid from or into account_id as `to` on date
+    // amounts without a currency are in the account's
+    pub fn convert_amount(
+        amount_id: usize,
+        account_id: usize,
+        date: NaiveDate,
+        to: &str,
+        store: &Store,
+    ) -> BudgetResult<Decimal> {
+        let Some(amount) = store.amounts.get(&amount_id) else {
+            return Ok(Decimal::new(0, 0));
+        };
+        let account_currency = store
+            .accounts
+            .get(&account_id)
+            .map_or(DEFAULT_CURRENCY, |account| account.currency());
+        FxRate::convert(
+            amount.standard,
+            amount.currency.as_deref().unwrap_or(account_currency),
+            to,
+            date,
+            &store.fx_rates,
+        )
+    }
+}
+
+#[cfg(test)]
+mod fx_rate_spec {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn store() -> FxRateStore {
+        let mut store = FxRateStore::new();
+        for (month, rate) in [(1, 110), (3, 120)] {
+            FxRate::save_to_store(
+                FxRate {
+                    id: None,
+                    date: date(month, 1),
+                    pair: "EUR/USD".to_string(),
+                    rate: Decimal::new(rate, 2),
+                },
+                &mut store,
+            );
+        }
+        store
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn convert__uses_the_latest_rate_on_or_before_the_date() {
+        let amount = Decimal::new(100, 0);
+        assert_eq!(
+            Decimal::new(11000, 2),
+            FxRate::convert(amount, "EUR", "USD", date(2, 29), &store()).unwrap()
+        );
+        assert_eq!(
+            Decimal::new(12000, 2),
+            FxRate::convert(amount, "EUR", "USD", date(3, 1), &store()).unwrap()
+        );
+        assert_eq!(
+            amount,
+            FxRate::convert(amount, "EUR", "EUR", date(3, 1), &store()).unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn convert__inverts_the_pair_when_converting_the_other_way() {
+        // 100 / 1.20
+        assert_eq!(
+            Decimal::new(8333, 2),
+            FxRate::convert(Decimal::new(100, 0), "USD", "EUR", date(3, 1), &store()).unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn convert__returns_err_without_a_rate() {
+        assert!(matches!(
+            FxRate::convert(
+                Decimal::new(100, 0),
+                "EUR",
+                "USD",
+                date(1, 1) - chrono::Days::new(1),
+                &store()
+            ),
+            Err(BudgetError::MissingFxRate { .. })
+        ));
+        assert!(matches!(
+            FxRate::convert(Decimal::new(100, 0), "GBP", "USD", date(3, 1), &store()),
+            Err(BudgetError::MissingFxRate { .. })
+        ));
+    }
+}
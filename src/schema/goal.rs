@@ -0,0 +1,227 @@
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::suggestion::did_you_mean;
+use crate::schema::account::Account;
+use crate::schema::expense_tag::ExpenseTag;
+use crate::schema::tag::Tag;
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use crate::traits::tag_link::TagLink;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// a savings target, read from init/goals.csv
+// tracked by the balance of account_name, or by the total paid to Expenses tagged tag
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Goal {
+    pub id: Option<usize>,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub target_amount: Decimal,
+    pub target_date: NaiveDate,
+    #[serde(default)]
+    pub account_name: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+pub type GoalStore = BTreeMap<usize, Goal>;
+
+// amount toward the goal as of a start, then every change until an end
+pub type GoalProgress = (Decimal, Vec<(NaiveDateTime, Decimal)>);
+
+pub enum GoalLink<'a> {
+    Account(&'a str),
+    Tag(&'a str),
+}
+
+impl CsvRecord<Goal> for Goal {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> Goal {
+        self.clone()
+    }
+}
+
+impl CsvStore<Goal> for Goal {}
+
+impl Goal {
+    pub fn link(&self) -> BudgetResult<GoalLink<'_>> {
+        match (&self.account_name, &self.tag) {
+            (Some(account_name), None) => Ok(GoalLink::Account(account_name)),
+            (None, Some(tag)) => Ok(GoalLink::Tag(tag)),
+            _ => Err(BudgetError::Other {
+                message: format!(
+                    "goal {} {:?}: set one of account_name or tag",
+                    self.id.unwrap_or_default(),
+                    self.name
+                ),
+            }),
+        }
+    }
+
+    // the amount as last recorded at or before start, then each change in (start, end)
+    pub fn progress(
+        &self,
+        store: &Store,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> BudgetResult<GoalProgress> {
+        let mut points: Vec<(NaiveDateTime, Decimal)> = match self.link()? {
+            GoalLink::Account(name) => {
+                let account = Account::by_name(name, &store.accounts).ok_or_else(|| {
+                    BudgetError::UnknownAccount {
+                        name: name.to_string(),
+                        did_you_mean: did_you_mean(
+                            name,
+                            store.accounts.values().map(|account| account.name.as_str()),
+                        ),
+                    }
+                })?;
+                store
+                    .account_balances
+                    .reported_ids(account.id.unwrap(), ..)
+                    .map(|id| store.account_balances[&id])
+                    .map(|balance| (balance.reported_at, balance.amount))
+                    .collect()
+            }
+            GoalLink::Tag(content) => {
+                let tag =
+                    Tag::by_content(content, &store.tags).ok_or_else(|| BudgetError::Other {
+                        message: format!("goal {:?}: tag {content:?} does not exist", self.name),
+                    })?;
+                let expense_ids: BTreeSet<usize> =
+                    ExpenseTag::tagged_ids(tag.id.unwrap(), &store.expense_tags)
+                        .into_iter()
+                        .collect();
+                let mut payments: Vec<(NaiveDateTime, Decimal)> = store
+                    .payments
+                    .values()
+                    .filter(|payment| expense_ids.contains(&payment.expense_id))
+                    .map(|payment| {
+                        (
+                            payment.completed_at,
+                            payment.standard_amount(&store.amounts).unwrap_or_default(),
+                        )
+                    })
+                    .collect();
+                payments.sort_by_key(|(completed_at, _)| *completed_at);
+                // running total paid so far
+                let mut total = Decimal::new(0, 0);
+                payments
+                    .into_iter()
+                    .map(|(completed_at, amount)| {
+                        total += amount;
+                        (completed_at, total)
+                    })
+                    .collect()
+            }
+        };
+        points.sort_by_key(|(at, _)| *at);
+
+        let opening = points
+            .iter()
+            .rev()
+            .find(|(at, _)| *at <= start)
+            .map_or(Decimal::new(0, 0), |(_, amount)| *amount);
+        let history = points
+            .into_iter()
+            .filter(|(at, _)| start < *at && *at < end)
+            .collect();
+        Ok((opening, history))
+    }
+}
+
+#[cfg(test)]
+mod goal_spec {
+    use super::*;
+    use crate::schema::account_balance::AccountBalance;
+    use crate::test::spec::Spec;
+    use chrono::NaiveDate;
+
+    fn at(month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn goal(account_name: Option<&str>, tag: Option<&str>) -> Goal {
+        Goal {
+            id: Some(1),
+            name: "Emergency Fund".to_string(),
+            target_amount: Decimal::new(1000, 0),
+            target_date: NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+            account_name: account_name.map(String::from),
+            tag: tag.map(String::from),
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn link__requires_exactly_one_of_account_name_or_tag() {
+        assert!(matches!(
+            goal(Some("piggybank"), None).link(),
+            Ok(GoalLink::Account("piggybank"))
+        ));
+        assert!(matches!(
+            goal(None, None).link(),
+            Err(BudgetError::Other { .. })
+        ));
+        assert!(matches!(
+            goal(Some("piggybank"), Some("savings")).link(),
+            Err(BudgetError::Other { .. })
+        ));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn progress__returns_balance_at_start_and_changes_before_end() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let account_id = Account::by_name("piggybank", &store.accounts)
+            .unwrap()
+            .id
+            .unwrap();
+        for (reported_at, amount) in [(at(3, 1), 100), (at(3, 15), 250), (at(5, 1), 400)] {
+            AccountBalance::save_to_store(
+                AccountBalance {
+                    id: None,
+                    account_id,
+                    reported_at,
+                    amount: Decimal::new(amount, 0),
+                },
+                &mut store.account_balances,
+            );
+        }
+
+        let (opening, history) = goal(Some("piggybank"), None)
+            .progress(&store, at(3, 1), at(5, 1))
+            .unwrap();
+        assert_eq!(Decimal::new(100, 0), opening);
+        assert_eq!(vec![(at(3, 15), Decimal::new(250, 0))], history);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn progress__returns_err_for_unknown_account() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        assert!(matches!(
+            goal(Some("piggybnak"), None).progress(&store, at(3, 1), at(5, 1)),
+            Err(BudgetError::UnknownAccount {
+                did_you_mean: Some(_),
+                ..
+            })
+        ));
+    }
+}
@@ -1,14 +1,17 @@
 use crate::calendar::month::Month;
+use crate::error::budget_error::{BudgetError, BudgetResult};
 use crate::error::error_handler::ErrorHandler;
+use crate::schema::fx_rate::FxRate;
 use crate::schema::payment_received::{PaymentReceived, PaymentReceivedStore};
+use crate::storage::indexed_store::IndexedStore;
 use crate::storage::store::Store;
+use crate::traits::csv_index::CsvIndex;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Income {
     pub id: Option<usize>,
     pub active: bool,
@@ -34,18 +37,19 @@ impl CsvRecord<Income> for Income {
 }
 impl CsvStore<Income> for Income {}
 
-pub type IncomeStore = BTreeMap<usize, Income>;
+pub type IncomeStore = IndexedStore<Income>;
+
+impl CsvIndex for Income {
+    type Key = String;
+
+    fn index_key(&self) -> String {
+        self.name.clone()
+    }
+}
 
 impl<'a, 'b: 'a> Income {
     pub fn by_name(name: &'a str, store: &'b IncomeStore) -> Option<Income> {
-        let mut income: Option<Income> = None;
-        for (_id, inc) in store.iter() {
-            if inc.name == name {
-                income = Some(inc.clone_record());
-                break;
-            }
-        }
-        income
+        store.first_id(name).map(|id| store[&id].clone_record())
     }
 
     pub fn name_by_id(id: usize, store: &mut IncomeStore) -> String {
@@ -55,49 +59,63 @@ impl<'a, 'b: 'a> Income {
         }
     }
 
-    pub fn total_by_id(id: usize, store: &mut Store) -> Decimal {
+    pub fn total_by_id(id: usize, store: &mut Store, base_currency: &str) -> BudgetResult<Decimal> {
         match Income::by_id(id, &mut store.incomes) {
-            None => Decimal::new(00, 1),
+            None => Ok(Decimal::new(00, 1)),
             Some(income) => {
                 let payments_rec = income.payments_received(&store.payments_received);
-                PaymentReceived::total(payments_rec, &store.amounts)
+                PaymentReceived::total(&payments_rec, store, base_currency)
             }
         }
     }
 
-    pub fn month_total_by_id(income_id: usize, month: &Month) -> Decimal {
+    pub fn month_total_by_id(
+        income_id: usize,
+        month: &Month,
+        store: &Store,
+        base_currency: &str,
+    ) -> BudgetResult<Decimal> {
         let mut total = Decimal::new(0, 0);
         for (_id, day) in month.days.iter() {
             for (_id, payment) in day.payments_received.iter() {
                 if payment.income_id.unwrap() == income_id {
-                    total += payment.amount_standard;
+                    total += FxRate::convert_amount(
+                        payment.amount_id.unwrap(),
+                        payment.account_id.unwrap(),
+                        payment.payment_received_completed_at.date(),
+                        base_currency,
+                        store,
+                    )?;
                 }
             }
         }
-        total
+        Ok(total)
     }
 
     pub fn mark_all_inactive(store: &mut IncomeStore) {
-        for (_id, income) in store.iter_mut() {
-            income.active = false;
+        let ids: Vec<usize> = store.keys().copied().collect();
+        for id in ids {
+            store.update(id, |income| income.active = false);
         }
     }
 
     pub fn payments_received(&'a self, store: &'b PaymentReceivedStore) -> PaymentReceivedStore {
-        if self.id.is_none() {
-            ErrorHandler::log(From::from(format!(
-                "Income {:?} does not exist in main_store.",
-                self.name
-            )))
-        }
-
-        let mut payments_received: PaymentReceivedStore = BTreeMap::new();
+        let mut payments_received = PaymentReceivedStore::new();
+        // an unsaved income has no payments received
+        let income_id = match self.id {
+            Some(id) => id,
+            None => {
+                ErrorHandler::log(BudgetError::UnknownIncome {
+                    name: self.name.clone(),
+                    did_you_mean: None,
+                });
+                return payments_received;
+            }
+        };
         for (id, payment_received) in store.iter() {
-            if payment_received.income_id == self.id.unwrap() {
+            if payment_received.income_id == income_id {
                 // TODO: handle error
-                payments_received
-                    .entry(*id)
-                    .or_insert_with(|| payment_received.clone_record());
+                payments_received.insert(*id, payment_received.clone_record());
             }
         }
 
@@ -137,7 +155,7 @@ mod income_spec {
         let mut store = Store::new();
         Spec::init(&mut store);
 
-        let income = Income::by_name("spaceman", &mut store.incomes).unwrap();
+        let income = Income::by_name("spaceman", &store.incomes).unwrap();
         assert_eq!(2, income.id.unwrap());
     }
 
@@ -170,6 +188,19 @@ mod income_spec {
         assert_eq!(second_payment_received.amount_id, 2);
     }
 
+    #[test]
+    #[allow(non_snake_case)]
+    fn payments_received__returns_empty_store_for_an_unsaved_income() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        let mut income = Income::by_id(1, &mut store.incomes).unwrap();
+        income.id = None;
+        assert!(income
+            .payments_received(&store.payments_received)
+            .is_empty());
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn last_payment_received__returns_most_recent_payment() {
@@ -179,10 +210,7 @@ mod income_spec {
         let income = Income::by_id(1, &mut store.incomes).unwrap();
         let payments_received = income.payments_received(&store.payments_received);
         let most_recent_payment_received: PaymentReceived = payments_received[&3]; // by construction
-        assert_eq!(
-            payments_received[&3].completed_at > payments_received[&1].completed_at,
-            true
-        );
+        assert!(payments_received[&3].completed_at > payments_received[&1].completed_at);
 
         let res: PaymentReceived = income
             .last_payment_received(&store.payments_received)
@@ -0,0 +1,82 @@
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use crate::traits::tag_link::TagLink;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// join table, an Income may have many Tags and a Tag many Incomes
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct IncomeTag {
+    pub id: Option<usize>,
+    pub income_id: usize,
+    pub tag_id: usize,
+}
+
+impl CsvRecord<IncomeTag> for IncomeTag {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> IncomeTag {
+        *self
+    }
+}
+impl CsvStore<IncomeTag> for IncomeTag {}
+
+pub type IncomeTagStore = BTreeMap<usize, IncomeTag>;
+
+impl TagLink<IncomeTag> for IncomeTag {
+    fn new_link(record_id: usize, tag_id: usize) -> IncomeTag {
+        IncomeTag {
+            id: None,
+            income_id: record_id,
+            tag_id,
+        }
+    }
+
+    fn record_id(&self) -> usize {
+        self.income_id
+    }
+
+    fn tag_id(&self) -> usize {
+        self.tag_id
+    }
+}
+
+#[cfg(test)]
+mod income_tag_spec {
+    use super::*;
+    use crate::storage::store::Store;
+    use crate::test::spec::Spec;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn tagged_ids__returns_incomes_with_tag() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        assert_eq!(vec![1, 2], IncomeTag::tagged_ids(2, &store.income_tags));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn tag_record__creates_tags_and_skips_existing_links() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        IncomeTag::tag_record(
+            1,
+            &["work".to_string(), "bonus".to_string()],
+            &mut store.tags,
+            &mut store.income_tags,
+        );
+        assert_eq!(3, store.tags.len());
+        assert_eq!(3, store.income_tags.len());
+        assert_eq!(vec![1], IncomeTag::tagged_ids(3, &store.income_tags));
+    }
+}
@@ -1,8 +1,14 @@
 pub mod account;
 pub mod account_balance;
 pub mod amount;
+pub mod budget;
 pub mod expense;
+pub mod expense_tag;
+pub mod fx_rate;
+pub mod goal;
 pub mod income;
+pub mod income_tag;
+pub mod opening_balance;
 pub mod payment;
 pub mod payment_received;
 pub mod recurrance;
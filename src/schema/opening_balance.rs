@@ -0,0 +1,315 @@
+use crate::calendar::year_month::YearMonth as YM;
+use crate::error::budget_error::{BudgetError, BudgetResult};
+use crate::error::error_handler::ErrorHandler;
+use crate::error::suggestion::did_you_mean;
+use crate::schema::account::Account;
+use crate::schema::account_balance::AccountBalance;
+use crate::storage::store::Store;
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound::{Excluded, Unbounded};
+
+// "as of 2023-03-01, Big Bank = X", read from init/opening_balances.csv
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpeningBalance {
+    pub id: Option<usize>,
+    pub year_month: String, // YYYY-MM, the balance at the start of the month
+    pub account_name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+}
+
+pub type OpeningBalanceStore = BTreeMap<usize, OpeningBalance>;
+
+impl CsvRecord<OpeningBalance> for OpeningBalance {
+    fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn set_id(&mut self, new_id: usize) -> Option<usize> {
+        self.id = Some(new_id);
+        self.id
+    }
+
+    fn clone_record(&self) -> OpeningBalance {
+        self.clone()
+    }
+}
+
+impl CsvStore<OpeningBalance> for OpeningBalance {}
+
+impl OpeningBalance {
+    pub fn parsed_year_month(&self) -> BudgetResult<YM> {
+        YM::try_parse(&self.year_month).map_err(|message| BudgetError::Calendar {
+            message: format!(
+                "opening balance {} for {:?}: {message}",
+                self.id.unwrap_or_default(),
+                self.account_name
+            ),
+        })
+    }
+
+    // months that have a snapshot
+    pub fn year_months(opening_balances: &OpeningBalanceStore) -> BudgetResult<BTreeSet<YM>> {
+        opening_balances
+            .values()
+            .map(|opening_balance| opening_balance.parsed_year_month())
+            .collect()
+    }
+
+    // records the snapshot for start as each account's balance at the start of the month
+    // the snapshot is authoritative, balances the account reported after it are dropped
+    // an account that does not exist is an error, the snapshot never creates one
+    // returns the number of balances applied
+    pub fn apply(
+        opening_balances: &OpeningBalanceStore,
+        start: YM,
+        store: &mut Store,
+    ) -> BudgetResult<usize> {
+        let reported_at = start.start_of_month().and_hms_opt(0, 0, 0).unwrap();
+        let mut applied: usize = 0;
+        for opening_balance in opening_balances.values() {
+            if opening_balance.parsed_year_month()? != start {
+                continue;
+            }
+            let account_id = match Account::by_name(&opening_balance.account_name, &store.accounts)
+            {
+                Some(account) => account.id.unwrap(),
+                None => {
+                    return Err(BudgetError::UnknownAccount {
+                        name: opening_balance.account_name.clone(),
+                        did_you_mean: did_you_mean(
+                            &opening_balance.account_name,
+                            store.accounts.values().map(|account| account.name.as_str()),
+                        ),
+                    })
+                }
+            };
+
+            let later_ids: Vec<usize> = store
+                .account_balances
+                .reported_ids(account_id, (Excluded(reported_at), Unbounded))
+                .collect();
+            if !later_ids.is_empty() {
+                ErrorHandler::log(From::from(format!(
+                    "WARNING: opening balance for {:?} as of {} replaces {} later balance(s).",
+                    opening_balance.account_name,
+                    start.display(),
+                    later_ids.len()
+                )));
+                store
+                    .account_balances
+                    .retain(|id, _account_balance| !later_ids.contains(id));
+            }
+
+            // replaces a balance reported at the same moment, e.g. from account_balances.csv
+            let existing_id = store
+                .account_balances
+                .reported_ids(account_id, reported_at..=reported_at)
+                .next();
+            match existing_id {
+                Some(id) => {
+                    store.account_balances.update(id, |account_balance| {
+                        account_balance.amount = opening_balance.amount
+                    });
+                }
+                None => {
+                    AccountBalance::save_to_store(
+                        AccountBalance {
+                            id: None,
+                            account_id,
+                            reported_at,
+                            amount: opening_balance.amount,
+                        },
+                        &mut store.account_balances,
+                    );
+                }
+            }
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    // one error per snapshot row, in any month, naming an account not in the store
+    pub fn unknown_names(
+        opening_balances: &OpeningBalanceStore,
+        store: &Store,
+    ) -> Vec<BudgetError> {
+        opening_balances
+            .values()
+            .filter(|opening_balance| {
+                Account::by_name(&opening_balance.account_name, &store.accounts).is_none()
+            })
+            .map(|opening_balance| BudgetError::UnknownAccount {
+                name: opening_balance.account_name.clone(),
+                did_you_mean: did_you_mean(
+                    &opening_balance.account_name,
+                    store.accounts.values().map(|account| account.name.as_str()),
+                ),
+            })
+            .collect()
+    }
+
+    // names of accounts with no balance reported at or before the start of start
+    // account_names may include accounts events will create, those always start at 0
+    pub fn missing(store: &Store, account_names: &BTreeSet<String>, start: YM) -> Vec<String> {
+        let opening = start.start_of_month().and_hms_opt(0, 0, 0).unwrap();
+        account_names
+            .iter()
+            .filter(|name| match Account::by_name(name, &store.accounts) {
+                None => true,
+                Some(account) => store
+                    .account_balances
+                    .reported_ids(account.id.unwrap(), ..=opening)
+                    .next()
+                    .is_none(),
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod opening_balance_spec {
+    use super::*;
+    use crate::calendar::month_key::MonthKey as MK;
+    use crate::test::spec::Spec;
+
+    fn opening_balances() -> OpeningBalanceStore {
+        let mut opening_balances = OpeningBalanceStore::new();
+        for (year_month, account_name, amount) in [
+            ("2023-03", "piggybank", 500),
+            ("2023-03", "swearjar", 5000),
+            ("2023-04", "piggybank", 700),
+        ] {
+            OpeningBalance::save_to_store(
+                OpeningBalance {
+                    id: None,
+                    year_month: year_month.to_string(),
+                    account_name: account_name.to_string(),
+                    amount: Decimal::new(amount, 0),
+                },
+                &mut opening_balances,
+            );
+        }
+        opening_balances
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__sets_balances_as_of_start() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let start = YM::new(2023, MK::Mar);
+
+        assert_eq!(
+            2,
+            OpeningBalance::apply(&opening_balances(), start, &mut store).unwrap()
+        );
+        let piggybank = Account::by_name("piggybank", &store.accounts).unwrap();
+        assert_eq!(
+            Decimal::new(500, 0),
+            piggybank.current_balance(&mut store.account_balances)
+        );
+        let swearjar = Account::by_name("swearjar", &store.accounts).unwrap();
+        assert_eq!(
+            Decimal::new(5000, 0),
+            swearjar.current_balance(&mut store.account_balances)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__drops_balances_reported_after_start() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let mut opening_balances = opening_balances();
+        opening_balances.get_mut(&1).unwrap().year_month = "2023-01".to_string();
+
+        // piggybank reported 100 and 200 during January
+        OpeningBalance::apply(&opening_balances, YM::new(2023, MK::Jan), &mut store).unwrap();
+        let piggybank = Account::by_name("piggybank", &store.accounts).unwrap();
+        assert_eq!(
+            1,
+            piggybank
+                .account_balance_ids(&mut store.account_balances)
+                .len()
+        );
+        assert_eq!(
+            Decimal::new(500, 0),
+            piggybank.current_balance(&mut store.account_balances)
+        );
+        assert_eq!(3, store.account_balances.len()); // swearjar's two are untouched
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__returns_err_for_an_unknown_account() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let mut opening_balances = opening_balances();
+        opening_balances.get_mut(&1).unwrap().account_name = "piggybnak".to_string();
+
+        let err = OpeningBalance::apply(&opening_balances, YM::new(2023, MK::Mar), &mut store)
+            .unwrap_err();
+        assert_eq!(
+            r#"Unknown account "piggybnak", did you mean "piggybank"?"#,
+            err.to_string()
+        );
+        assert_eq!(2, store.accounts.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn unknown_names__lists_unknown_accounts_in_every_month() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let mut opening_balances = opening_balances();
+        opening_balances.get_mut(&2).unwrap().account_name = "swearjr".to_string();
+        opening_balances.get_mut(&3).unwrap().account_name = "Side Hustle".to_string();
+
+        let unknown: Vec<String> = OpeningBalance::unknown_names(&opening_balances, &store)
+            .iter()
+            .map(|err| err.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                r#"Unknown account "swearjr", did you mean "swearjar"?"#.to_string(),
+                r#"Unknown account "Side Hustle"."#.to_string(),
+            ],
+            unknown
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn missing__lists_accounts_without_a_balance_at_start() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        let start = YM::new(2023, MK::Mar);
+        OpeningBalance::apply(&opening_balances(), start, &mut store).unwrap();
+
+        let account_names: BTreeSet<String> = ["piggybank", "swearjar", "Side Hustle"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(
+            vec!["Side Hustle".to_string()],
+            OpeningBalance::missing(&store, &account_names, start)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn year_months__rejects_malformed_year_month() {
+        let mut opening_balances = opening_balances();
+        opening_balances.get_mut(&1).unwrap().year_month = "March".to_string();
+        assert!(matches!(
+            OpeningBalance::year_months(&opening_balances),
+            Err(BudgetError::Calendar { .. })
+        ));
+    }
+}
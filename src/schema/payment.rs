@@ -1,12 +1,16 @@
+use crate::error::budget_error::BudgetResult;
 use crate::schema::account::{Account, AccountStore};
 use crate::schema::amount::{Amount, AmountStore};
 use crate::schema::expense::ExpenseStore;
+use crate::schema::fx_rate::FxRate;
+use crate::storage::indexed_store::IndexedStore;
+use crate::storage::store::Store;
+use crate::traits::csv_index::CsvIndex;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 pub struct Payment {
@@ -33,7 +37,15 @@ impl CsvRecord<Payment> for Payment {
 }
 impl CsvStore<Payment> for Payment {}
 
-pub type PaymentStore = BTreeMap<usize, Payment>;
+pub type PaymentStore = IndexedStore<Payment>;
+
+impl CsvIndex for Payment {
+    type Key = usize;
+
+    fn index_key(&self) -> usize {
+        self.account_id
+    }
+}
 
 impl<'a, 'b: 'a> Payment {
     #[allow(unused)]
@@ -41,7 +53,7 @@ impl<'a, 'b: 'a> Payment {
         let mut amount: Option<Amount> = None;
         for (id, amt) in store.iter() {
             if *id == self.amount_id {
-                amount = Some(*amt);
+                amount = Some(amt.clone());
                 break;
             }
         }
@@ -76,23 +88,28 @@ impl<'a, 'b: 'a> Payment {
         self.amount(store).map(|amt| amt.standard)
     }
 
-    pub fn total(payment_store: PaymentStore, amount_store: &AmountStore) -> Decimal {
+    // each payment converted to base_currency on the day it was made
+    pub fn total(
+        payment_store: &PaymentStore,
+        store: &Store,
+        base_currency: &str,
+    ) -> BudgetResult<Decimal> {
         let mut total = Decimal::new(00, 1);
         for (_id, payment) in payment_store.iter() {
-            total += payment.standard_amount(amount_store).unwrap();
+            total += FxRate::convert_amount(
+                payment.amount_id,
+                payment.account_id,
+                payment.completed_at.date(),
+                base_currency,
+                store,
+            )?;
         }
-        total
+        Ok(total)
     }
 
     #[allow(unused)]
     pub fn ids_by_account_id(account_id: usize, store: &mut PaymentStore) -> Vec<usize> {
-        let mut payment_ids: Vec<usize> = vec![];
-        for (id, payment) in store.iter() {
-            if payment.account_id == account_id {
-                payment_ids.push(*id);
-            }
-        }
-        payment_ids
+        store.ids(&account_id).collect()
     }
 }
 
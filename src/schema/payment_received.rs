@@ -1,12 +1,15 @@
+use crate::error::budget_error::BudgetResult;
 use crate::schema::account::Account;
 use crate::schema::amount::{Amount, AmountStore};
+use crate::schema::fx_rate::FxRate;
+use crate::storage::indexed_store::IndexedStore;
 use crate::storage::store::Store;
+use crate::traits::csv_index::CsvIndex;
 use crate::traits::csv_record::CsvRecord;
 use crate::traits::csv_store::CsvStore;
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 pub struct PaymentReceived {
@@ -33,31 +36,51 @@ impl CsvRecord<PaymentReceived> for PaymentReceived {
 }
 impl CsvStore<PaymentReceived> for PaymentReceived {}
 
-pub type PaymentReceivedStore = BTreeMap<usize, PaymentReceived>;
+pub type PaymentReceivedStore = IndexedStore<PaymentReceived>;
+
+impl CsvIndex for PaymentReceived {
+    type Key = usize;
+
+    fn index_key(&self) -> usize {
+        self.account_id
+    }
+}
 
 impl<'a, 'b: 'a> PaymentReceived {
+    #[allow(unused)]
     pub fn amount(&'a self, store: &'b AmountStore) -> Option<Amount> {
         let mut amount: Option<Amount> = None;
         for (id, amt) in store.iter() {
             if *id == self.amount_id {
-                amount = Some(*amt);
+                amount = Some(amt.clone());
                 break;
             }
         }
         amount
     }
 
-    pub fn total(payment_rec_store: PaymentReceivedStore, amount_store: &AmountStore) -> Decimal {
+    // each payment received converted to base_currency on the day it arrived
+    pub fn total(
+        payment_rec_store: &PaymentReceivedStore,
+        store: &Store,
+        base_currency: &str,
+    ) -> BudgetResult<Decimal> {
         let mut total = Decimal::new(00, 1);
         for (_id, payment_rec) in payment_rec_store.iter() {
-            total += payment_rec.standard_amount(amount_store).unwrap();
+            total += FxRate::convert_amount(
+                payment_rec.amount_id,
+                payment_rec.account_id,
+                payment_rec.completed_at.date(),
+                base_currency,
+                store,
+            )?;
         }
-        total
+        Ok(total)
     }
 
     #[allow(unused)]
-    pub fn deposit_to_account(&'a self, store: &'b Store) -> Option<&Account> {
-        let mut account: Option<&Account> = None;
+    pub fn deposit_to_account(&'a self, store: &'b Store) -> Option<&'b Account> {
+        let mut account: Option<&'b Account> = None;
         for (id, acc) in store.accounts.iter() {
             if *id == self.account_id {
                 account = Some(acc);
@@ -67,19 +90,14 @@ impl<'a, 'b: 'a> PaymentReceived {
         account
     }
 
+    #[allow(unused)]
     pub fn standard_amount(&self, store: &AmountStore) -> Option<Decimal> {
         self.amount(store).map(|amt| amt.standard)
     }
 
     #[allow(unused)]
     pub fn ids_by_account_id(account_id: usize, store: &mut PaymentReceivedStore) -> Vec<usize> {
-        let mut payment_rec_ids: Vec<usize> = vec![];
-        for (id, payment_rec) in store.iter() {
-            if payment_rec.account_id == account_id {
-                payment_rec_ids.push(*id);
-            }
-        }
-        payment_rec_ids
+        store.ids(&account_id).collect()
     }
 }
 
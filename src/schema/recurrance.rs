@@ -1,16 +1,303 @@
+use crate::calendar::holiday::HolidayCalendar;
+use crate::calendar::month::Month;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
+#[allow(unused)]
 pub struct Recurrence {
     pub id: Option<usize>,
     pub frequency: Every,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(tag = "t", content = "c")]
 pub enum Every {
     Days(u64),   // Every::Days(2) = every other day
     Weeks(u64),  // Every::Weeks(2) = every other week
     Months(u32), // Every::Months(6) = twice a year
     Years(u32),  // Every::Years(10) = once per decade
+    // rules, matched month by month instead of stepped from start
+    NthWeekday(u8, Weekday), // Every::NthWeekday(2, Weekday::Fri) = 2nd Friday of the month
+    LastDayOfMonth,
+    LastBusinessDay,  // last banking day of the month
+    SemiMonthly(u32), // Every::SemiMonthly(15) = the 15th and the last day of the month
+    FirstOfQuarter,   // Jan 1, Apr 1, Jul 1, Oct 1
+}
+
+// where a payment that falls on a weekend or bank holiday lands
+// None by default, the payment stays on the scheduled date
+// statement and loan payments are never rolled
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Roll {
+    #[default]
+    None,
+    Forward, // next business day
+    Back,    // previous business day
+}
+
+impl Roll {
+    pub fn apply(&self, date: NaiveDate, holidays: &HolidayCalendar) -> NaiveDate {
+        let mut rolled = date;
+        while !holidays.is_banking_day(rolled) {
+            rolled = match self {
+                Roll::None => return date,
+                Roll::Forward => rolled.succ_opt(),
+                Roll::Back => rolled.pred_opt(),
+            }
+            .expect("Error Rolling Payment Event Date");
+        }
+        rolled
+    }
+
+    // clap value parser
+    pub fn try_parse(roll: &str) -> Result<Roll, String> {
+        match roll {
+            "none" => Ok(Roll::None),
+            "forward" => Ok(Roll::Forward),
+            "back" => Ok(Roll::Back),
+            _ => Err(format!(
+                "Unknown roll {roll:?}, expected none, forward or back"
+            )),
+        }
+    }
+}
+
+fn last_day_of_month(year: i32, month: u32) -> NaiveDate {
+    let length = Month::length(year, Month::key_from_id(month));
+    NaiveDate::from_ymd_opt(year, month, length).unwrap()
+}
+
+impl Every {
+    // clap value parser, "weeks:2" => Every::Weeks(2)
+    pub fn try_parse(every: &str) -> Result<Every, String> {
+        match every {
+            "last_day" => return Ok(Every::LastDayOfMonth),
+            "last_business_day" => return Ok(Every::LastBusinessDay),
+            "first_of_quarter" => return Ok(Every::FirstOfQuarter),
+            _ => (),
+        }
+        let (unit, count) = every
+            .split_once(':')
+            .ok_or_else(|| format!("{every:?} is not unit:count, e.g. weeks:2"))?;
+        let invalid_count = |_| format!("Invalid count {count:?} in {every:?}");
+        match unit {
+            "days" => Ok(Every::Days(count.parse().map_err(invalid_count)?)),
+            "weeks" => Ok(Every::Weeks(count.parse().map_err(invalid_count)?)),
+            "months" => Ok(Every::Months(count.parse().map_err(invalid_count)?)),
+            "years" => Ok(Every::Years(count.parse().map_err(invalid_count)?)),
+            "semi_monthly" => match count.parse().map_err(invalid_count)? {
+                day @ 1..=31 => Ok(Every::SemiMonthly(day)),
+                day => Err(format!("Invalid day {day} in {every:?}, expected 1 to 31")),
+            },
+            // "nth_weekday:2:fri" => 2nd Friday
+            "nth_weekday" => {
+                let (n, weekday) = count
+                    .split_once(':')
+                    .ok_or_else(|| format!("{every:?} is not nth_weekday:n:day, e.g. nth_weekday:2:fri"))?;
+                let n: u8 = n
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=5).contains(n))
+                    .ok_or_else(|| format!("Invalid n {n:?} in {every:?}, expected 1 to 5"))?;
+                let weekday: Weekday = weekday
+                    .parse()
+                    .map_err(|_| format!("Invalid weekday {weekday:?} in {every:?}"))?;
+                Ok(Every::NthWeekday(n, weekday))
+            }
+            _ => Err(format!(
+                "Unknown unit {unit:?}, expected days, weeks, months, years, semi_monthly or nth_weekday"
+            )),
+        }
+    }
+
+    // first date on or after `date` matched by a rule, `date` itself for fixed steps
+    pub fn on_or_after(&self, date: NaiveDate) -> NaiveDate {
+        if !self.is_rule() {
+            return date;
+        }
+        let (mut year, mut month) = (date.year(), date.month());
+        // a 5th weekday turns up at least once a quarter
+        for _ in 0..12 {
+            let matched = self
+                .dates_in_month(year, month)
+                .into_iter()
+                .find(|matched| *matched >= date);
+            if let Some(matched) = matched {
+                return matched;
+            }
+            (year, month) = match month {
+                12 => (year + 1, 1),
+                _ => (year, month + 1),
+            };
+        }
+        panic!("Error Computing Next Payment Event Date by Rule {self:?}")
+    }
+
+    pub fn is_rule(&self) -> bool {
+        !matches!(
+            self,
+            Every::Days(_) | Every::Weeks(_) | Every::Months(_) | Every::Years(_)
+        )
+    }
+
+    // dates matched by a rule within one month, in order
+    fn dates_in_month(&self, year: i32, month: u32) -> Vec<NaiveDate> {
+        let last_day = last_day_of_month(year, month);
+        match self {
+            Every::NthWeekday(n, weekday) => {
+                NaiveDate::from_weekday_of_month_opt(year, month, *weekday, *n)
+                    .into_iter()
+                    .collect()
+            }
+            Every::LastDayOfMonth => vec![last_day],
+            Every::LastBusinessDay => {
+                // holidays are stepped back past in RecurringPaymentEvent::payment_dates
+                let mut date = last_day;
+                while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                    date = date.checked_sub_days(Days::new(1)).unwrap();
+                }
+                vec![date]
+            }
+            Every::SemiMonthly(day) if *day < last_day.day() => {
+                vec![
+                    NaiveDate::from_ymd_opt(year, month, *day).unwrap(),
+                    last_day,
+                ]
+            }
+            Every::SemiMonthly(_) => vec![last_day],
+            Every::FirstOfQuarter if month % 3 == 1 => {
+                vec![NaiveDate::from_ymd_opt(year, month, 1).unwrap()]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod recurrance_spec {
+    use super::*;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse__reads_unit_and_count() {
+        assert_eq!(Every::Weeks(2), Every::try_parse("weeks:2").unwrap());
+        assert_eq!(Every::Months(1), Every::try_parse("months:1").unwrap());
+        assert!(Every::try_parse("fortnights:1").is_err());
+        assert!(Every::try_parse("weeks").is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn try_parse__reads_rules() {
+        assert_eq!(
+            Every::NthWeekday(2, Weekday::Fri),
+            Every::try_parse("nth_weekday:2:fri").unwrap()
+        );
+        assert_eq!(
+            Every::SemiMonthly(15),
+            Every::try_parse("semi_monthly:15").unwrap()
+        );
+        assert_eq!(
+            Every::LastBusinessDay,
+            Every::try_parse("last_business_day").unwrap()
+        );
+        assert!(Every::try_parse("nth_weekday:6:fri").is_err());
+        assert!(Every::try_parse("nth_weekday:2").is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn deserialize__reads_rules_from_json() {
+        let every: Every =
+            serde_json::from_str(r#"{ "t": "NthWeekday", "c": [2, "Fri"] }"#).unwrap();
+        assert_eq!(Every::NthWeekday(2, Weekday::Fri), every);
+        let every: Every = serde_json::from_str(r#"{ "t": "LastBusinessDay" }"#).unwrap();
+        assert_eq!(Every::LastBusinessDay, every);
+        let roll: Roll = serde_json::from_str(r#""forward""#).unwrap();
+        assert_eq!(Roll::Forward, roll);
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn on_or_after__finds_next_date_matched_by_rule() {
+        let second_friday = Every::NthWeekday(2, Weekday::Fri);
+        assert_eq!(
+            date(2023, 3, 10),
+            second_friday.on_or_after(date(2023, 3, 1))
+        );
+        assert_eq!(
+            date(2023, 4, 14),
+            second_friday.on_or_after(date(2023, 3, 11))
+        );
+        // 2023-09-30 is a Saturday
+        assert_eq!(
+            date(2023, 9, 29),
+            Every::LastBusinessDay.on_or_after(date(2023, 9, 1))
+        );
+        assert_eq!(
+            date(2023, 2, 28),
+            Every::SemiMonthly(15).on_or_after(date(2023, 2, 16))
+        );
+        assert_eq!(
+            date(2023, 3, 15),
+            Every::SemiMonthly(15).on_or_after(date(2023, 3, 1))
+        );
+        assert_eq!(
+            date(2023, 7, 1),
+            Every::FirstOfQuarter.on_or_after(date(2023, 4, 2))
+        );
+        // fixed steps start where they are told
+        assert_eq!(
+            date(2023, 3, 3),
+            Every::Weeks(2).on_or_after(date(2023, 3, 3))
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__rolls_weekend_dates_to_a_business_day() {
+        // 2023-04-01 is a Saturday
+        let saturday = date(2023, 4, 1);
+        let holidays = HolidayCalendar::default();
+        assert_eq!(saturday, Roll::None.apply(saturday, &holidays));
+        assert_eq!(date(2023, 4, 3), Roll::Forward.apply(saturday, &holidays));
+        assert_eq!(date(2023, 3, 31), Roll::Back.apply(saturday, &holidays));
+        assert_eq!(
+            date(2023, 4, 4),
+            Roll::Back.apply(date(2023, 4, 4), &holidays)
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn apply__rolls_past_bank_holidays() {
+        use crate::calendar::holiday::{Holiday, HolidayStore};
+        use crate::traits::csv_store::CsvStore;
+
+        // 2023-04-01 is a Saturday, Monday 2023-04-03 a holiday
+        let mut store = HolidayStore::new();
+        Holiday::save_to_store(
+            Holiday {
+                id: None,
+                date: date(2023, 4, 3),
+                name: "Bank Holiday".to_string(),
+            },
+            &mut store,
+        );
+        let holidays = HolidayCalendar::new(&store);
+        assert_eq!(
+            date(2023, 4, 4),
+            Roll::Forward.apply(date(2023, 4, 1), &holidays)
+        );
+        assert_eq!(
+            date(2023, 3, 31),
+            Roll::Back.apply(date(2023, 4, 3), &holidays)
+        );
+    }
 }
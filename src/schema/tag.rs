@@ -3,13 +3,12 @@ use crate::traits::csv_store::CsvStore;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Tag {
-    id: Option<usize>,
+    pub id: Option<usize>,
     pub content: String,
 }
 
-#[allow(unused)]
 pub type TagStore = BTreeMap<usize, Tag>;
 
 impl CsvRecord<Tag> for Tag {
@@ -30,3 +29,56 @@ impl CsvRecord<Tag> for Tag {
     }
 }
 impl CsvStore<Tag> for Tag {}
+
+impl Tag {
+    pub fn by_content(content: &str, store: &TagStore) -> Option<Tag> {
+        store
+            .values()
+            .find(|tag| tag.content == content)
+            .map(|tag| tag.clone_record())
+    }
+
+    // returns id of existing tag with content, creating it if needed
+    pub fn find_or_create(content: &str, store: &mut TagStore) -> usize {
+        match Tag::by_content(content, store) {
+            Some(tag) => tag.id.unwrap(),
+            None => Tag::save_to_store(
+                Tag {
+                    id: None,
+                    content: content.to_string(),
+                },
+                store,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tag_spec {
+    use super::*;
+    use crate::storage::store::Store;
+    use crate::test::spec::Spec;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn find_or_create__returns_existing_tag_id() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        assert_eq!(1, Tag::find_or_create("household", &mut store.tags));
+        assert_eq!(2, store.tags.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn find_or_create__creates_missing_tag() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        assert_eq!(3, Tag::find_or_create("kids", &mut store.tags));
+        assert_eq!(
+            "kids".to_string(),
+            Tag::by_id(3, &mut store.tags).unwrap().content
+        );
+    }
+}
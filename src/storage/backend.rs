@@ -0,0 +1,90 @@
+use crate::error::budget_error::BudgetError;
+use crate::storage::sqlite_backend::SqliteBackend;
+use crate::storage::store::Store;
+
+pub type StoreBackendResult = Result<(), BudgetError>;
+
+// where a Store is loaded from before a run and persisted to after it
+// the two are kept apart so a run never starts from the output of the last one
+pub trait StoreBackend {
+    fn load(&self, store: &mut Store) -> StoreBackendResult;
+    fn save(&self, store: &Store) -> StoreBackendResult;
+    // what the last save wrote
+    fn load_saved(&self, store: &mut Store) -> StoreBackendResult;
+}
+
+// one csv per table, read from init/ and written to reports/
+pub struct CsvBackend {
+    pub init_dir: String,
+    pub out_dir: String,
+}
+
+impl StoreBackend for CsvBackend {
+    fn load(&self, store: &mut Store) -> StoreBackendResult {
+        store.init(Some(self.init_dir.clone()))?;
+        Ok(())
+    }
+
+    fn save(&self, store: &Store) -> StoreBackendResult {
+        store.write_to_csv(Some(self.out_dir.clone()))
+    }
+
+    fn load_saved(&self, store: &mut Store) -> StoreBackendResult {
+        store.init(Some(self.out_dir.clone()))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreKind {
+    Csv,
+    Sqlite(String), // path to the database loaded, the run saves a copy in the out dir
+}
+
+type StoreKindParseResult = Result<StoreKind, String>;
+
+impl StoreKind {
+    // "csv" or "sqlite:path/to/file.db", also used as a clap value parser
+    pub fn parse(store: &str) -> StoreKindParseResult {
+        match store.split_once(':') {
+            None if store == "csv" => Ok(StoreKind::Csv),
+            Some(("sqlite", path)) if !path.is_empty() => Ok(StoreKind::Sqlite(path.to_string())),
+            _ => Err(format!(
+                "Unknown store {store:?}, expected \"csv\" or \"sqlite:path.db\"."
+            )),
+        }
+    }
+
+    pub fn backend(&self, init_dir: String, out_dir: String) -> Box<dyn StoreBackend> {
+        match self {
+            StoreKind::Csv => Box::new(CsvBackend { init_dir, out_dir }),
+            StoreKind::Sqlite(db_path) => Box::new(SqliteBackend {
+                db_path: db_path.clone(),
+                out_path: SqliteBackend::out_path(db_path, &out_dir),
+                seed_dir: init_dir,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod backend_spec {
+    use super::*;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn parse__reads_csv_and_sqlite_stores() {
+        assert_eq!(StoreKind::Csv, StoreKind::parse("csv").unwrap());
+        assert_eq!(
+            StoreKind::Sqlite("data/budget.db".to_string()),
+            StoreKind::parse("sqlite:data/budget.db").unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn parse__returns_err_for_unknown_store() {
+        assert!(StoreKind::parse("postgres:localhost").is_err());
+        assert!(StoreKind::parse("sqlite:").is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::traits::csv_index::CsvIndex;
+use crate::traits::csv_store::CsvRecords;
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::ops::{Deref, RangeBounds};
+
+// BTreeMap<usize, T> with a secondary BTreeMap<column value, ids> on T::index_key
+// derefs to the BTreeMap for reads, every write goes through insert, update or retain
+// so the index is kept current as records change
+#[derive(Debug, Clone)]
+pub struct IndexedStore<T: CsvIndex> {
+    records: BTreeMap<usize, T>,
+    ids_by_key: BTreeMap<T::Key, Vec<usize>>, // ids ascending
+}
+
+impl<T: CsvIndex> IndexedStore<T> {
+    pub fn new() -> IndexedStore<T> {
+        IndexedStore {
+            records: BTreeMap::new(),
+            ids_by_key: BTreeMap::new(),
+        }
+    }
+
+    // ids of records whose index_key is key, ascending
+    pub fn ids<Q>(&self, key: &Q) -> impl DoubleEndedIterator<Item = usize> + '_
+    where
+        T::Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.ids_by_key.get(key).into_iter().flatten().copied()
+    }
+
+    // ids of records whose index_key falls in range, in key order then ascending
+    pub fn ids_in<R>(&self, range: R) -> impl DoubleEndedIterator<Item = usize> + '_
+    where
+        R: RangeBounds<T::Key>,
+    {
+        self.ids_by_key
+            .range(range)
+            .flat_map(|(_key, ids)| ids)
+            .copied()
+    }
+
+    // lowest id whose index_key is key
+    pub fn first_id<Q>(&self, key: &Q) -> Option<usize>
+    where
+        T::Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.ids(key).next()
+    }
+
+    // saves record as id, returns the record it replaced
+    pub fn insert(&mut self, id: usize, record: T) -> Option<T> {
+        let key = record.index_key();
+        let replaced = self.records.insert(id, record);
+        if let Some(old) = &replaced {
+            self.unindex(&old.index_key(), id);
+        }
+        self.index(key, id);
+        replaced
+    }
+
+    // changes the record saved as id, reindexing it if its index_key changed
+    pub fn update<R>(&mut self, id: usize, change: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let record = self.records.get_mut(&id)?;
+        let old_key = record.index_key();
+        let result = change(record);
+        let new_key = record.index_key();
+        if old_key != new_key {
+            self.unindex(&old_key, id);
+            self.index(new_key, id);
+        }
+        Some(result)
+    }
+
+    // keeps only the records keep returns true for
+    pub fn retain(&mut self, mut keep: impl FnMut(&usize, &T) -> bool) {
+        self.records.retain(|id, record| keep(id, record));
+        self.ids_by_key.clear();
+        for (id, record) in self.records.iter() {
+            self.ids_by_key
+                .entry(record.index_key())
+                .or_default()
+                .push(*id);
+        }
+    }
+
+    fn index(&mut self, key: T::Key, id: usize) {
+        let ids = self.ids_by_key.entry(key).or_default();
+        if let Err(position) = ids.binary_search(&id) {
+            ids.insert(position, id);
+        }
+    }
+
+    fn unindex(&mut self, key: &T::Key, id: usize) {
+        if let Some(ids) = self.ids_by_key.get_mut(key) {
+            ids.retain(|indexed| *indexed != id);
+            if ids.is_empty() {
+                self.ids_by_key.remove(key);
+            }
+        }
+    }
+}
+
+impl<T: CsvIndex> Default for IndexedStore<T> {
+    fn default() -> Self {
+        IndexedStore::new()
+    }
+}
+
+impl<T: CsvIndex> Deref for IndexedStore<T> {
+    type Target = BTreeMap<usize, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.records
+    }
+}
+
+impl<T: CsvIndex> CsvRecords<T> for IndexedStore<T> {
+    fn records(&self) -> &BTreeMap<usize, T> {
+        &self.records
+    }
+
+    fn insert_record(&mut self, id: usize, record: T) {
+        self.insert(id, record);
+    }
+}
+
+#[cfg(test)]
+mod indexed_store_spec {
+    use crate::schema::account_balance::{AccountBalance, AccountBalanceStore};
+    use crate::storage::store::Store;
+    use crate::test::spec::Spec;
+    use crate::traits::csv_store::CsvStore;
+    use chrono::{Duration, NaiveDate};
+    use rust_decimal::Decimal;
+
+    fn account_balance(id: Option<usize>, account_id: usize) -> AccountBalance {
+        AccountBalance {
+            id,
+            account_id,
+            reported_at: NaiveDate::from_ymd_opt(2023, 1, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            amount: Decimal::new(0, 0),
+        }
+    }
+
+    fn ids(account_balances: &AccountBalanceStore, account_id: usize) -> Vec<usize> {
+        account_balances.reported_ids(account_id, ..).collect()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn ids_in__are_built_from_init_store() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        assert_eq!(vec![1, 2], ids(&store.account_balances, 1));
+        assert_eq!(vec![3, 4], ids(&store.account_balances, 2));
+        assert!(ids(&store.account_balances, 3).is_empty());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn ids_in__include_records_added_by_save_to_store() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        assert_eq!(vec![1, 2], ids(&store.account_balances, 1));
+
+        // reported after the spec's balances
+        let mut later = account_balance(None, 1);
+        later.reported_at += Duration::days(365);
+        let new_id = AccountBalance::save_to_store(later, &mut store.account_balances);
+        assert_eq!(vec![1, 2, new_id], ids(&store.account_balances, 1));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn ids_in__include_records_saved_below_the_last_id() {
+        let mut account_balances = AccountBalanceStore::new();
+        AccountBalance::save_to_store(account_balance(Some(5), 1), &mut account_balances);
+        assert_eq!(vec![5], ids(&account_balances, 1));
+
+        AccountBalance::save_to_store(account_balance(Some(2), 1), &mut account_balances);
+        AccountBalance::save_to_store(account_balance(Some(7), 2), &mut account_balances);
+        assert_eq!(vec![2, 5], ids(&account_balances, 1));
+        assert_eq!(vec![7], ids(&account_balances, 2));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn ids_in__order_by_key_before_id() {
+        let mut account_balances = AccountBalanceStore::new();
+        let mut earlier = account_balance(Some(3), 1);
+        earlier.reported_at -= Duration::days(1);
+        AccountBalance::save_to_store(account_balance(Some(1), 1), &mut account_balances);
+        AccountBalance::save_to_store(earlier, &mut account_balances);
+
+        assert_eq!(vec![3, 1], ids(&account_balances, 1));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn update__reindexes_a_record_whose_key_changed() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        store
+            .account_balances
+            .update(2, |account_balance| account_balance.account_id = 99);
+        assert_eq!(vec![1], ids(&store.account_balances, 1));
+        assert_eq!(vec![2], ids(&store.account_balances, 99));
+        assert_eq!(None, store.account_balances.update(42, |_| ()));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn retain__drops_removed_records_from_the_index() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        store.account_balances.retain(|id, _| *id != 1);
+        assert_eq!(vec![2], ids(&store.account_balances, 1));
+        assert_eq!(vec![3, 4], ids(&store.account_balances, 2));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn first_id__returns_lowest_matching_id() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+
+        assert_eq!(Some(2), store.accounts.first_id("swearjar"));
+        assert_eq!(None, store.accounts.first_id("mattress"));
+    }
+}
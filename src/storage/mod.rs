@@ -1 +1,4 @@
+pub mod backend;
+pub mod indexed_store;
+pub mod sqlite_backend;
 pub mod store;
@@ -0,0 +1,910 @@
+use crate::error::budget_error::BudgetError;
+use crate::schema::account::Account;
+use crate::schema::account_balance::AccountBalance;
+use crate::schema::amount::Amount;
+use crate::schema::budget::Budget;
+use crate::schema::expense::Expense;
+use crate::schema::expense_tag::ExpenseTag;
+use crate::schema::fx_rate::FxRate;
+use crate::schema::income::Income;
+use crate::schema::income_tag::IncomeTag;
+use crate::schema::payment::Payment;
+use crate::schema::payment_received::PaymentReceived;
+use crate::schema::tag::Tag;
+use crate::storage::backend::{StoreBackend, StoreBackendResult};
+use crate::storage::store::Store;
+use crate::traits::csv_store::CsvStore;
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, Row};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// decimals are NUMERIC so a sql client can compare, order and sum them
+// each keeps an exact TEXT copy in {column}_exact, which is what load reads back
+// foreign keys are deferred so tables can be written in any order inside one transaction
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS accounts (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    credit_limit NUMERIC,
+    credit_limit_exact TEXT,
+    statement_closing_day INTEGER,
+    payment_due_day INTEGER,
+    funding_account_id INTEGER REFERENCES accounts(id) DEFERRABLE INITIALLY DEFERRED,
+    interest_rate NUMERIC,
+    interest_rate_exact TEXT,
+    compounding TEXT NOT NULL,
+    min_balance NUMERIC,
+    min_balance_exact TEXT,
+    currency TEXT
+);
+CREATE TABLE IF NOT EXISTS account_balances (
+    id INTEGER PRIMARY KEY,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) DEFERRABLE INITIALLY DEFERRED,
+    reported_at TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    amount_exact TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS amounts (
+    id INTEGER PRIMARY KEY,
+    standard NUMERIC NOT NULL,
+    standard_exact TEXT NOT NULL,
+    low NUMERIC,
+    low_exact TEXT,
+    high NUMERIC,
+    high_exact TEXT,
+    currency TEXT
+);
+CREATE TABLE IF NOT EXISTS expenses (
+    id INTEGER PRIMARY KEY,
+    active INTEGER NOT NULL,
+    name TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS incomes (
+    id INTEGER PRIMARY KEY,
+    active INTEGER NOT NULL,
+    name TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS payments (
+    id INTEGER PRIMARY KEY,
+    completed_at TEXT NOT NULL,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) DEFERRABLE INITIALLY DEFERRED,
+    amount_id INTEGER NOT NULL REFERENCES amounts(id) DEFERRABLE INITIALLY DEFERRED,
+    expense_id INTEGER NOT NULL REFERENCES expenses(id) DEFERRABLE INITIALLY DEFERRED
+);
+CREATE TABLE IF NOT EXISTS payments_received (
+    id INTEGER PRIMARY KEY,
+    completed_at TEXT NOT NULL,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) DEFERRABLE INITIALLY DEFERRED,
+    income_id INTEGER NOT NULL REFERENCES incomes(id) DEFERRABLE INITIALLY DEFERRED,
+    amount_id INTEGER NOT NULL REFERENCES amounts(id) DEFERRABLE INITIALLY DEFERRED
+);
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY,
+    content TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS budgets (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    expense_id INTEGER REFERENCES expenses(id) DEFERRABLE INITIALLY DEFERRED,
+    expense_name TEXT,
+    tag_id INTEGER REFERENCES tags(id) DEFERRABLE INITIALLY DEFERRED,
+    \"limit\" NUMERIC NOT NULL,
+    limit_exact TEXT NOT NULL,
+    rollover INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS expense_tags (
+    id INTEGER PRIMARY KEY,
+    expense_id INTEGER NOT NULL REFERENCES expenses(id) DEFERRABLE INITIALLY DEFERRED,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) DEFERRABLE INITIALLY DEFERRED
+);
+CREATE TABLE IF NOT EXISTS fx_rates (
+    id INTEGER PRIMARY KEY,
+    date TEXT NOT NULL,
+    pair TEXT NOT NULL,
+    rate NUMERIC NOT NULL,
+    rate_exact TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS income_tags (
+    id INTEGER PRIMARY KEY,
+    income_id INTEGER NOT NULL REFERENCES incomes(id) DEFERRABLE INITIALLY DEFERRED,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) DEFERRABLE INITIALLY DEFERRED
+);
+";
+
+// children first so a plain DELETE never trips a foreign key
+const TABLES: [&str; 12] = [
+    "fx_rates",
+    "income_tags",
+    "expense_tags",
+    "budgets",
+    "payments_received",
+    "payments",
+    "account_balances",
+    "tags",
+    "incomes",
+    "expenses",
+    "amounts",
+    "accounts",
+];
+
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+// the whole Store in local sqlite files, loaded from db_path and saved to out_path
+// a database that does not exist yet is seeded from the csvs in seed_dir
+pub struct SqliteBackend {
+    pub db_path: String,
+    pub out_path: String,
+    pub seed_dir: String,
+}
+
+impl StoreBackend for SqliteBackend {
+    fn load(&self, store: &mut Store) -> StoreBackendResult {
+        if SqliteBackend::same_file(&self.db_path, &self.out_path) {
+            return Err(BudgetError::Sqlite {
+                message: format!(
+                    "{} is also where the run saves, load a database outside the out dir.",
+                    self.db_path
+                ),
+            });
+        }
+        if !Path::new(&self.db_path).exists() {
+            store.init(Some(self.seed_dir.clone()))?;
+            return Ok(());
+        }
+        SqliteBackend::load_from(&SqliteBackend::open(&self.db_path)?, store)
+    }
+
+    // replaces every table of out_path with the contents of store
+    // tables are recreated so a database saved by an older version gets NUMERIC decimals
+    fn save(&self, store: &Store) -> StoreBackendResult {
+        let mut conn = SqliteBackend::open(&self.out_path)?;
+        let tx = conn.transaction()?;
+        for table in TABLES.iter() {
+            tx.execute(format!("DROP TABLE {table}").as_str(), [])?;
+        }
+        tx.execute_batch(SCHEMA)?;
+
+        for account in store.accounts.values() {
+            tx.execute(
+                "INSERT INTO accounts \
+                 (id, name, kind, credit_limit, credit_limit_exact, statement_closing_day, \
+                 payment_due_day, funding_account_id, interest_rate, interest_rate_exact, \
+                 compounding, min_balance, min_balance_exact, currency) \
+                 VALUES (?1, ?2, ?3, ?4, ?4, ?5, ?6, ?7, ?8, ?8, ?9, ?10, ?10, ?11)",
+                params![
+                    account.id,
+                    account.name,
+                    enum_to_sql(&account.kind)?,
+                    account.credit_limit.map(|d| d.to_string()),
+                    account.statement_closing_day,
+                    account.payment_due_day,
+                    account.funding_account_id,
+                    account.interest_rate.map(|d| d.to_string()),
+                    enum_to_sql(&account.compounding)?,
+                    account.min_balance.map(|d| d.to_string()),
+                    account.currency,
+                ],
+            )?;
+        }
+        for balance in store.account_balances.values() {
+            tx.execute(
+                "INSERT INTO account_balances \
+                 (id, account_id, reported_at, amount, amount_exact) \
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+                params![
+                    balance.id,
+                    balance.account_id,
+                    balance.reported_at.format(DATETIME_FORMAT).to_string(),
+                    balance.amount.to_string(),
+                ],
+            )?;
+        }
+        for amount in store.amounts.values() {
+            tx.execute(
+                "INSERT INTO amounts \
+                 (id, standard, standard_exact, low, low_exact, high, high_exact, currency) \
+                 VALUES (?1, ?2, ?2, ?3, ?3, ?4, ?4, ?5)",
+                params![
+                    amount.id,
+                    amount.standard.to_string(),
+                    amount.low.map(|d| d.to_string()),
+                    amount.high.map(|d| d.to_string()),
+                    amount.currency,
+                ],
+            )?;
+        }
+        for expense in store.expenses.values() {
+            tx.execute(
+                "INSERT INTO expenses (id, active, name) VALUES (?1, ?2, ?3)",
+                params![expense.id, expense.active, expense.name],
+            )?;
+        }
+        for income in store.incomes.values() {
+            tx.execute(
+                "INSERT INTO incomes (id, active, name) VALUES (?1, ?2, ?3)",
+                params![income.id, income.active, income.name],
+            )?;
+        }
+        for payment in store.payments.values() {
+            tx.execute(
+                "INSERT INTO payments (id, completed_at, account_id, amount_id, expense_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    payment.id,
+                    payment.completed_at.format(DATETIME_FORMAT).to_string(),
+                    payment.account_id,
+                    payment.amount_id,
+                    payment.expense_id,
+                ],
+            )?;
+        }
+        for payment_rec in store.payments_received.values() {
+            tx.execute(
+                "INSERT INTO payments_received \
+                 (id, completed_at, account_id, income_id, amount_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    payment_rec.id,
+                    payment_rec.completed_at.format(DATETIME_FORMAT).to_string(),
+                    payment_rec.account_id,
+                    payment_rec.income_id,
+                    payment_rec.amount_id,
+                ],
+            )?;
+        }
+        for tag in store.tags.values() {
+            tx.execute(
+                "INSERT INTO tags (id, content) VALUES (?1, ?2)",
+                params![tag.id, tag.content],
+            )?;
+        }
+        for budget in store.budgets.values() {
+            tx.execute(
+                "INSERT INTO budgets \
+                 (id, name, expense_id, expense_name, tag_id, \"limit\", limit_exact, rollover) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7)",
+                params![
+                    budget.id,
+                    budget.name,
+                    budget.expense_id,
+                    budget.expense_name,
+                    budget.tag_id,
+                    budget.limit.to_string(),
+                    budget.rollover,
+                ],
+            )?;
+        }
+        for expense_tag in store.expense_tags.values() {
+            tx.execute(
+                "INSERT INTO expense_tags (id, expense_id, tag_id) VALUES (?1, ?2, ?3)",
+                params![expense_tag.id, expense_tag.expense_id, expense_tag.tag_id],
+            )?;
+        }
+        for income_tag in store.income_tags.values() {
+            tx.execute(
+                "INSERT INTO income_tags (id, income_id, tag_id) VALUES (?1, ?2, ?3)",
+                params![income_tag.id, income_tag.income_id, income_tag.tag_id],
+            )?;
+        }
+        for fx_rate in store.fx_rates.values() {
+            tx.execute(
+                "INSERT INTO fx_rates (id, date, pair, rate, rate_exact) \
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+                params![
+                    fx_rate.id,
+                    fx_rate.date.to_string(),
+                    fx_rate.pair,
+                    fx_rate.rate.to_string(),
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load_saved(&self, store: &mut Store) -> StoreBackendResult {
+        SqliteBackend::load_from(&SqliteBackend::open(&self.out_path)?, store)
+    }
+}
+
+impl SqliteBackend {
+    // the database's file name in out_dir
+    pub fn out_path(db_path: &str, out_dir: &str) -> String {
+        let file_name = Path::new(db_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("budget.db");
+        format!("{out_dir}/{file_name}")
+    }
+
+    fn same_file(path: &str, other: &str) -> bool {
+        match (fs::canonicalize(path), fs::canonicalize(other)) {
+            (Ok(path), Ok(other)) => path == other,
+            _ => path == other,
+        }
+    }
+
+    fn load_from(conn: &Connection, store: &mut Store) -> StoreBackendResult {
+        SqliteBackend::load_accounts(conn, store)?;
+        SqliteBackend::load_account_balances(conn, store)?;
+        SqliteBackend::load_amounts(conn, store)?;
+        SqliteBackend::load_expenses(conn, store)?;
+        SqliteBackend::load_incomes(conn, store)?;
+        SqliteBackend::load_payments(conn, store)?;
+        SqliteBackend::load_payments_received(conn, store)?;
+        SqliteBackend::load_tags(conn, store)?;
+        SqliteBackend::load_budgets(conn, store)?;
+        SqliteBackend::load_expense_tags(conn, store)?;
+        SqliteBackend::load_income_tags(conn, store)?;
+        SqliteBackend::load_fx_rates(conn, store)?;
+        Ok(())
+    }
+
+    fn open(path: &str) -> rusqlite::Result<Connection> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        SqliteBackend::migrate(&conn)?;
+        Ok(conn)
+    }
+
+    // columns added after a database may have been created
+    // an older database keeps its decimals in TEXT columns and no _exact copy, load falls back to them
+    fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+        for (table, column) in [
+            ("accounts", "min_balance"),
+            ("accounts", "currency"),
+            ("amounts", "currency"),
+            ("accounts", "credit_limit_exact"),
+            ("accounts", "interest_rate_exact"),
+            ("accounts", "min_balance_exact"),
+            ("account_balances", "amount_exact"),
+            ("amounts", "standard_exact"),
+            ("amounts", "low_exact"),
+            ("amounts", "high_exact"),
+            ("budgets", "limit_exact"),
+            ("fx_rates", "rate_exact"),
+        ] {
+            let has_column: bool = conn.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+                params![table, column],
+                |row| row.get(0),
+            )?;
+            if !has_column {
+                conn.execute_batch(
+                    format!("ALTER TABLE {table} ADD COLUMN {column} TEXT;").as_str(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load_accounts(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            format!(
+                "SELECT id, name, kind, {}, statement_closing_day, payment_due_day, \
+                 funding_account_id, {}, compounding, {}, currency \
+                 FROM accounts ORDER BY id",
+                exact("credit_limit"),
+                exact("interest_rate"),
+                exact("min_balance"),
+            )
+            .as_str(),
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Account {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                kind: enum_from_sql(row, 2)?,
+                credit_limit: decimal_option(row, 3)?,
+                statement_closing_day: row.get(4)?,
+                payment_due_day: row.get(5)?,
+                funding_account_id: row.get(6)?,
+                interest_rate: decimal_option(row, 7)?,
+                compounding: enum_from_sql(row, 8)?,
+                min_balance: decimal_option(row, 9)?,
+                currency: row.get(10)?,
+            })
+        })?;
+        for account in rows {
+            Account::save_to_store(account?, &mut store.accounts);
+        }
+        Ok(())
+    }
+
+    fn load_account_balances(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            format!(
+                "SELECT id, account_id, reported_at, {} FROM account_balances ORDER BY id",
+                exact("amount"),
+            )
+            .as_str(),
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(AccountBalance {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                reported_at: datetime(row, 2)?,
+                amount: decimal(row, 3)?,
+            })
+        })?;
+        for balance in rows {
+            AccountBalance::save_to_store(balance?, &mut store.account_balances);
+        }
+        Ok(())
+    }
+
+    fn load_amounts(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            format!(
+                "SELECT id, {}, {}, {}, currency FROM amounts ORDER BY id",
+                exact("standard"),
+                exact("low"),
+                exact("high"),
+            )
+            .as_str(),
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Amount {
+                id: row.get(0)?,
+                standard: decimal(row, 1)?,
+                low: decimal_option(row, 2)?,
+                high: decimal_option(row, 3)?,
+                currency: row.get(4)?,
+            })
+        })?;
+        for amount in rows {
+            Amount::save_to_store(amount?, &mut store.amounts);
+        }
+        Ok(())
+    }
+
+    fn load_expenses(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare("SELECT id, active, name FROM expenses ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Expense {
+                id: row.get(0)?,
+                active: row.get(1)?,
+                name: row.get(2)?,
+            })
+        })?;
+        for expense in rows {
+            Expense::save_to_store(expense?, &mut store.expenses);
+        }
+        Ok(())
+    }
+
+    fn load_incomes(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare("SELECT id, active, name FROM incomes ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Income {
+                id: row.get(0)?,
+                active: row.get(1)?,
+                name: row.get(2)?,
+            })
+        })?;
+        for income in rows {
+            Income::save_to_store(income?, &mut store.incomes);
+        }
+        Ok(())
+    }
+
+    fn load_payments(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            "SELECT id, completed_at, account_id, amount_id, expense_id FROM payments ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Payment {
+                id: row.get(0)?,
+                completed_at: datetime(row, 1)?,
+                account_id: row.get(2)?,
+                amount_id: row.get(3)?,
+                expense_id: row.get(4)?,
+            })
+        })?;
+        for payment in rows {
+            Payment::save_to_store(payment?, &mut store.payments);
+        }
+        Ok(())
+    }
+
+    fn load_payments_received(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            "SELECT id, completed_at, account_id, income_id, amount_id \
+             FROM payments_received ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PaymentReceived {
+                id: row.get(0)?,
+                completed_at: datetime(row, 1)?,
+                account_id: row.get(2)?,
+                income_id: row.get(3)?,
+                amount_id: row.get(4)?,
+            })
+        })?;
+        for payment_rec in rows {
+            PaymentReceived::save_to_store(payment_rec?, &mut store.payments_received);
+        }
+        Ok(())
+    }
+
+    fn load_tags(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare("SELECT id, content FROM tags ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                content: row.get(1)?,
+            })
+        })?;
+        for tag in rows {
+            Tag::save_to_store(tag?, &mut store.tags);
+        }
+        Ok(())
+    }
+
+    fn load_budgets(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            format!(
+                "SELECT id, name, expense_id, expense_name, tag_id, {}, rollover \
+                 FROM budgets ORDER BY id",
+                exact("limit"),
+            )
+            .as_str(),
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Budget {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                expense_id: row.get(2)?,
+                expense_name: row.get(3)?,
+                tag_id: row.get(4)?,
+                limit: decimal(row, 5)?,
+                rollover: row.get(6)?,
+            })
+        })?;
+        for budget in rows {
+            Budget::save_to_store(budget?, &mut store.budgets);
+        }
+        Ok(())
+    }
+
+    fn load_expense_tags(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt =
+            conn.prepare("SELECT id, expense_id, tag_id FROM expense_tags ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ExpenseTag {
+                id: row.get(0)?,
+                expense_id: row.get(1)?,
+                tag_id: row.get(2)?,
+            })
+        })?;
+        for expense_tag in rows {
+            ExpenseTag::save_to_store(expense_tag?, &mut store.expense_tags);
+        }
+        Ok(())
+    }
+
+    fn load_income_tags(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare("SELECT id, income_id, tag_id FROM income_tags ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(IncomeTag {
+                id: row.get(0)?,
+                income_id: row.get(1)?,
+                tag_id: row.get(2)?,
+            })
+        })?;
+        for income_tag in rows {
+            IncomeTag::save_to_store(income_tag?, &mut store.income_tags);
+        }
+        Ok(())
+    }
+
+    fn load_fx_rates(conn: &Connection, store: &mut Store) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare(
+            format!(
+                "SELECT id, date, pair, {} FROM fx_rates ORDER BY id",
+                exact("rate")
+            )
+            .as_str(),
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FxRate {
+                id: row.get(0)?,
+                date: date(row, 1)?,
+                pair: row.get(2)?,
+                rate: decimal(row, 3)?,
+            })
+        })?;
+        for fx_rate in rows {
+            FxRate::save_to_store(fx_rate?, &mut store.fx_rates);
+        }
+        Ok(())
+    }
+}
+
+fn conversion_error<E: std::error::Error + Send + Sync + 'static>(
+    idx: usize,
+    err: E,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(err))
+}
+
+// a decimal column's exact copy, or the column itself as text in an older database
+fn exact(column: &str) -> String {
+    format!("COALESCE({column}_exact, CAST(\"{column}\" AS TEXT))")
+}
+
+fn decimal(row: &Row, idx: usize) -> rusqlite::Result<Decimal> {
+    let text: String = row.get(idx)?;
+    Decimal::from_str(&text).map_err(|err| conversion_error(idx, err))
+}
+
+fn decimal_option(row: &Row, idx: usize) -> rusqlite::Result<Option<Decimal>> {
+    let text: Option<String> = row.get(idx)?;
+    text.map(|t| Decimal::from_str(&t).map_err(|err| conversion_error(idx, err)))
+        .transpose()
+}
+
+fn datetime(row: &Row, idx: usize) -> rusqlite::Result<NaiveDateTime> {
+    let text: String = row.get(idx)?;
+    NaiveDateTime::from_str(&text).map_err(|err| conversion_error(idx, err))
+}
+
+fn date(row: &Row, idx: usize) -> rusqlite::Result<NaiveDate> {
+    let text: String = row.get(idx)?;
+    NaiveDate::from_str(&text).map_err(|err| conversion_error(idx, err))
+}
+
+// enums are stored by their serde name, e.g. "liability"
+fn enum_to_sql<E: Serialize>(value: &E) -> rusqlite::Result<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => Ok(name),
+        Ok(other) => Err(rusqlite::Error::ToSqlConversionFailure(From::from(
+            format!("Expected enum to serialize as a string, got {other}"),
+        ))),
+        Err(err) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(err))),
+    }
+}
+
+fn enum_from_sql<E: DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<E> {
+    let name: String = row.get(idx)?;
+    serde_json::from_value(serde_json::Value::String(name))
+        .map_err(|err| conversion_error(idx, err))
+}
+
+#[cfg(test)]
+mod sqlite_backend_spec {
+    use super::*;
+    use crate::schema::account::AccountKind;
+    use crate::test::spec::Spec;
+    use std::fs;
+
+    fn db_path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("monthly_budget_{name}_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path.to_str().unwrap().to_string()
+    }
+
+    fn backend(db_path: &str, out_path: &str) -> SqliteBackend {
+        SqliteBackend {
+            db_path: db_path.to_string(),
+            out_path: out_path.to_string(),
+            seed_dir: "src/test/data/init/".to_string(),
+        }
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn load__seeds_from_csv_when_database_does_not_exist() {
+        let other_path = db_path("seed_out");
+        let db_path = db_path("seed");
+        let mut store = Store::new();
+        backend(&db_path, &other_path).load(&mut store).unwrap();
+
+        assert_eq!(2, store.accounts.len());
+        assert_eq!(4, store.account_balances.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn load__reads_the_database_save_does_not_write() {
+        let (db_path, out_path) = (db_path("input"), db_path("input_out"));
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        backend(&out_path, &db_path).save(&store).unwrap();
+
+        let mut simulated = Store::new();
+        Spec::init(&mut simulated);
+        simulated.account_balances.retain(|id, _| *id != 1);
+        let backend = backend(&db_path, &out_path);
+        backend.save(&simulated).unwrap();
+
+        let mut loaded = Store::new();
+        backend.load(&mut loaded).unwrap();
+        let mut saved = Store::new();
+        backend.load_saved(&mut saved).unwrap();
+        fs::remove_file(&db_path).unwrap();
+        fs::remove_file(&out_path).unwrap();
+
+        assert_eq!(4, loaded.account_balances.len());
+        assert_eq!(3, saved.account_balances.len());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn load__returns_err_when_the_database_is_also_the_output() {
+        let other_path = db_path("in_place_in");
+        let db_path = db_path("in_place");
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        backend(&other_path, &db_path).save(&store).unwrap();
+
+        let mut loaded = Store::new();
+        let result = backend(&db_path, &db_path).load(&mut loaded);
+        fs::remove_file(&db_path).unwrap();
+        assert!(matches!(result, Err(BudgetError::Sqlite { .. })));
+        assert!(loaded.accounts.is_empty());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn out_path__keeps_the_file_name_in_the_out_dir() {
+        assert_eq!(
+            "data/reports/budget.db",
+            SqliteBackend::out_path("data/budget.db", "data/reports")
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn save__round_trips_every_table() {
+        let other_path = db_path("round_trip_in");
+        let db_path = db_path("round_trip");
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.accounts.update(2, |account| {
+            account.kind = AccountKind::Liability;
+            account.credit_limit = Some(Decimal::new(150050, 2));
+            account.currency = Some("EUR".to_string());
+        });
+        if let Some(amount) = store.amounts.get_mut(&1) {
+            amount.standard = Decimal::new(310000, 2);
+            amount.currency = Some("EUR".to_string());
+        }
+        FxRate::save_to_store(
+            FxRate {
+                id: None,
+                date: NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+                pair: "EUR/USD".to_string(),
+                rate: Decimal::new(10875, 4),
+            },
+            &mut store.fx_rates,
+        );
+        backend(&other_path, &db_path).save(&store).unwrap();
+
+        let mut loaded = Store::new();
+        backend(&db_path, &other_path).load(&mut loaded).unwrap();
+        fs::remove_file(&db_path).unwrap();
+
+        assert_eq!(store.accounts.len(), loaded.accounts.len());
+        assert_eq!(store.account_balances.len(), loaded.account_balances.len());
+        assert_eq!(store.amounts.len(), loaded.amounts.len());
+        assert_eq!(store.expenses.len(), loaded.expenses.len());
+        assert_eq!(store.incomes.len(), loaded.incomes.len());
+        assert_eq!(store.payments.len(), loaded.payments.len());
+        assert_eq!(
+            store.payments_received.len(),
+            loaded.payments_received.len()
+        );
+        assert_eq!(store.tags.len(), loaded.tags.len());
+        assert_eq!(store.budgets.len(), loaded.budgets.len());
+        assert_eq!(store.expense_tags.len(), loaded.expense_tags.len());
+        assert_eq!(store.income_tags.len(), loaded.income_tags.len());
+        assert_eq!(1, loaded.fx_rates.len());
+
+        let card = &loaded.accounts[&2];
+        assert_eq!(AccountKind::Liability, card.kind);
+        assert_eq!(Some(Decimal::new(150050, 2)), card.credit_limit);
+        assert_eq!("EUR", card.currency());
+        assert_eq!(Some("EUR".to_string()), loaded.amounts[&1].currency);
+        assert_eq!("1.0875", loaded.fx_rates[&1].rate.to_string());
+        // exact decimal, scale included
+        assert_eq!("3100.00", loaded.amounts[&1].standard.to_string());
+        assert_eq!(
+            store.payments[&1].completed_at,
+            loaded.payments[&1].completed_at
+        );
+        assert_eq!(Some(1), loaded.accounts.first_id("piggybank"));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn save__stores_decimals_a_sql_client_can_compare_and_sum() {
+        let other_path = db_path("numeric_in");
+        let db_path = db_path("numeric");
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.account_balances.update(3, |balance| {
+            balance.amount = Decimal::new(-2550, 2);
+        });
+        backend(&other_path, &db_path).save(&store).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let negative: Vec<usize> = conn
+            .prepare("SELECT id FROM account_balances WHERE amount < 0")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<usize>>>()
+            .unwrap();
+        let (total, largest): (f64, usize) = conn
+            .query_row(
+                "SELECT SUM(amount), \
+                 (SELECT id FROM account_balances ORDER BY amount DESC LIMIT 1) \
+                 FROM account_balances",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        let mut loaded = Store::new();
+        backend(&db_path, &other_path).load(&mut loaded).unwrap();
+        fs::remove_file(&db_path).unwrap();
+
+        assert_eq!(vec![3], negative);
+        assert_eq!(674.5, total);
+        assert_eq!(4, largest);
+        // the exact copy keeps the scale
+        assert_eq!("-25.50", loaded.account_balances[&3].amount.to_string());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn load__reads_decimals_from_a_database_saved_as_text() {
+        let other_path = db_path("text_decimals_out");
+        let db_path = db_path("text_decimals");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE accounts (id INTEGER PRIMARY KEY, name TEXT NOT NULL, \
+             kind TEXT NOT NULL, credit_limit TEXT, statement_closing_day INTEGER, \
+             payment_due_day INTEGER, funding_account_id INTEGER, interest_rate TEXT, \
+             compounding TEXT NOT NULL);
+             INSERT INTO accounts (id, name, kind, credit_limit, compounding) \
+             VALUES (1, 'card', 'liability', '1500.50', 'none');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut loaded = Store::new();
+        backend(&db_path, &other_path).load(&mut loaded).unwrap();
+        fs::remove_file(&db_path).unwrap();
+
+        assert_eq!(
+            Some(Decimal::new(150050, 2)),
+            loaded.accounts[&1].credit_limit
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn save__returns_err_on_missing_foreign_key() {
+        let other_path = db_path("foreign_key_in");
+        let db_path = db_path("foreign_key");
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        store.payments.update(1, |payment| {
+            payment.account_id = 99;
+        });
+
+        assert!(backend(&other_path, &db_path).save(&store).is_err());
+        fs::remove_file(&db_path).unwrap();
+    }
+}
@@ -1,26 +1,36 @@
+use crate::error::budget_error::BudgetError;
 use crate::schema::account::{Account, AccountStore};
 use crate::schema::account_balance::{AccountBalance, AccountBalanceStore};
 use crate::schema::amount::{Amount, AmountStore};
+use crate::schema::budget::{Budget, BudgetStore};
 use crate::schema::expense::{Expense, ExpenseStore};
+use crate::schema::expense_tag::{ExpenseTag, ExpenseTagStore};
+use crate::schema::fx_rate::{FxRate, FxRateStore};
 use crate::schema::income::{Income, IncomeStore};
+use crate::schema::income_tag::{IncomeTag, IncomeTagStore};
 use crate::schema::payment::{Payment, PaymentStore};
 use crate::schema::payment_received::{PaymentReceived, PaymentReceivedStore};
-use crate::traits::csv_store::{CsvReadResult, CsvStore, CsvWriteResult};
-use std::error::Error;
+use crate::schema::tag::{Tag, TagStore};
+use crate::traits::csv_store::{CsvStore, CsvWriteResult};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Store {
     pub accounts: AccountStore,
     pub account_balances: AccountBalanceStore,
     pub amounts: AmountStore,
+    pub budgets: BudgetStore,
     pub expenses: ExpenseStore,
+    pub expense_tags: ExpenseTagStore,
+    pub fx_rates: FxRateStore,
     pub incomes: IncomeStore,
+    pub income_tags: IncomeTagStore,
     pub payments: PaymentStore,
     pub payments_received: PaymentReceivedStore,
+    pub tags: TagStore,
 }
 
-pub type StoreInitResult<'a> = Result<&'a mut Store, Box<dyn Error>>;
-pub type StoreWriteResult = Result<(), Box<dyn Error>>;
+pub type StoreInitResult<'a> = Result<&'a mut Store, BudgetError>;
+pub type StoreWriteResult = Result<(), BudgetError>;
 
 impl Store {
     pub fn new() -> Store {
@@ -28,61 +38,244 @@ impl Store {
             accounts: AccountStore::new(),
             account_balances: AccountBalanceStore::new(),
             amounts: AmountStore::new(),
+            budgets: BudgetStore::new(),
             expenses: ExpenseStore::new(),
+            expense_tags: ExpenseTagStore::new(),
+            fx_rates: FxRateStore::new(),
             incomes: IncomeStore::new(),
+            income_tags: IncomeTagStore::new(),
             payments: PaymentStore::new(),
             payments_received: PaymentReceivedStore::new(),
+            tags: TagStore::new(),
         }
     }
 
-    pub fn init(&mut self, dir: Option<String>) -> StoreInitResult {
+    pub fn init(&mut self, dir: Option<String>) -> StoreInitResult<'_> {
         let path: String = match dir {
             None => "data".to_string(),
             Some(root) => root,
         };
 
-        let import_res: [CsvReadResult; 7] = [
-            Account::init_store(
+        // the first failing table, its error carries the file, row and column
+        // a repeated id keeps the first row, as it always has
+        if let Some(err) = self.read_tables(&path, false).into_iter().next() {
+            return Err(err);
+        }
+
+        Ok(self)
+    }
+
+    // every bad row of every table, validate reports them all, with report_duplicates
+    pub fn read_tables(&mut self, path: &str, report_duplicates: bool) -> Vec<BudgetError> {
+        let errors: [Vec<BudgetError>; 12] = [
+            Account::read_rows(
                 &mut self.accounts,
                 format!("{path}/{}", "accounts.csv").as_str(),
+                report_duplicates,
             ),
-            AccountBalance::init_store(
+            AccountBalance::read_rows(
                 &mut self.account_balances,
                 format!("{path}/{}", "account_balances.csv").as_str(),
+                report_duplicates,
             ),
-            Amount::init_store(
+            Amount::read_rows(
                 &mut self.amounts,
                 format!("{path}/{}", "amounts.csv").as_str(),
+                report_duplicates,
             ),
-            Expense::init_store(
+            Expense::read_rows(
                 &mut self.expenses,
                 format!("{path}/{}", "expenses.csv").as_str(),
+                report_duplicates,
             ),
-            Income::init_store(
+            Income::read_rows(
                 &mut self.incomes,
                 format!("{path}/{}", "incomes.csv").as_str(),
+                report_duplicates,
             ),
-            Payment::init_store(
+            Payment::read_rows(
                 &mut self.payments,
                 format!("{path}/{}", "payments.csv").as_str(),
+                report_duplicates,
             ),
-            PaymentReceived::init_store(
+            PaymentReceived::read_rows(
                 &mut self.payments_received,
                 format!("{path}/{}", "payments_received.csv").as_str(),
+                report_duplicates,
+            ),
+            // optional tables
+            Budget::read_rows_if_exists(
+                &mut self.budgets,
+                format!("{path}/{}", "budgets.csv").as_str(),
+                report_duplicates,
+            ),
+            ExpenseTag::read_rows_if_exists(
+                &mut self.expense_tags,
+                format!("{path}/{}", "expense_tags.csv").as_str(),
+                report_duplicates,
+            ),
+            FxRate::read_rows_if_exists(
+                &mut self.fx_rates,
+                format!("{path}/{}", "fx_rates.csv").as_str(),
+                report_duplicates,
+            ),
+            IncomeTag::read_rows_if_exists(
+                &mut self.income_tags,
+                format!("{path}/{}", "income_tags.csv").as_str(),
+                report_duplicates,
+            ),
+            Tag::read_rows_if_exists(
+                &mut self.tags,
+                format!("{path}/{}", "tags.csv").as_str(),
+                report_duplicates,
             ),
         ];
+        errors.into_iter().flatten().collect()
+    }
 
-        for res in import_res.iter() {
-            if let Err(err) = res {
-                return Err(From::from(format!("Init Store Error: {err}")));
+    // foreign keys naming a record that is not in the store
+    pub fn dangling_references(&self) -> Vec<String> {
+        // (file, id, column, foreign key, record exists)
+        let mut references: Vec<(&str, usize, &str, usize, bool)> = vec![];
+        for (id, account) in self.accounts.iter() {
+            if let Some(funding_account_id) = account.funding_account_id {
+                references.push((
+                    "accounts.csv",
+                    *id,
+                    "funding_account_id",
+                    funding_account_id,
+                    self.accounts.contains_key(&funding_account_id),
+                ));
             }
         }
-        Ok(self)
+        for (id, account_balance) in self.account_balances.iter() {
+            references.push((
+                "account_balances.csv",
+                *id,
+                "account_id",
+                account_balance.account_id,
+                self.accounts.contains_key(&account_balance.account_id),
+            ));
+        }
+        for (id, payment) in self.payments.iter() {
+            references.extend([
+                (
+                    "payments.csv",
+                    *id,
+                    "account_id",
+                    payment.account_id,
+                    self.accounts.contains_key(&payment.account_id),
+                ),
+                (
+                    "payments.csv",
+                    *id,
+                    "amount_id",
+                    payment.amount_id,
+                    self.amounts.contains_key(&payment.amount_id),
+                ),
+                (
+                    "payments.csv",
+                    *id,
+                    "expense_id",
+                    payment.expense_id,
+                    self.expenses.contains_key(&payment.expense_id),
+                ),
+            ]);
+        }
+        for (id, payment_received) in self.payments_received.iter() {
+            references.extend([
+                (
+                    "payments_received.csv",
+                    *id,
+                    "account_id",
+                    payment_received.account_id,
+                    self.accounts.contains_key(&payment_received.account_id),
+                ),
+                (
+                    "payments_received.csv",
+                    *id,
+                    "amount_id",
+                    payment_received.amount_id,
+                    self.amounts.contains_key(&payment_received.amount_id),
+                ),
+                (
+                    "payments_received.csv",
+                    *id,
+                    "income_id",
+                    payment_received.income_id,
+                    self.incomes.contains_key(&payment_received.income_id),
+                ),
+            ]);
+        }
+        for (id, budget) in self.budgets.iter() {
+            if let Some(expense_id) = budget.expense_id {
+                references.push((
+                    "budgets.csv",
+                    *id,
+                    "expense_id",
+                    expense_id,
+                    self.expenses.contains_key(&expense_id),
+                ));
+            }
+            if let Some(tag_id) = budget.tag_id {
+                references.push((
+                    "budgets.csv",
+                    *id,
+                    "tag_id",
+                    tag_id,
+                    self.tags.contains_key(&tag_id),
+                ));
+            }
+        }
+        for (id, expense_tag) in self.expense_tags.iter() {
+            references.extend([
+                (
+                    "expense_tags.csv",
+                    *id,
+                    "expense_id",
+                    expense_tag.expense_id,
+                    self.expenses.contains_key(&expense_tag.expense_id),
+                ),
+                (
+                    "expense_tags.csv",
+                    *id,
+                    "tag_id",
+                    expense_tag.tag_id,
+                    self.tags.contains_key(&expense_tag.tag_id),
+                ),
+            ]);
+        }
+        for (id, income_tag) in self.income_tags.iter() {
+            references.extend([
+                (
+                    "income_tags.csv",
+                    *id,
+                    "income_id",
+                    income_tag.income_id,
+                    self.incomes.contains_key(&income_tag.income_id),
+                ),
+                (
+                    "income_tags.csv",
+                    *id,
+                    "tag_id",
+                    income_tag.tag_id,
+                    self.tags.contains_key(&income_tag.tag_id),
+                ),
+            ]);
+        }
+
+        references
+            .into_iter()
+            .filter(|(_, _, _, _, exists)| !exists)
+            .map(|(file, id, column, foreign_key, _)| {
+                format!("{file} id {id}: {column} {foreign_key} does not exist")
+            })
+            .collect()
     }
 
     pub fn write_to_csv(&self, dir: Option<String>) -> StoreWriteResult {
         let path = dir.unwrap_or_else(|| "data/reports".to_string());
-        let write_res: [CsvWriteResult; 7] = [
+        let write_res: [CsvWriteResult; 12] = [
             Account::write_to_csv(
                 &self.accounts,
                 format!("{path}/{}", "accounts.csv").as_str(),
@@ -92,11 +285,24 @@ impl Store {
                 format!("{path}/{}", "account_balances.csv").as_str(),
             ),
             Amount::write_to_csv(&self.amounts, format!("{path}/{}", "amounts.csv").as_str()),
+            Budget::write_to_csv(&self.budgets, format!("{path}/{}", "budgets.csv").as_str()),
             Expense::write_to_csv(
                 &self.expenses,
                 format!("{path}/{}", "expenses.csv").as_str(),
             ),
+            ExpenseTag::write_to_csv(
+                &self.expense_tags,
+                format!("{path}/{}", "expense_tags.csv").as_str(),
+            ),
+            FxRate::write_to_csv(
+                &self.fx_rates,
+                format!("{path}/{}", "fx_rates.csv").as_str(),
+            ),
             Income::write_to_csv(&self.incomes, format!("{path}/{}", "incomes.csv").as_str()),
+            IncomeTag::write_to_csv(
+                &self.income_tags,
+                format!("{path}/{}", "income_tags.csv").as_str(),
+            ),
             Payment::write_to_csv(
                 &self.payments,
                 format!("{path}/{}", "payments.csv").as_str(),
@@ -105,14 +311,120 @@ impl Store {
                 &self.payments_received,
                 format!("{path}/{}", "payments_received.csv").as_str(),
             ),
+            Tag::write_to_csv(&self.tags, format!("{path}/{}", "tags.csv").as_str()),
         ];
 
-        for res in write_res.iter() {
-            if let Err(err) = res {
-                return Err(From::from(format!("Csv Store Write Error: {err}")));
-            }
+        for res in write_res.into_iter() {
+            res?;
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod store_spec {
+    use super::*;
+    use crate::test::spec::Spec;
+    use std::fs;
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn dangling_references__lists_foreign_keys_without_a_record() {
+        let mut store = Store::new();
+        Spec::init(&mut store);
+        assert!(store.dangling_references().is_empty());
+
+        let payment_id = *store.payments.keys().next().unwrap();
+        store
+            .payments
+            .update(payment_id, |payment| payment.account_id = 99);
+        assert_eq!(
+            vec![format!(
+                "payments.csv id {payment_id}: account_id 99 does not exist"
+            )],
+            store.dangling_references()
+        );
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn read_tables__reports_every_bad_row_and_duplicate_id() {
+        let dir = format!(
+            "{}/monthly_budget_read_tables",
+            std::env::temp_dir().display()
+        );
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for entry in fs::read_dir("src/test/data/init").unwrap() {
+            let entry = entry.unwrap();
+            fs::copy(
+                entry.path(),
+                format!("{dir}/{}", entry.file_name().to_str().unwrap()),
+            )
+            .unwrap();
+        }
+        fs::write(
+            format!("{dir}/accounts.csv"),
+            "id,name\n1,piggybank\n1,swearjar\n",
+        )
+        .unwrap();
+        fs::write(
+            format!("{dir}/tags.csv"),
+            "id,content\nx,housing\n2,work\n,side\n",
+        )
+        .unwrap();
+
+        let mut store = Store::new();
+        let errors: Vec<String> = store
+            .read_tables(&dir, true)
+            .iter()
+            .map(|err| err.to_string())
+            .collect();
+        assert_eq!(3, errors.len());
+        assert!(errors[0].ends_with(r#"accounts.csv row 3 column "id": duplicate id 1"#));
+        assert!(errors[1].contains(r#"tags.csv row 2 column "id""#));
+        assert!(errors[2].ends_with(r#"tags.csv row 4 column "id": missing id"#));
+        // the rows that parsed are still loaded
+        assert_eq!(1, store.accounts.len());
+        assert_eq!(1, store.tags.len());
+        assert!(Store::new().init(Some(dir)).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn init__keeps_the_first_row_of_a_duplicate_id() {
+        use std::fs;
+
+        let dir = format!(
+            "{}/monthly_budget_init_duplicates_{}",
+            std::env::temp_dir().display(),
+            std::process::id()
+        );
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for entry in fs::read_dir("src/test/data/init").unwrap() {
+            let entry = entry.unwrap();
+            fs::copy(
+                entry.path(),
+                format!("{dir}/{}", entry.file_name().to_str().unwrap()),
+            )
+            .unwrap();
+        }
+        fs::write(
+            format!("{dir}/accounts.csv"),
+            "id,name\n1,piggybank\n1,swearjar\n",
+        )
+        .unwrap();
+
+        let mut store = Store::new();
+        store.init(Some(dir.clone())).unwrap();
+        assert_eq!(1, store.accounts.len());
+        assert_eq!("piggybank", store.accounts[&1].name);
+        // validate still reports it
+        let errors = Store::new().read_tables(&dir, true);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(1, errors.len());
+        assert!(errors[0].to_string().ends_with("duplicate id 1"));
+    }
+}
@@ -1,19 +1,33 @@
-use crate::app::cli::Cli;
+use crate::app::cli::{SimulateArgs, StoreArgs};
+use crate::calendar::year_month::YearMonth as YM;
 use crate::programs::calendar_slice_model::CalendarSliceModel;
+use crate::schema::fx_rate::DEFAULT_CURRENCY;
+use crate::storage::backend::StoreKind;
 
 pub struct CsmTest;
 
 impl CsmTest {
-    #[allow(unused)] // used in tests below
-    pub fn run(start: String, end: String) -> u8 {
-        let cli = Cli::new(
-            "src/test/end_to_end/calendar_slice_model/data".to_string(),
-            start,           // start
-            end,             // end
-            "f".to_string(), // test t/f - used to call this test from main
-        );
+    pub fn run(start: YM, end: YM) -> u8 {
+        let args = SimulateArgs {
+            start,
+            end,
+            monte_carlo_runs: 0,
+            seed: 0,
+            dry_run: false,
+            fail_on_overdraft: false,
+            actuals_through: None,
+            strict: false,
+            scenarios: vec![],
+            base_currency: DEFAULT_CURRENCY.to_string(),
+            store: StoreArgs {
+                store: StoreKind::Csv,
+            },
+        };
         println!("CSMTEST RUN");
-        match CalendarSliceModel::run_cli(cli) {
+        match CalendarSliceModel::run_cli(
+            "src/test/end_to_end/calendar_slice_model/data".to_string(),
+            args,
+        ) {
             Ok(_) => 0,
             Err(_) => 1,
         }
@@ -23,7 +37,11 @@ impl CsmTest {
 #[cfg(test)]
 mod calendar_slice_model_e2e {
     use super::*;
+    use crate::composite::alert::{Alert, AlertKind, AlertStore};
+    use crate::composite::budget_summary::{BudgetSummary, BudgetSummaryStore};
+    use crate::composite::tag_summary::{TagSummary, TagSummaryStore};
     use crate::storage::store::Store;
+    use crate::traits::csv_store::CsvStore;
     use rust_decimal::Decimal;
     use std::fs;
     use std::sync::Once;
@@ -37,7 +55,10 @@ mod calendar_slice_model_e2e {
         unsafe {
             INIT.call_once(|| {
                 clean_up_previous_data();
-                RES = CsmTest::run("2023-03".to_string(), "2023-06".to_string());
+                RES = CsmTest::run(
+                    YM::try_parse("2023-03").unwrap(),
+                    YM::try_parse("2023-06").unwrap(),
+                );
             });
             RES
         }
@@ -61,7 +82,8 @@ mod calendar_slice_model_e2e {
 
         let final_balance = store.account_balances[&37];
         assert_eq!(2, final_balance.account_id);
-        assert_eq!(Decimal::new(31_900, 0), final_balance.amount);
+        // 2023-06-15 pays Cowboy and buys Groceries at the same noon, both count
+        assert_eq!(Decimal::new(32_900, 0), final_balance.amount);
     }
 
     #[test]
@@ -74,7 +96,7 @@ mod calendar_slice_model_e2e {
         let final_payment = store.payments[&8];
         assert_eq!(
             Decimal::new(200, 0),
-            final_payment.amount(&mut store.amounts).unwrap().standard
+            final_payment.amount(&store.amounts).unwrap().standard
         );
     }
 
@@ -89,7 +111,7 @@ mod calendar_slice_model_e2e {
         assert_eq!(
             Decimal::new(5000, 0),
             final_payment_received
-                .amount(&mut store.amounts)
+                .amount(&store.amounts)
                 .unwrap()
                 .standard
         );
@@ -128,6 +150,70 @@ mod calendar_slice_model_e2e {
         assert_eq!("Cowboy".to_string(), store.incomes[&2].name);
     }
 
+    #[test]
+    fn check_budget_vs_actual() {
+        run_test();
+        let mut march = BudgetSummaryStore::new();
+        BudgetSummary::init_store(
+            &mut march,
+            format!("{STORE_INIT}2023_03_budget_vs_actual.csv").as_str(),
+        )
+        .unwrap();
+        let car_repair = &march[&2];
+        assert_eq!(Decimal::new(400, 0), car_repair.actual);
+        assert_eq!(Decimal::new(-100, 0), car_repair.remaining);
+
+        let mut may = BudgetSummaryStore::new();
+        BudgetSummary::init_store(
+            &mut may,
+            format!("{STORE_INIT}2023_05_budget_vs_actual.csv").as_str(),
+        )
+        .unwrap();
+        // groceries: 4 x 200 in april leaves 200 to roll into may
+        let groceries = &may[&1];
+        assert_eq!(Decimal::new(200, 0), groceries.rollover_in);
+        assert_eq!(Decimal::new(400, 0), groceries.remaining);
+        // car repair does not roll over
+        assert_eq!(Decimal::new(0, 0), may[&2].rollover_in);
+    }
+
+    #[test]
+    fn check_tag_summary() {
+        run_test();
+        let mut tag_summary = TagSummaryStore::new();
+        TagSummary::init_store(
+            &mut tag_summary,
+            format!("{STORE_INIT}tag_summary.csv").as_str(),
+        )
+        .unwrap();
+        let by_name = |name: &str| {
+            tag_summary
+                .values()
+                .find(|summary| summary.name == name)
+                .unwrap()
+                .clone()
+        };
+
+        // 18 x 200 groceries + 4 x 2000 mortgage
+        assert_eq!(Decimal::new(11_600, 0), by_name("household").expense_total);
+        assert_eq!(Decimal::new(8_000, 0), by_name("housing").expense_total);
+        assert_eq!(Decimal::new(40_000, 0), by_name("work").income_total);
+    }
+
+    #[test]
+    fn check_alerts() {
+        run_test();
+        let mut alerts = AlertStore::new();
+        Alert::init_store(&mut alerts, format!("{STORE_INIT}alerts.csv").as_str()).unwrap();
+
+        // credit union: 1000 - 200 groceries - 2000 mortgage, then every debit until payday
+        assert_eq!(3, alerts.len());
+        assert_eq!(AlertKind::Overdraft, alerts[&1].kind);
+        assert_eq!("Mortgage", alerts[&1].event_name);
+        assert_eq!(Decimal::new(1_200, 0), alerts[&1].shortfall);
+        assert_eq!("Car Repair", alerts[&3].event_name);
+    }
+
     #[test]
     fn check_amounts() {
         run_test();
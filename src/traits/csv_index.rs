@@ -0,0 +1,9 @@
+use std::fmt::Debug;
+
+// column a CsvRecord is looked up by, see storage::indexed_store::IndexedStore
+// change the column through IndexedStore::update so the record is reindexed
+pub trait CsvIndex {
+    type Key: Ord + Clone + Debug;
+
+    fn index_key(&self) -> Self::Key;
+}
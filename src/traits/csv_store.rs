@@ -1,92 +1,182 @@
-use crate::error::error_handler::ErrorHandler;
+use crate::error::budget_error::BudgetError;
 use crate::traits::csv_record::CsvRecord;
-use csv::Reader;
+use csv::{Reader, StringRecord};
 use serde::{Deserialize, Serialize};
-use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
-use std::error::Error;
 use std::fs::File;
+use std::path::Path;
 
-pub type CsvReadResult = Result<(), Box<dyn Error>>;
-pub type CsvWriteResult = Result<(), Box<dyn Error>>;
+pub type CsvReadResult = Result<(), BudgetError>;
+pub type CsvWriteResult = Result<(), BudgetError>;
+
+// what rows are read and records saved into, a plain BTreeMap or an IndexedStore
+pub trait CsvRecords<T> {
+    fn records(&self) -> &BTreeMap<usize, T>;
+
+    fn insert_record(&mut self, id: usize, record: T);
+}
+
+impl<T> CsvRecords<T> for BTreeMap<usize, T> {
+    fn records(&self) -> &BTreeMap<usize, T> {
+        self
+    }
+
+    fn insert_record(&mut self, id: usize, record: T) {
+        self.insert(id, record);
+    }
+}
 
 pub trait CsvStore<
     T: for<'a> Deserialize<'a> + for<'a> Serialize + std::fmt::Debug + CsvRecord<T> + CsvStore<T>,
 >
 {
-    fn init_store(store: &mut BTreeMap<usize, T>, csv_path: &str) -> CsvReadResult {
-        let file = File::open(csv_path)?;
+    // a repeated id keeps the first row, validate reports it
+    fn init_store<S: CsvRecords<T>>(store: &mut S, csv_path: &str) -> CsvReadResult {
+        match T::read_rows(store, csv_path, false).into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    // optional tables, data dirs created before they existed will not have them
+    fn init_store_if_exists<S: CsvRecords<T>>(store: &mut S, csv_path: &str) -> CsvReadResult {
+        if !Path::new(csv_path).exists() {
+            return Ok(());
+        }
+        T::init_store(store, csv_path)
+    }
+
+    // loads every row that parses, one error per row that does not
+    // a missing id is an error, a repeated id keeps the first row
+    // and is an error only with report_duplicates
+    fn read_rows<S: CsvRecords<T>>(
+        store: &mut S,
+        csv_path: &str,
+        report_duplicates: bool,
+    ) -> Vec<BudgetError> {
+        let file = match File::open(csv_path) {
+            Err(err) => return vec![BudgetError::csv(csv_path, None, err.into())],
+            Ok(file) => file,
+        };
         let mut reader = Reader::from_reader(file);
+        let headers = match reader.headers() {
+            Err(err) => return vec![BudgetError::csv(csv_path, None, err)],
+            Ok(headers) => headers.clone(),
+        };
 
-        for result in reader.deserialize() {
-            match result {
-                Err(err) => return Err(From::from(err)),
-                Ok(res) => {
-                    let record: T = res; // type hint for .deserialize
-                    let id: usize = record.id().unwrap();
-                    store.entry(id).or_insert(record);
+        let mut errors: Vec<BudgetError> = vec![];
+        let mut row = StringRecord::new();
+        loop {
+            match reader.read_record(&mut row) {
+                Ok(false) => break,
+                Ok(true) => (),
+                // the reader cannot recover its place, stop at the first one
+                Err(err) => {
+                    errors.push(BudgetError::csv(csv_path, None, err));
+                    break;
                 }
             }
+            let id_error = |message: String| BudgetError::Csv {
+                file: csv_path.to_string(),
+                row: row.position().map(|position| position.line()),
+                column: Some("id".to_string()),
+                message,
+            };
+            let record: T = match row.deserialize(Some(&headers)) {
+                Err(err) => {
+                    errors.push(BudgetError::csv(csv_path, Some(&headers), err));
+                    continue;
+                }
+                Ok(record) => record,
+            };
+            match record.id() {
+                None => errors.push(id_error("missing id".to_string())),
+                Some(id) if store.records().contains_key(&id) => {
+                    if report_duplicates {
+                        errors.push(id_error(format!("duplicate id {id}")));
+                    }
+                }
+                Some(id) => store.insert_record(id, record),
+            }
         }
-        Ok(())
+        errors
     }
 
-    fn write_to_csv(store: &BTreeMap<usize, T>, path: &str) -> Result<(), Box<dyn Error>> {
-        let mut wtr = csv::Writer::from_path(path)?;
+    fn read_rows_if_exists<S: CsvRecords<T>>(
+        store: &mut S,
+        csv_path: &str,
+        report_duplicates: bool,
+    ) -> Vec<BudgetError> {
+        if !Path::new(csv_path).exists() {
+            return vec![];
+        }
+        T::read_rows(store, csv_path, report_duplicates)
+    }
+
+    fn write_to_csv(store: &BTreeMap<usize, T>, path: &str) -> CsvWriteResult {
+        let mut wtr =
+            csv::Writer::from_path(path).map_err(|err| BudgetError::csv(path, None, err))?;
 
         for (_id, record) in store.iter() {
-            wtr.serialize(record.clone_record())?;
+            wtr.serialize(record.clone_record())
+                .map_err(|err| BudgetError::csv(path, None, err))?;
         }
 
-        wtr.flush()?;
+        wtr.flush()
+            .map_err(|err| BudgetError::csv(path, None, err.into()))?;
         Ok(())
     }
 
+    // keys are sorted, the last is the largest
     fn new_id(csv_store: &BTreeMap<usize, T>) -> usize {
-        let mut max_id: usize = 0;
-        for id in csv_store.keys() {
-            if *id > max_id {
-                max_id = *id
-            }
-        }
-        max_id + 1
+        csv_store.keys().next_back().map_or(1, |id| id + 1)
     }
 
-    fn save_to_store(mut record: T, csv_store: &mut BTreeMap<usize, T>) -> usize {
+    fn save_to_store<S: CsvRecords<T>>(mut record: T, csv_store: &mut S) -> usize {
         // returns id newly saved record
-        #[allow(unused_assignments)]
-        let mut new_id: usize = 0;
-        match record.id() {
+        let new_id = match record.id() {
             None => {
-                new_id = T::new_id(csv_store);
+                let new_id = T::new_id(csv_store.records());
                 record.set_id(new_id);
-                csv_store.entry(new_id).or_insert(record);
-            }
-            Some(id) => {
-                new_id = id;
-                csv_store.entry(id).or_insert(record);
-            }
-        }
-
-        if let Entry::Vacant(_) = csv_store.entry(new_id) {
-            ErrorHandler::log(From::from(format!(
-                "Error saving new {:?} record: {:?}",
-                std::any::type_name::<T>(),
                 new_id
-            )));
-            return 0;
+            }
+            Some(id) => id,
+        };
+        // an existing record with the id is kept
+        if !csv_store.records().contains_key(&new_id) {
+            csv_store.insert_record(new_id, record);
         }
-
         new_id
     }
 
-    fn by_id(id: usize, csv_store: &mut BTreeMap<usize, T>) -> Option<T> {
-        match csv_store.entry(id) {
-            Entry::Vacant(_) => None,
-            Entry::Occupied(record) => {
-                let rec: T = record.get().clone_record();
-                Some(rec)
-            }
+    fn by_id<S: CsvRecords<T>>(id: usize, csv_store: &mut S) -> Option<T> {
+        csv_store
+            .records()
+            .get(&id)
+            .map(|record| record.clone_record())
+    }
+}
+
+#[cfg(test)]
+mod csv_store_spec {
+    use super::*;
+    use crate::schema::tag::{Tag, TagStore};
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn new_id__returns_one_past_the_largest_id() {
+        let mut store = TagStore::new();
+        assert_eq!(1, Tag::new_id(&store));
+
+        for id in [3, 7] {
+            Tag::save_to_store(
+                Tag {
+                    id: Some(id),
+                    content: format!("tag {id}"),
+                },
+                &mut store,
+            );
         }
+        assert_eq!(8, Tag::new_id(&store));
     }
 }
@@ -1,3 +1,5 @@
+pub mod csv_index;
 pub mod csv_record;
 pub mod csv_store;
 pub mod file_io;
+pub mod tag_link;
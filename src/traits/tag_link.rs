@@ -0,0 +1,48 @@
+use crate::schema::tag::{Tag, TagStore};
+use crate::traits::csv_record::CsvRecord;
+use crate::traits::csv_store::CsvStore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// join table rows linking a record, e.g. an Expense or Income, to a Tag
+pub trait TagLink<
+    T: for<'a> Deserialize<'a>
+        + for<'a> Serialize
+        + std::fmt::Debug
+        + CsvRecord<T>
+        + CsvStore<T>
+        + TagLink<T>,
+>
+{
+    fn new_link(record_id: usize, tag_id: usize) -> T;
+
+    fn record_id(&self) -> usize;
+
+    fn tag_id(&self) -> usize;
+
+    fn tagged_ids(tag_id: usize, store: &BTreeMap<usize, T>) -> Vec<usize> {
+        store
+            .values()
+            .filter(|link| link.tag_id() == tag_id)
+            .map(|link| link.record_id())
+            .collect()
+    }
+
+    // tag record with each of tag_names, creating Tags as needed
+    fn tag_record(
+        record_id: usize,
+        tag_names: &[String],
+        tags: &mut TagStore,
+        store: &mut BTreeMap<usize, T>,
+    ) {
+        for tag_name in tag_names.iter() {
+            let tag_id = Tag::find_or_create(tag_name, tags);
+            let exists = store
+                .values()
+                .any(|link| link.record_id() == record_id && link.tag_id() == tag_id);
+            if !exists {
+                T::save_to_store(T::new_link(record_id, tag_id), store);
+            }
+        }
+    }
+}